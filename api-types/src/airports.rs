@@ -0,0 +1,158 @@
+// Trajets desservant les aéroports (JFK, LaGuardia, Newark)
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "diesel")]
+use diesel::deserialize::QueryableByName;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "UPPERCASE")]
+pub enum AirportCode {
+    Jfk,
+    Lga,
+    Ewr,
+}
+
+impl AirportCode {
+    pub const ALL: [AirportCode; 3] = [AirportCode::Jfk, AirportCode::Lga, AirportCode::Ewr];
+
+    pub fn code(self) -> &'static str {
+        match self {
+            AirportCode::Jfk => "JFK",
+            AirportCode::Lga => "LGA",
+            AirportCode::Ewr => "EWR",
+        }
+    }
+
+    // `LocationID` TLC de la zone de l'aéroport
+    pub fn location_id(self) -> i32 {
+        match self {
+            AirportCode::Jfk => 132,
+            AirportCode::Lga => 138,
+            AirportCode::Ewr => 1,
+        }
+    }
+}
+
+// Sens du trajet par rapport à l'aéroport
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum AirportDirection {
+    To,
+    From,
+}
+
+impl AirportDirection {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AirportDirection::To => "to",
+            AirportDirection::From => "from",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct AirportParams {
+    // Un seul aéroport (tous par défaut)
+    pub airport: Option<AirportCode>,
+    // Vers ou depuis l'aéroport (les deux par défaut)
+    pub direction: Option<AirportDirection>,
+}
+
+// Vue d'ensemble par aéroport et par sens
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "diesel", derive(QueryableByName))]
+pub struct AirportSummary {
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Text))]
+    pub airport: String,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Text))]
+    pub direction: String,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::BigInt))]
+    pub trip_count: i64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_fare: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_total_amount: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_distance: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_duration_seconds: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_tolls: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_airport_fee: f64,
+    // Parts des péages et de la taxe aéroport dans le montant total
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub tolls_share: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub airport_fee_share: f64,
+    // Part des trajets au forfait (rate code JFK)
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub flat_rate_share: f64,
+}
+
+// Activité par jour de la semaine (1 = lundi) et heure de prise en charge
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "diesel", derive(QueryableByName))]
+pub struct AirportHourlyActivity {
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Text))]
+    pub airport: String,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Text))]
+    pub direction: String,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Integer))]
+    pub day_of_week: i32,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Integer))]
+    pub hour_of_day: i32,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::BigInt))]
+    pub trip_count: i64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_fare: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_duration_seconds: f64,
+}
+
+// Forfait (`flat_rate`), compteur (`metered`) ou autre tarif (`other`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "diesel", derive(QueryableByName))]
+pub struct AirportFareTypeStats {
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Text))]
+    pub airport: String,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Text))]
+    pub direction: String,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Text))]
+    pub fare_type: String,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::BigInt))]
+    pub trip_count: i64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_fare: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_total_amount: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_distance: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_duration_seconds: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Float8>))]
+    pub fare_per_mile: Option<f64>,
+}
+
+// Distribution des durées (en minutes) : quantiles et histogramme
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct AirportDurationDistribution {
+    pub airport: String,
+    pub direction: String,
+    pub trip_count: i64,
+    pub p10_minutes: f64,
+    pub p25_minutes: f64,
+    pub p50_minutes: f64,
+    pub p75_minutes: f64,
+    pub p90_minutes: f64,
+    // Largeur des classes de l'histogramme ; la dernière classe regroupe les durées au-delà
+    pub bucket_minutes: i32,
+    pub histogram: Vec<i64>,
+}
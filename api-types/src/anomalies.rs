@@ -0,0 +1,65 @@
+// Détection d'anomalies sur les séries journalières (volume, recettes)
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+// Série analysée
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyMetric {
+    // Nombre de trajets par jour
+    #[default]
+    TripCount,
+    // Somme de `total_amount` par jour
+    Revenue,
+}
+
+// Méthode de calcul de la valeur attendue
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyMethod {
+    // Tendance (médiane glissante centrée) + saisonnalité par jour de la semaine
+    #[default]
+    Seasonal,
+    // Médiane et MAD des mêmes jours de la semaine sur la fenêtre précédente
+    RobustZ,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct AnomalyParams {
+    pub metric: Option<AnomalyMetric>,
+    pub method: Option<AnomalyMethod>,
+    // Score robuste à partir duquel un jour est signalé (défaut 3,5)
+    pub threshold: Option<f64>,
+    // Fenêtre de la tendance ou de l'historique, en jours (défaut 28)
+    pub window_days: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Anomaly {
+    pub date: NaiveDate,
+    pub value: f64,
+    pub expected: f64,
+    // value - expected
+    pub deviation: f64,
+    // deviation / expected (absent si la valeur attendue est nulle)
+    pub relative_deviation: Option<f64>,
+    // Écart en unités d'écart-type robuste (1,4826 x MAD)
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct AnomalyReport {
+    pub metric: AnomalyMetric,
+    pub method: AnomalyMethod,
+    pub threshold: f64,
+    pub window_days: i64,
+    // Jours analysés (jours sans trajet inclus)
+    pub days: i64,
+    pub anomalies: Vec<Anomaly>,
+}
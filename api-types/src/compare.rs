@@ -0,0 +1,114 @@
+// Comparaison de deux segments de trajets (A / B), chacun défini par un jeu
+// de filtres communs
+use serde::{Deserialize, Serialize};
+
+use crate::TripFilters;
+
+// Indicateurs comparés trajet par trajet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum CompareMetric {
+    FareAmount,
+    TotalAmount,
+    // Pourboire / tarif, trajets de tarif positif
+    TipRate,
+    // Secondes, trajets de durée positive
+    TripDuration,
+    TripDistance,
+}
+
+impl CompareMetric {
+    pub const ALL: [CompareMetric; 5] = [
+        CompareMetric::FareAmount,
+        CompareMetric::TotalAmount,
+        CompareMetric::TipRate,
+        CompareMetric::TripDuration,
+        CompareMetric::TripDistance,
+    ];
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CompareRequest {
+    #[serde(default)]
+    pub a: TripFilters,
+    #[serde(default)]
+    pub b: TripFilters,
+    // Libellés affichés (défaut « A » et « B »)
+    pub label_a: Option<String>,
+    pub label_b: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SegmentSummary {
+    pub label: String,
+    pub trip_count: i64,
+    pub total_revenue: f64,
+    // Somme des pourboires / somme des tarifs
+    pub tip_rate: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct MetricStats {
+    // Trajets pour lesquels l'indicateur est défini
+    pub count: i64,
+    pub mean: Option<f64>,
+    pub stddev: Option<f64>,
+    pub p25: Option<f64>,
+    pub p50: Option<f64>,
+    pub p75: Option<f64>,
+    pub p90: Option<f64>,
+}
+
+// Test t de Welch sur les moyennes (variances inégales), bilatéral
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct WelchTest {
+    pub t: f64,
+    pub df: f64,
+    pub p_value: f64,
+}
+
+// Test de Mann-Whitney (approximation normale, correction des ex aequo),
+// bilatéral, sur des échantillons de chaque segment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct MannWhitneyTest {
+    // U du segment B
+    pub u: f64,
+    pub z: f64,
+    pub p_value: f64,
+    pub sample_a: i64,
+    pub sample_b: i64,
+    // Probabilité qu'un trajet de B dépasse un trajet de A (ex aequo : 1/2)
+    pub prob_b_greater: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct MetricComparison {
+    pub metric: CompareMetric,
+    pub a: MetricStats,
+    pub b: MetricStats,
+    // Moyenne B - moyenne A
+    pub abs_diff: Option<f64>,
+    // (moyenne B - moyenne A) / moyenne A
+    pub rel_diff: Option<f64>,
+    // Absents si un segment compte moins de deux valeurs
+    pub welch: Option<WelchTest>,
+    pub mann_whitney: Option<MannWhitneyTest>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CompareResponse {
+    pub a: SegmentSummary,
+    pub b: SegmentSummary,
+    // Trajets B - trajets A, et rapport à A
+    pub trip_count_diff: i64,
+    pub trip_count_rel_diff: Option<f64>,
+    pub metrics: Vec<MetricComparison>,
+}
@@ -0,0 +1,76 @@
+// Trajets simultanés (offre approchée à partir des horodatages, faute
+// d'identifiant de chauffeur)
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::od_matrix::ZoneRef;
+
+// `start_date` et `end_date` requis, sur 7 jours au plus à la minute, 366 à
+// l'heure et 731 au jour
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ConcurrencyGranularity {
+    Minute,
+    #[default]
+    Hour,
+    Day,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct ConcurrencyParams {
+    pub granularity: Option<ConcurrencyGranularity>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ConcurrencyPoint {
+    // Début de la minute, de l'heure ou du jour (périodes sans trajet actif omises)
+    pub period: NaiveDateTime,
+    pub trip_starts: i64,
+    // Trajets actifs en moyenne par minute, et maximum atteint
+    pub avg_active: f64,
+    pub peak_active: i64,
+    pub peak_at: NaiveDateTime,
+    // Minutes-trajet : temps passé en course par l'ensemble des véhicules
+    pub active_trip_minutes: i64,
+    // Trajets actifs moyens / flotte estimée
+    pub utilisation: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ConcurrencySeries {
+    pub granularity: ConcurrencyGranularity,
+    // Pic de trajets simultanés sur toute la période : borne basse du nombre
+    // de véhicules en service
+    pub fleet_estimate: i64,
+    pub fleet_peak_at: Option<NaiveDateTime>,
+    pub avg_active: f64,
+    pub utilisation: Option<f64>,
+    pub points: Vec<ConcurrencyPoint>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct ZoneConcurrencyParams {
+    // Nombre de zones, par pic décroissant (défaut 50)
+    pub limit: Option<i64>,
+}
+
+// Trajets simultanés partis d'une zone
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ZoneConcurrency {
+    pub zone: ZoneRef,
+    pub trip_count: i64,
+    pub peak_active: i64,
+    pub peak_at: NaiveDateTime,
+    // Moyenne sur toute la période analysée
+    pub avg_active: f64,
+    // Pic par heure de la journée (0 - 23)
+    pub hourly_peak: Vec<i64>,
+}
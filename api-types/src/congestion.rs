@@ -0,0 +1,98 @@
+// Surcharge de congestion : recettes par jour / heure / zone et comparaison
+// avant / après une date de changement de politique tarifaire
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "diesel")]
+use diesel::deserialize::QueryableByName;
+
+// Axe de regroupement des recettes de surcharge
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum CongestionGrouping {
+    #[default]
+    Day,
+    Hour,
+    Zone,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct CongestionParams {
+    // Jour (défaut), heure de prise en charge ou zone de départ
+    pub group_by: Option<CongestionGrouping>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct CongestionPolicyParams {
+    // Date d'entrée en vigueur (défaut : `CONGESTION_POLICY_DATE` côté serveur)
+    pub policy_date: Option<NaiveDate>,
+    // Fenêtre de comparaison, en jours de part et d'autre de la date (toutes les données par défaut)
+    pub window_days: Option<i64>,
+}
+
+// Clé : date (AAAA-MM-JJ), heure (00-23) ou location_id selon le regroupement
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "diesel", derive(QueryableByName))]
+pub struct CongestionSurchargeStats {
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Text))]
+    pub key: String,
+    // Libellé de la zone (« zone, borough ») pour le regroupement par zone
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>))]
+    pub label: Option<String>,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::BigInt))]
+    pub trip_count: i64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::BigInt))]
+    pub charged_trips: i64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub charged_share: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub surcharge_revenue: f64,
+    // Surcharge moyenne des trajets facturés
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_surcharge: f64,
+}
+
+// Indicateurs d'une période (avant ou après la date de politique)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "diesel", derive(QueryableByName))]
+pub struct CongestionPeriodStats {
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Date>))]
+    pub start_date: Option<NaiveDate>,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Date>))]
+    pub end_date: Option<NaiveDate>,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::BigInt))]
+    pub days: i64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::BigInt))]
+    pub trip_count: i64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub trips_per_day: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub charged_share: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_surcharge: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub surcharge_revenue_per_day: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_fare: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_total_amount: f64,
+}
+
+// Comparaison avant / après ; variations relatives (après / avant - 1)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CongestionPolicyImpact {
+    pub policy_date: NaiveDate,
+    pub before: CongestionPeriodStats,
+    pub after: CongestionPeriodStats,
+    pub trips_per_day_change: Option<f64>,
+    pub charged_share_change: Option<f64>,
+    pub surcharge_revenue_per_day_change: Option<f64>,
+    pub avg_total_amount_change: Option<f64>,
+}
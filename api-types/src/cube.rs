@@ -0,0 +1,91 @@
+// Requêtes de type cube (mesures x dimensions) sur le schéma en étoile,
+// exposées par l'endpoint GraphQL
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "diesel")]
+use diesel::deserialize::QueryableByName;
+
+// Axes d'analyse disponibles (dim_date, dim_location, dim_vendor, ...)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "snake_case")]
+pub enum CubeDimension {
+    Date,
+    Year,
+    Quarter,
+    Month,
+    Day,
+    DayOfWeek,   // ISO : 1 (Lundi) - 7 (Dimanche)
+    DayName,
+    MonthName,
+    IsWeekend,
+    IsHoliday,
+    HolidayName,
+    IsSchoolDay, // Jour de classe des écoles publiques de New York
+    IsDstTransition, // Passage à l'heure d'été ou d'hiver
+    DayType,     // workday, weekend ou holiday
+    HourOfDay,   // Heure de prise en charge : 0 - 23
+    PickupBorough,
+    PickupZone,
+    PickupServiceZone,
+    DropoffBorough,
+    DropoffZone,
+    DropoffServiceZone,
+    Vendor,
+    PaymentType,
+    RateCode,
+}
+
+// Mesures agrégées sur fact_trips
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "snake_case")]
+pub enum CubeMeasure {
+    TripCount,
+    TotalRevenue,
+    AvgFare,
+    AvgTotalAmount,
+    AvgTip,
+    TipRatio,           // Somme des pourboires / somme des tarifs
+    AvgDistance,
+    AvgDurationSeconds,
+    DurationP25,
+    DurationP50,
+    DurationP75,
+    DurationP90,
+}
+
+// Restriction d'une dimension à une liste de valeurs (comparées en texte)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "graphql", derive(async_graphql::InputObject))]
+pub struct DimensionFilter {
+    pub dimension: CubeDimension,
+    pub values: Vec<String>,
+}
+
+// Une cellule du cube : valeurs alignées sur les dimensions et mesures demandées
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject))]
+#[cfg_attr(feature = "diesel", derive(QueryableByName))]
+pub struct CubeRow {
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Array<diesel::sql_types::Nullable<diesel::sql_types::Text>>))]
+    pub dimensions: Vec<Option<String>>,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Array<diesel::sql_types::Nullable<diesel::sql_types::Float8>>))]
+    pub measures: Vec<Option<f64>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject))]
+pub struct CubeResult {
+    pub dimensions: Vec<CubeDimension>,
+    pub measures: Vec<CubeMeasure>,
+    pub date_from: Option<NaiveDate>,
+    pub date_to: Option<NaiveDate>,
+    pub cost: i32,       // Coût estimé de la requête (voir la limite côté serveur)
+    pub rows: Vec<CubeRow>,
+}
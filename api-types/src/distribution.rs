@@ -0,0 +1,99 @@
+// Distribution d'un champ numérique des trajets : histogramme, percentiles,
+// moments et valeurs aberrantes
+use serde::{Deserialize, Serialize};
+
+// Champ analysé (segment de chemin de `/api/v1/distribution/{field}`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum DistributionField {
+    TripDistance,
+    FareAmount,
+    TipAmount,
+    TotalAmount,
+    // En secondes
+    TripDuration,
+    PassengerCount,
+}
+
+// Découpage de l'histogramme
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum BinningMode {
+    // Classes de largeur constante
+    #[default]
+    Fixed,
+    // Classes de largeur constante en échelle logarithmique (valeurs > 0)
+    Log,
+    // Classes d'effectifs égaux (bornes aux quantiles)
+    Quantile,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct DistributionParams {
+    pub binning: Option<BinningMode>,
+    // Nombre de classes (défaut 20)
+    pub bins: Option<i32>,
+    // Bornes de l'histogramme ; par défaut du minimum observé à la clôture
+    // haute de Tukey (Q3 + 3 IQR), le reste étant compté hors bornes
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    // Liste de percentiles séparés par des virgules, entre 0 et 1 (ex. `0.5,0.9,0.99`)
+    pub percentiles: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct HistogramBin {
+    pub lower: f64,
+    // Borne exclue, sauf pour la dernière classe
+    pub upper: f64,
+    pub count: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PercentileValue {
+    pub percentile: f64,
+    pub value: f64,
+}
+
+// Valeurs hors des clôtures de Tukey (Q1 - 1,5 IQR, Q3 + 1,5 IQR)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct OutlierCounts {
+    pub q1: f64,
+    pub q3: f64,
+    pub lower_fence: f64,
+    pub upper_fence: f64,
+    pub low_count: i64,
+    pub high_count: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Distribution {
+    pub field: DistributionField,
+    // `miles`, `usd`, `seconds` ou `passengers`
+    pub unit: String,
+    // Valeurs non nulles
+    pub count: i64,
+    pub null_count: i64,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub mean: Option<f64>,
+    pub stddev: Option<f64>,
+    // Coefficient d'asymétrie de Fisher-Pearson
+    pub skewness: Option<f64>,
+    pub percentiles: Vec<PercentileValue>,
+    pub outliers: Option<OutlierCounts>,
+    pub binning: BinningMode,
+    pub bins: Vec<HistogramBin>,
+    // Valeurs sous la première borne (en échelle log : valeurs <= 0 comprises)
+    pub underflow: i64,
+    // Valeurs au-delà de la dernière borne
+    pub overflow: i64,
+}
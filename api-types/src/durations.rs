@@ -0,0 +1,93 @@
+// Statistiques de durée des trajets ventilées par dimension (boîtes à moustaches)
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "diesel")]
+use diesel::deserialize::QueryableByName;
+
+// Axe de ventilation des durées
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum DurationDimension {
+    #[default]
+    PickupBorough,
+    // Paire borough de départ -> borough d'arrivée
+    OdBoroughPair,
+    Hour,
+    // 1 = lundi
+    DayOfWeek,
+    // Mois calendaire (AAAA-MM)
+    Month,
+    // Jour ouvré, week-end ou férié
+    DayType,
+}
+
+impl DurationDimension {
+    pub const ALL: [DurationDimension; 6] = [
+        DurationDimension::PickupBorough,
+        DurationDimension::OdBoroughPair,
+        DurationDimension::Hour,
+        DurationDimension::DayOfWeek,
+        DurationDimension::Month,
+        DurationDimension::DayType,
+    ];
+
+    // Valeur du paramètre `by`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DurationDimension::PickupBorough => "pickup_borough",
+            DurationDimension::OdBoroughPair => "od_borough_pair",
+            DurationDimension::Hour => "hour",
+            DurationDimension::DayOfWeek => "day_of_week",
+            DurationDimension::Month => "month",
+            DurationDimension::DayType => "day_type",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DurationDimension::PickupBorough => "Borough de départ",
+            DurationDimension::OdBoroughPair => "Paire de boroughs",
+            DurationDimension::Hour => "Heure",
+            DurationDimension::DayOfWeek => "Jour de la semaine",
+            DurationDimension::Month => "Mois",
+            DurationDimension::DayType => "Type de jour",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct DurationBreakdownParams {
+    pub by: Option<DurationDimension>,
+}
+
+// Quantiles de durée (secondes) d'un groupe ; p10 / p90 servent de moustaches
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "diesel", derive(QueryableByName))]
+pub struct TripDurationBreakdown {
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Text))]
+    pub key: String,
+    // Libellé lisible (nom du jour pour `day_of_week`)
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>))]
+    pub label: Option<String>,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::BigInt))]
+    pub trip_count: i64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_duration_seconds: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub min_duration_seconds: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub p10_duration_seconds: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub p25_duration_seconds: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub p50_duration_seconds: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub p75_duration_seconds: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub p90_duration_seconds: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub max_duration_seconds: f64,
+}
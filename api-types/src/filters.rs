@@ -0,0 +1,33 @@
+// Filtres communs des endpoints d'analyse, passés en paramètres de requête
+// (`?start_date=2024-10-01&hour_from=7&pickup_borough=Manhattan&day_type=workday`)
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams, utoipa::ToSchema))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct TripFilters {
+    // Date de prise en charge, bornes incluses
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+    // Heure de prise en charge (0 - 23), bornes incluses
+    pub hour_from: Option<i32>,
+    pub hour_to: Option<i32>,
+    pub pickup_borough: Option<String>,
+    pub dropoff_borough: Option<String>,
+    pub vendor: Option<String>,
+    pub payment_type: Option<String>,
+    pub rate_code: Option<String>,
+    // Type de jour de prise en charge (calendrier de `dim_date`)
+    pub day_type: Option<DayType>,
+}
+
+// Type de jour : un jour férié tombant un week-end compte comme férié
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum DayType {
+    Workday,
+    Weekend,
+    Holiday,
+}
@@ -0,0 +1,101 @@
+// Prévision de la demande (nombre de trajets) par jour ou par heure, avec
+// intervalles de prévision et métriques de backtest
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+// Pas de temps de la série prévue
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ForecastGranularity {
+    // Saisonnalité hebdomadaire (7 jours)
+    #[default]
+    Daily,
+    // Saisonnalité journalière et hebdomadaire (168 heures)
+    Hourly,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ForecastMethod {
+    // Lissage exponentiel triple, saisonnalité additive
+    #[default]
+    HoltWinters,
+    // Valeur de la même période de la saison précédente
+    SeasonalNaive,
+}
+
+// Découpage des séries prévues
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ForecastSplit {
+    // Une seule série pour les trajets filtrés
+    #[default]
+    Total,
+    // Une série par borough de départ
+    PickupBorough,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct ForecastParams {
+    pub granularity: Option<ForecastGranularity>,
+    pub method: Option<ForecastMethod>,
+    pub by: Option<ForecastSplit>,
+    // Nombre de périodes prévues (défaut 28 jours ou 168 heures)
+    pub horizon: Option<i64>,
+    // Niveau des intervalles de prévision, entre 0,5 et 0,99 (défaut 0,95)
+    pub confidence: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ForecastPoint {
+    // Début de la période (minuit pour la granularité journalière)
+    pub period: NaiveDateTime,
+    pub forecast: f64,
+    pub lower: f64,
+    pub upper: f64,
+}
+
+// Erreurs de prévision sur les dernières périodes observées, le modèle étant
+// ajusté sur l'historique qui les précède
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ForecastBacktest {
+    pub holdout_periods: i64,
+    pub mae: f64,
+    // En pourcentage, sur les périodes de valeur non nulle
+    pub mape: Option<f64>,
+    // Part des valeurs observées comprises dans l'intervalle de prévision
+    pub coverage: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ForecastSeries {
+    // `total` ou nom du borough
+    pub key: String,
+    // Périodes d'historique utilisées pour l'ajustement
+    pub history_periods: i64,
+    pub last_observed: Option<NaiveDateTime>,
+    // Absent si l'historique est trop court pour un backtest
+    pub backtest: Option<ForecastBacktest>,
+    // Vide si l'historique couvre moins de deux saisons
+    pub points: Vec<ForecastPoint>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ForecastReport {
+    pub granularity: ForecastGranularity,
+    pub method: ForecastMethod,
+    pub horizon: i64,
+    pub confidence: f64,
+    // Longueur de la saison (7 ou 168 périodes)
+    pub season_length: i64,
+    pub series: Vec<ForecastSeries>,
+}
@@ -0,0 +1,33 @@
+// Zones de taxi géolocalisées : paramètres de simplification et métriques
+// par zone fusionnées dans les propriétés GeoJSON
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "diesel")]
+use diesel::deserialize::QueryableByName;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct GeoZonesParams {
+    // Niveau de zoom de la carte (0-22) : choisit la tolérance de simplification
+    pub zoom: Option<u8>,
+}
+
+// Métriques d'une zone pour les cartes choroplèthes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "diesel", derive(QueryableByName))]
+pub struct ZoneMetrics {
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Integer))]
+    pub location_id: i32,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::BigInt))]
+    pub pickups: i64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::BigInt))]
+    pub dropoffs: i64,
+    // Moyennes calculées sur les trajets partant de la zone
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Float8>))]
+    pub avg_fare: Option<f64>,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Float8>))]
+    pub avg_total_amount: Option<f64>,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Float8>))]
+    pub tip_rate: Option<f64>,
+}
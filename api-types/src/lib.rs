@@ -0,0 +1,265 @@
+//! Types de requête et de réponse de l'API DataViz, partagés entre le backend
+//! Axum et le frontend Yew.
+//!
+//! La crate ne dépend que de serde, serde_json et chrono et compile en WebAssembly. Les
+//! dérivations propres au backend sont derrière des features, toutes activées
+//! par le backend : `diesel` (`QueryableByName`), `openapi` (`utoipa::ToSchema`)
+//! et `graphql` (types `async_graphql`).
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "diesel")]
+use diesel::deserialize::QueryableByName;
+
+mod airports;
+mod anomalies;
+mod compare;
+mod concurrency;
+mod congestion;
+mod cube;
+mod distribution;
+mod durations;
+mod filters;
+mod forecast;
+mod geo;
+mod od_matrix;
+mod query;
+mod rankings;
+mod revenue;
+mod route_efficiency;
+mod routing;
+mod speed;
+mod store_forward;
+mod tips;
+mod weather;
+
+pub use airports::*;
+pub use anomalies::*;
+pub use compare::*;
+pub use concurrency::*;
+pub use congestion::*;
+pub use cube::*;
+pub use distribution::*;
+pub use durations::*;
+pub use filters::*;
+pub use forecast::*;
+pub use geo::*;
+pub use od_matrix::*;
+pub use query::*;
+pub use rankings::*;
+pub use revenue::*;
+pub use route_efficiency::*;
+pub use routing::*;
+pub use speed::*;
+pub use store_forward::*;
+pub use tips::*;
+pub use weather::*;
+
+// Renamed struct to reflect more data
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "diesel", derive(QueryableByName))]
+pub struct AggregatedTripStats {
+    // Grouping key (Date)
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Date))]
+    pub date: NaiveDate,
+    
+    // Original count
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::BigInt))]
+    pub trip_count: i64,
+    
+    // New average metrics (using Float8 for SQL compatibility with AVG)
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_total_amount: f64,
+    
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_tip_amount: f64,
+    
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_trip_distance: f64,
+    
+    // Average duration in seconds (epoch)
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_trip_duration_seconds: f64,
+}
+
+// Nouvelle structure pour l'analyse par type de paiement
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "diesel", derive(QueryableByName))]
+pub struct PaymentTypeAnalysis {
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Text))]
+    pub payment_type_name: String,
+    
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::BigInt))]
+    pub trip_count: i64,
+    
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_tip_amount: f64,
+}
+
+// Nouvelle structure pour l'activité par heure/jour
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "diesel", derive(QueryableByName))]
+pub struct HourlyWeekdayActivity {
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Integer))]
+    pub day_of_week: i32, // ISO day: 1 (Lundi) - 7 (Dimanche)
+    
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Integer))]
+    pub hour_of_day: i32, // Heure: 0 - 23
+    
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::BigInt))]
+    pub trip_count: i64,
+}
+
+// Analyse par Nombre de Passagers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "diesel", derive(QueryableByName))]
+pub struct PassengerAnalysis {
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Integer>))] // Passenger count peut être NULL
+    pub passenger_count: Option<i32>,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::BigInt))]
+    pub trip_count: i64,
+}
+
+// Décomposition Financière (par période)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "diesel", derive(QueryableByName))]
+pub struct FinancialBreakdown {
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Date))]
+    pub date: NaiveDate, // Ou une autre clé de période
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_fare_amount: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_tip_amount: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_tolls_amount: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_mta_tax: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_improvement_surcharge: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_extra: f64, // Autres frais
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_congestion_surcharge: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_airport_fee: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_total_amount: f64,
+}
+
+// Analyse par Vendeur
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "diesel", derive(QueryableByName))]
+pub struct VendorAnalysis {
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Text))]
+    pub vendor_name: String,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::BigInt))]
+    pub trip_count: i64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_total_amount: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_trip_distance: f64,
+}
+
+// Analyse par Code Tarifaire
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "diesel", derive(QueryableByName))]
+pub struct RateCodeAnalysis {
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Text))]
+    pub rate_code_name: String,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::BigInt))]
+    pub trip_count: i64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_total_amount: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_trip_distance: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_tip_amount: f64, // Ajouté pour une analyse plus complète
+}
+
+// Statistiques sur la durée des trajets
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "diesel", derive(QueryableByName))]
+pub struct TripDurationStats {
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_duration_seconds: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub min_duration_seconds: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub max_duration_seconds: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub p25_duration_seconds: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub p50_duration_seconds: f64, // Median
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub p75_duration_seconds: f64,
+}
+
+// Analyse par Zone
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "diesel", derive(QueryableByName))]
+pub struct ZoneActivity {
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Integer>))]
+    pub location_id: Option<i32>,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>))]
+    pub zone: Option<String>,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>))]
+    pub borough: Option<String>,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::BigInt))]
+    pub trip_count: i64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_total_amount: f64,
+}
+
+// Données pour Tendance KPI (valeur actuelle et précédente)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct KpiTrendData {
+    pub total_trips: TrendValue,
+    pub avg_trips_per_period: TrendValue,
+    pub max_trips_per_period: TrendValue,
+    pub avg_amount_overall: TrendValue,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TrendValue {
+    pub current: f64,       // Valeur pour la période sélectionnée
+    pub previous: Option<f64>, // Valeur pour la période précédente
+    pub trend: Option<f64>,    // Pourcentage de changement
+}
+
+// Statistiques sur l'efficacité tarifaire
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "diesel", derive(QueryableByName))]
+pub struct FareEfficiencyStats {
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_fare_per_km: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_fare_per_minute: f64,
+}
+
+// Statistiques sur les flux entre arrondissements
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "diesel", derive(QueryableByName))]
+pub struct BoroughFlowStats {
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Text))]
+    pub pickup_borough: String,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Text))]
+    pub dropoff_borough: String,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::BigInt))]
+    pub trip_count: i64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_fare_amount: f64,
+    // On pourrait ajouter avg_trip_duration, avg_trip_distance plus tard
+}
@@ -0,0 +1,39 @@
+// Matrice origine-destination entre zones de taxi, au format creux (COO)
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct OdMatrixParams {
+    // Seuil : paires avec au moins `min_trips` trajets (défaut 1)
+    pub min_trips: Option<i64>,
+    // Pagination des paires, triées par nombre de trajets décroissant
+    pub page: Option<i64>,
+    pub page_size: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ZoneRef {
+    pub location_id: i32,
+    pub zone: Option<String>,
+    pub borough: Option<String>,
+}
+
+// Une colonne par métrique ; l'élément i de chaque vecteur décrit la paire i
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct OdMatrix {
+    pub zones: Vec<ZoneRef>,                // Zones présentes dans la page
+    pub origins: Vec<i32>,                  // location_id de départ
+    pub destinations: Vec<i32>,             // location_id d'arrivée
+    pub trip_counts: Vec<i64>,
+    pub avg_fare: Vec<f64>,
+    pub avg_duration_seconds: Vec<f64>,
+    pub avg_distance: Vec<f64>,
+    pub tip_rate: Vec<f64>,                 // Pourboires / tarifs
+    pub total_pairs: i64,                   // Paires au-dessus du seuil, toutes pages
+    pub page: i64,
+    pub page_size: i64,
+    pub min_trips: i64,
+}
@@ -0,0 +1,126 @@
+// Spécification JSON de l'endpoint générique /api/v1/query (pivot / agrégation)
+use serde::{Deserialize, Serialize};
+
+use crate::cube::{CubeDimension, CubeRow};
+
+// Fonctions d'agrégation disponibles
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum Aggregate {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+    Percentile,
+}
+
+// Colonnes numériques de fact_trips autorisées dans les mesures et filtres
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum MeasureColumn {
+    PassengerCount,
+    TripDistance,
+    FareAmount,
+    Extra,
+    MtaTax,
+    TipAmount,
+    TollsAmount,
+    ImprovementSurcharge,
+    CongestionSurcharge,
+    AirportFee,
+    TotalAmount,
+    TripDuration,   // En secondes
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct QueryMeasure {
+    pub agg: Aggregate,
+    // Optionnelle pour `count` (nombre de trajets)
+    pub column: Option<MeasureColumn>,
+    // Fraction entre 0 et 1, requise pour `percentile`
+    pub percentile: Option<f64>,
+    // Nom de la mesure dans la réponse et pour le tri ([a-z0-9_])
+    pub alias: Option<String>,
+}
+
+// Champ filtrable : une dimension ou une colonne numérique
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(untagged)]
+pub enum FilterField {
+    Dimension(CubeDimension),
+    Column(MeasureColumn),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum FilterOp {
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    In,
+    NotIn,
+    Between,
+    IsNull,
+    NotNull,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct QueryFilter {
+    pub field: FilterField,
+    pub op: FilterOp,
+    // Scalaire, ou liste pour `in`, `not_in` et `between` ; absent pour `is_null` / `not_null`
+    #[serde(default)]
+    #[cfg_attr(feature = "openapi", schema(value_type = Object))]
+    pub value: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum SortDirection {
+    #[default]
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct QuerySort {
+    // Alias d'une mesure ou nom d'une dimension de `group_by`
+    pub by: String,
+    #[serde(default)]
+    pub direction: SortDirection,
+}
+
+// Corps de POST /api/v1/query
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct QuerySpec {
+    pub measures: Vec<QueryMeasure>,
+    #[serde(default)]
+    pub group_by: Vec<CubeDimension>,
+    #[serde(default)]
+    pub filters: Vec<QueryFilter>,
+    #[serde(default)]
+    pub sort: Vec<QuerySort>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct QueryResponse {
+    pub dimensions: Vec<CubeDimension>,
+    pub measures: Vec<String>,   // Alias, dans l'ordre des valeurs de chaque ligne
+    pub cost: i32,
+    pub rows: Vec<CubeRow>,
+}
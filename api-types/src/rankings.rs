@@ -0,0 +1,69 @@
+// Classements top N / bottom N d'une dimension du cube selon une mesure,
+// avec évolution par rapport à la période précédente et groupe « Autres »
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::cube::{CubeDimension, CubeMeasure};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct RankingParams {
+    // Défaut : zone de départ
+    pub dimension: Option<CubeDimension>,
+    // Défaut : nombre de trajets
+    pub measure: Option<CubeMeasure>,
+    // Nombre de premiers et de derniers groupes (défauts 10 et 0, ex aequo inclus)
+    pub top: Option<i64>,
+    pub bottom: Option<i64>,
+    // Regrouper les autres groupes sous « Autres » (défaut : oui)
+    pub other: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RankingItem {
+    pub key: String,
+    pub value: Option<f64>,
+    // Rang par valeur décroissante ; les ex aequo partagent le même rang
+    pub rank: i64,
+    // Part du total et part cumulée des groupes de rang inférieur ou égal
+    // (Pareto), pour les mesures additives uniquement
+    pub share: Option<f64>,
+    pub cumulative_share: Option<f64>,
+    // Période précédente (absente sans `start_date` et `end_date`, ou si le
+    // groupe n'y figure pas)
+    pub previous_value: Option<f64>,
+    pub previous_rank: Option<i64>,
+    // Rang précédent - rang actuel : positif quand le groupe progresse
+    pub rank_change: Option<i64>,
+}
+
+// Groupes hors du top et du bottom, agrégés
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RankingOther {
+    pub group_count: i64,
+    // Mesure recalculée sur l'ensemble des trajets de ces groupes
+    pub value: Option<f64>,
+    pub share: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RankingReport {
+    pub dimension: CubeDimension,
+    pub measure: CubeMeasure,
+    // Mesure sur l'ensemble des trajets filtrés
+    pub total: Option<f64>,
+    pub group_count: i64,
+    // Période de même durée précédant `start_date`
+    pub previous_start: Option<NaiveDate>,
+    pub previous_end: Option<NaiveDate>,
+    // Nombre de premiers groupes couvrant 80 % du total (mesures additives)
+    pub pareto_80_groups: Option<i64>,
+    pub top: Vec<RankingItem>,
+    // Du plus petit au plus grand
+    pub bottom: Vec<RankingItem>,
+    pub other: Option<RankingOther>,
+}
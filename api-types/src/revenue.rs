@@ -0,0 +1,105 @@
+// Décomposition de la variation du revenu entre deux périodes (graphique en
+// cascade)
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+// Axe des effets de mix
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum RevenueMixDimension {
+    #[default]
+    PickupBorough,
+    RateCode,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct RevenueDecompositionParams {
+    // Période de référence et période comparée (dates de prise en charge,
+    // bornes incluses), obligatoires
+    pub base_start: Option<NaiveDate>,
+    pub base_end: Option<NaiveDate>,
+    pub compare_start: Option<NaiveDate>,
+    pub compare_end: Option<NaiveDate>,
+    // Borough de départ (défaut) ou code tarifaire
+    pub mix: Option<RevenueMixDimension>,
+}
+
+// Barres du graphique en cascade, dans l'ordre d'affichage
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum RevenueComponent {
+    // Revenu de la période de référence
+    Base,
+    // Nombre de trajets, à revenu moyen par trajet constant
+    Volume,
+    // Répartition des trajets entre les groupes de `mix`
+    Mix,
+    // Tarif moyen par trajet au sein de chaque groupe
+    Fare,
+    // Pourboire moyen par trajet au sein de chaque groupe
+    Tip,
+    // Surcharges, taxes, péages et frais aéroport par trajet
+    SurchargesTolls,
+    // Revenu de la période comparée
+    Compare,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct WaterfallStep {
+    pub component: RevenueComponent,
+    pub value: f64,
+    // Bornes de la barre : 0 et le revenu pour `base` / `compare`, cumul
+    // avant et après l'effet sinon
+    pub start: f64,
+    pub end: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RevenuePeriod {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub trip_count: i64,
+    // Somme des montants totaux
+    pub revenue: f64,
+    pub revenue_per_trip: Option<f64>,
+    pub fare_per_trip: Option<f64>,
+    pub tip_per_trip: Option<f64>,
+    pub surcharges_tolls_per_trip: Option<f64>,
+}
+
+// Contribution d'un groupe de `mix` aux effets de la cascade
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RevenueMixContribution {
+    pub key: String,
+    pub base_trips: i64,
+    pub compare_trips: i64,
+    // Part des trajets de la période
+    pub base_share: f64,
+    pub compare_share: f64,
+    pub base_revenue_per_trip: Option<f64>,
+    pub compare_revenue_per_trip: Option<f64>,
+    pub mix_effect: f64,
+    pub fare_effect: f64,
+    pub tip_effect: f64,
+    pub surcharges_tolls_effect: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RevenueDecomposition {
+    pub mix: RevenueMixDimension,
+    pub base: RevenuePeriod,
+    pub compare: RevenuePeriod,
+    // Revenu comparé - revenu de référence, égal à la somme des effets
+    pub change: f64,
+    pub steps: Vec<WaterfallStep>,
+    // Groupes par nombre de trajets décroissant
+    pub groups: Vec<RevenueMixContribution>,
+}
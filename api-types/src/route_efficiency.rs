@@ -0,0 +1,60 @@
+// Efficacité des itinéraires : distance déclarée comparée à la distance à vol
+// d'oiseau entre les centroïdes des zones de départ et d'arrivée
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "diesel")]
+use diesel::deserialize::QueryableByName;
+
+use crate::SortDirection;
+
+// Critère de tri des paires
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum RouteEfficiencySort {
+    #[default]
+    TripCount,
+    DetourRatio,
+    StraightLineMiles,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct RouteEfficiencyParams {
+    // Paires avec au moins `min_trips` trajets (défaut 10)
+    pub min_trips: Option<i64>,
+    pub limit: Option<i64>,
+    pub sort_by: Option<RouteEfficiencySort>,
+    // Décroissant par défaut
+    pub direction: Option<SortDirection>,
+}
+
+// Une paire origine-destination (zones distinctes)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "diesel", derive(QueryableByName))]
+pub struct RouteEfficiency {
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Integer))]
+    pub pickup_location_id: i32,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>))]
+    pub pickup_zone: Option<String>,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Integer))]
+    pub dropoff_location_id: i32,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>))]
+    pub dropoff_zone: Option<String>,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::BigInt))]
+    pub trip_count: i64,
+    // Distance à vol d'oiseau entre centroïdes (miles)
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub straight_line_miles: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_trip_distance: f64,
+    // Distance moyenne déclarée / distance à vol d'oiseau
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub detour_ratio: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub median_detour_ratio: f64,
+    // Part des trajets déclarant moins que la distance à vol d'oiseau (données suspectes)
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub share_below_straight_line: f64,
+}
@@ -0,0 +1,25 @@
+// Cycle de vie des routes de l'API (dépréciation, retrait)
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum RouteStatus {
+    // Toujours servie, avec les en-têtes Deprecation / Sunset
+    Deprecated,
+    // Répond 410 Gone
+    Removed,
+}
+
+// Annonce publiée sur /api/deprecations pour chaque route dépréciée ou retirée
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RouteNotice {
+    pub path: String,
+    pub status: RouteStatus,
+    pub deprecated_since: NaiveDate,
+    pub sunset: NaiveDate,       // Date à partir de laquelle la route répond 410
+    pub replacement: Option<String>,
+    pub reason: String,
+}
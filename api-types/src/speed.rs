@@ -0,0 +1,68 @@
+// Vitesse moyenne des trajets (mph) et indice de congestion par rapport à la
+// vitesse fluide (de nuit) de la zone de départ
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "diesel")]
+use diesel::deserialize::QueryableByName;
+
+// Axe de ventilation des vitesses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum SpeedDimension {
+    // Jour de la semaine (1 = lundi) et heure : clé `J-HH`
+    #[default]
+    HourOfWeek,
+    PickupZone,
+    OdBoroughPair,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct SpeedParams {
+    pub by: Option<SpeedDimension>,
+    // Nombre minimal de trajets par groupe (défaut 10)
+    pub min_trips: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "diesel", derive(QueryableByName))]
+pub struct SpeedStats {
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Text))]
+    pub key: String,
+    // Libellé de la zone (« zone, borough ») pour `pickup_zone`
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>))]
+    pub label: Option<String>,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::BigInt))]
+    pub trip_count: i64,
+    // Distance totale / durée totale
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_speed_mph: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub p10_speed_mph: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub p50_speed_mph: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub p90_speed_mph: f64,
+    // Durée réelle / durée à vitesse fluide (1 = circulation fluide)
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Float8>))]
+    pub congestion_index: Option<f64>,
+}
+
+// Cellule de la carte de chaleur heure x jour ; mêmes clés que `HourlyWeekdayActivity`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "diesel", derive(QueryableByName))]
+pub struct SpeedHeatmapCell {
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Integer))]
+    pub day_of_week: i32, // ISO day: 1 (Lundi) - 7 (Dimanche)
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Integer))]
+    pub hour_of_day: i32,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::BigInt))]
+    pub trip_count: i64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_speed_mph: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Float8>))]
+    pub congestion_index: Option<f64>,
+}
@@ -0,0 +1,65 @@
+// Trajets « store and forward » (`store_and_fwd_flag` = Y : enregistrés dans
+// le véhicule puis transmis plus tard, faute de connexion)
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum StoreForwardDimension {
+    #[default]
+    Vendor,
+    PickupZone,
+    Hour,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct StoreForwardParams {
+    pub by: Option<StoreForwardDimension>,
+    // Trajets minimum par groupe (défaut 30)
+    pub min_trips: Option<i64>,
+}
+
+// Profil des trajets transmis en différé ou directement
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TelemetryProfile {
+    pub trip_count: i64,
+    pub avg_duration_seconds: Option<f64>,
+    pub median_duration_seconds: Option<f64>,
+    pub avg_fare: Option<f64>,
+    // Part des trajets incohérents : dépose absente ou antérieure à la prise
+    // en charge, distance nulle, montant négatif, plus de 6 heures ou plus de
+    // 80 mph
+    pub invalid_share: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct StoreForwardGroup {
+    // Vendeur, location_id ou heure (00-23) ; `all` pour l'ensemble
+    pub key: String,
+    // « zone, borough » pour les zones
+    pub label: Option<String>,
+    pub trip_count: i64,
+    pub stored_share: f64,
+    // Écart de la part au taux global, en écarts-types binomiaux, et p bilatéral
+    pub share_z: Option<f64>,
+    pub share_p_value: Option<f64>,
+    pub stored: TelemetryProfile,
+    pub direct: TelemetryProfile,
+    // Différé - direct
+    pub duration_diff_seconds: Option<f64>,
+    pub fare_diff: Option<f64>,
+    pub invalid_share_diff: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct StoreForwardReport {
+    pub by: StoreForwardDimension,
+    pub overall: StoreForwardGroup,
+    // Heures dans l'ordre ; vendeurs et zones par écart au taux global décroissant
+    pub groups: Vec<StoreForwardGroup>,
+}
@@ -0,0 +1,96 @@
+// Comportement de pourboire. Le pourcentage de pourboire est calculé sur le
+// montant avant pourboire hors péages (`total_amount - tip_amount - tolls_amount`),
+// base des montants proposés par les terminaux de paiement.
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "diesel")]
+use diesel::deserialize::QueryableByName;
+
+// Axe de ventilation du taux de pourboire
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum TipDimension {
+    #[default]
+    PaymentType,
+    Hour,
+    PickupBorough,
+    DropoffBorough,
+    DistanceBand,
+    Vendor,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct TipBreakdownParams {
+    pub by: Option<TipDimension>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct TipDistributionParams {
+    // Largeur des classes, en points de pourcentage (défaut 2)
+    pub bucket_percent: Option<f64>,
+    // Borne haute de l'histogramme ; au-delà, dernière classe (défaut 50)
+    pub max_percent: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "diesel", derive(QueryableByName))]
+pub struct TipBreakdown {
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Text))]
+    pub key: String,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::BigInt))]
+    pub trip_count: i64,
+    // Part des trajets avec un pourboire non nul
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub tipped_share: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub avg_tip: f64,
+    // Somme des pourboires / somme des tarifs (comme les autres endpoints)
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub tip_rate: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Float8>))]
+    pub avg_tip_percent: Option<f64>,
+}
+
+// Histogramme des pourcentages de pourboire (trajets payés par carte)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TipDistribution {
+    pub bucket_percent: f64,
+    pub max_percent: f64,
+    pub trip_count: i64,
+    pub zero_tip_count: i64,
+    pub mean_tip_percent: Option<f64>,
+    pub median_tip_percent: Option<f64>,
+    // histogram[i] : trajets dont le pourcentage est dans [i * bucket_percent, (i + 1) * bucket_percent)
+    pub histogram: Vec<i64>,
+}
+
+// Trajets dont le pourboire correspond à un montant proposé (± tolérance)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TipPresetShare {
+    pub percent: i32,
+    pub trip_count: i64,
+    // Part des trajets par carte avec pourboire
+    pub share: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TipSummary {
+    pub trip_count: i64,
+    pub card_trips: i64,
+    pub zero_tip_card_trips: i64,
+    pub zero_tip_card_share: f64,
+    pub avg_tip_percent_card: Option<f64>,
+    // Tolérance de détection des montants proposés, en points de pourcentage
+    pub preset_tolerance_percent: f64,
+    pub presets: Vec<TipPresetShare>,
+    // Part des pourboires par carte en dollars ronds (montant entier)
+    pub round_dollar_share: f64,
+}
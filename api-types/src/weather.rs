@@ -0,0 +1,50 @@
+// Demande selon la météo (observations NOAA de `dim_weather`)
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "diesel")]
+use diesel::deserialize::QueryableByName;
+
+// Axe de ventilation des trajets selon la météo de l'heure de prise en charge
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum WeatherDimension {
+    // dry, rain, heavy_rain, snow, fog ou unknown
+    #[default]
+    Condition,
+    // Tranches de température de `band_celsius` degrés
+    TemperatureBand,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct WeatherBreakdownParams {
+    pub by: Option<WeatherDimension>,
+    // Largeur des tranches de température, en °C (défaut 5)
+    pub band_celsius: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "diesel", derive(QueryableByName))]
+pub struct WeatherBreakdown {
+    // Condition, ou tranche `[min, max[` en °C (`unknown` sans observation)
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Text))]
+    pub key: String,
+    // Borne basse de la tranche de température
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Float8>))]
+    pub temperature_from: Option<f64>,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::BigInt))]
+    pub trip_count: i64,
+    // Heures distinctes (date, heure) avec au moins un trajet
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::BigInt))]
+    pub hours: i64,
+    // Volume ramené au temps passé dans chaque groupe
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Float8))]
+    pub trips_per_hour: f64,
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Float8>))]
+    pub avg_duration_seconds: Option<f64>,
+    // Somme des pourboires / somme des tarifs
+    #[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Float8>))]
+    pub tip_rate: Option<f64>,
+}
@@ -1,28 +1,12 @@
 use yew::prelude::*;
 use wasm_bindgen::prelude::*;
 use gloo_net::http::Request;
-use serde::Deserialize;
 use wasm_bindgen_futures::spawn_local; 
 use gloo_utils::document;
-use gloo_console;
-
-// Structure pour désérialiser la réponse JSON de l'API
-#[derive(Clone, PartialEq, Deserialize, Debug)]
-pub struct TripDurationStats {
-    pub avg_duration_seconds: f64,
-    pub min_duration_seconds: f64,
-    pub max_duration_seconds: f64,
-    pub p25_duration_seconds: f64,
-    pub p50_duration_seconds: f64, // Median
-    pub p75_duration_seconds: f64,
-}
+use web_sys::HtmlSelectElement;
 
-// Structure pour désérialiser les stats d'efficacité tarifaire
-#[derive(Clone, PartialEq, Deserialize, Debug)]
-pub struct FareEfficiencyStats {
-    pub avg_fare_per_km: f64,
-    pub avg_fare_per_minute: f64,
-}
+// Structures de réponse de l'API, partagées avec le backend
+use api_types::{DurationDimension, FareEfficiencyStats, TripDurationBreakdown, TripDurationStats};
 
 #[function_component(FareEfficiencyDisplay)]
 fn fare_efficiency_display() -> Html {
@@ -34,7 +18,7 @@ fn fare_efficiency_display() -> Html {
         let error_state = error_state.clone();
         use_effect_with((), move |_unused_deps: &()| {
             spawn_local(async move {
-                match Request::get("/api/fare_efficiency").send().await {
+                match Request::get("/api/v1/fare_efficiency").send().await {
                     Ok(response) => {
                         if response.ok() {
                             match response.json::<FareEfficiencyStats>().await {
@@ -73,6 +57,123 @@ fn fare_efficiency_display() -> Html {
     }
 }
 
+// Largeur (px) des boîtes à moustaches du tableau des durées
+const BOX_WIDTH: f64 = 200.0;
+
+fn minutes(seconds: f64) -> String {
+    format!("{:.1} min", seconds / 60.0)
+}
+
+// Boîte à moustaches : moustaches p10-p90, boîte p25-p75, trait à la médiane.
+// `scale` : durée (s) correspondant à toute la largeur, commune aux lignes.
+fn duration_box(row: &TripDurationBreakdown, scale: f64) -> Html {
+    let x = |seconds: f64| format!("{:.1}", seconds / scale * BOX_WIDTH);
+    html! {
+        <svg width={BOX_WIDTH.to_string()} height="16">
+            <line x1={x(row.p10_duration_seconds)} x2={x(row.p90_duration_seconds)} y1="8" y2="8" stroke="#757575" />
+            <rect x={x(row.p25_duration_seconds)} y="3" width={x(row.p75_duration_seconds - row.p25_duration_seconds)} height="10" fill="#b3e5fc" stroke="#03a9f4" />
+            <line x1={x(row.p50_duration_seconds)} x2={x(row.p50_duration_seconds)} y1="2" y2="14" stroke="#212121" stroke-width="2" />
+        </svg>
+    }
+}
+
+#[function_component(DurationBreakdownDisplay)]
+fn duration_breakdown_display() -> Html {
+    let dimension_state = use_state(DurationDimension::default);
+    let rows_state = use_state(|| None::<Vec<TripDurationBreakdown>>);
+    let error_state = use_state(|| None::<String>);
+
+    // Rechargement à chaque changement de ventilation
+    {
+        let rows_state = rows_state.clone();
+        let error_state = error_state.clone();
+        use_effect_with(*dimension_state, move |dimension: &DurationDimension| {
+            let url = format!("/api/v1/trip_duration_stats/breakdown?by={}", dimension.as_str());
+            rows_state.set(None);
+            error_state.set(None);
+            spawn_local(async move {
+                match Request::get(&url).send().await {
+                    Ok(response) => {
+                        if response.ok() {
+                            match response.json::<Vec<TripDurationBreakdown>>().await {
+                                Ok(data) => rows_state.set(Some(data)),
+                                Err(e) => error_state.set(Some(format!("Désérialisation JSON (durées par dimension): {}", e))),
+                            }
+                        } else {
+                            error_state.set(Some(format!("API Durées par dimension (statut {}): {}", response.status(), response.status_text())));
+                        }
+                    }
+                    Err(e) => error_state.set(Some(format!("Requête Durées par dimension: {}", e))),
+                }
+            });
+            || {}
+        });
+    }
+
+    let on_change = {
+        let dimension_state = dimension_state.clone();
+        Callback::from(move |e: Event| {
+            let value = e.target_unchecked_into::<HtmlSelectElement>().value();
+            if let Some(dimension) = DurationDimension::ALL.into_iter().find(|d| d.as_str() == value) {
+                dimension_state.set(dimension);
+            }
+        })
+    };
+
+    html! {
+        <div class="stat-section duration-breakdown">
+            <h4>{ "Durées par " }
+                <select onchange={on_change}>
+                    { for DurationDimension::ALL.iter().map(|d| html! {
+                        <option value={d.as_str()} selected={*d == *dimension_state}>{ d.label() }</option>
+                    }) }
+                </select>
+            </h4>
+            {
+                if let Some(error_message) = &*error_state {
+                    html! { <p style="color: red;">{ error_message }</p> }
+                } else if let Some(rows) = &*rows_state {
+                    let scale = rows.iter().map(|r| r.p90_duration_seconds).fold(1.0, f64::max);
+                    html! {
+                        <table>
+                            <thead>
+                                <tr>
+                                    <th>{ dimension_state.label() }</th>
+                                    <th>{ "Trajets" }</th>
+                                    <th>{ "P10" }</th>
+                                    <th>{ "P25" }</th>
+                                    <th>{ "Médiane" }</th>
+                                    <th>{ "P75" }</th>
+                                    <th>{ "P90" }</th>
+                                    <th>{ "Moyenne" }</th>
+                                    <th>{ format!("0 - {}", minutes(scale)) }</th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                { for rows.iter().map(|row| html! {
+                                    <tr>
+                                        <td>{ row.label.clone().unwrap_or_else(|| row.key.clone()) }</td>
+                                        <td>{ row.trip_count }</td>
+                                        <td>{ minutes(row.p10_duration_seconds) }</td>
+                                        <td>{ minutes(row.p25_duration_seconds) }</td>
+                                        <td>{ minutes(row.p50_duration_seconds) }</td>
+                                        <td>{ minutes(row.p75_duration_seconds) }</td>
+                                        <td>{ minutes(row.p90_duration_seconds) }</td>
+                                        <td>{ minutes(row.avg_duration_seconds) }</td>
+                                        <td>{ duration_box(row, scale) }</td>
+                                    </tr>
+                                }) }
+                            </tbody>
+                        </table>
+                    }
+                } else {
+                    html! { <p>{ "Chargement durées par dimension..." }</p> }
+                }
+            }
+        </div>
+    }
+}
+
 // Renommer DurationStatsApp en DashboardWidgetsApp
 // et faire en sorte qu'il appelle les deux composants d'affichage
 // Modifi à venir pour DurationStatsApp
@@ -89,7 +190,7 @@ fn dashboard_widgets_app() -> Html {
         let duration_error_state = duration_error_state.clone();
         use_effect_with((), move |_unused_deps: &()| {
             spawn_local(async move {
-                match Request::get("/api/trip_duration_stats").send().await {
+                match Request::get("/api/v1/trip_duration_stats").send().await {
                     Ok(response) => {
                         if response.ok() {
                             match response.json::<TripDurationStats>().await {
@@ -138,6 +239,7 @@ fn dashboard_widgets_app() -> Html {
             </div>
             
             <FareEfficiencyDisplay /> // Appel du nouveau composant
+            <DurationBreakdownDisplay />
         </>
     }
 }
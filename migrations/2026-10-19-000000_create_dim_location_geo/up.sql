@@ -0,0 +1,10 @@
+-- Attributs géographiques des zones de taxi, calculés à partir des géométries
+-- TLC par `cargo run --bin enrich_locations`
+CREATE TABLE dim_location_geo (
+    location_id INT PRIMARY KEY,
+    centroid_lon DOUBLE PRECISION NOT NULL,
+    centroid_lat DOUBLE PRECISION NOT NULL,
+    area_km2 DOUBLE PRECISION NOT NULL,
+    neighbours INT[] NOT NULL DEFAULT '{}',
+    updated_at TIMESTAMP NOT NULL DEFAULT now()
+);
@@ -0,0 +1,6 @@
+ALTER TABLE dim_date
+    DROP COLUMN day_type,
+    DROP COLUMN is_dst_transition,
+    DROP COLUMN is_school_day,
+    DROP COLUMN holiday_name,
+    DROP COLUMN is_holiday;
@@ -0,0 +1,14 @@
+-- Calendrier de `dim_date` : jours fériés (fédéraux, État et ville de New
+-- York), jours de classe des écoles publiques et changements d'heure,
+-- renseignés par `cargo run --bin enrich_calendar`
+ALTER TABLE dim_date
+    ADD COLUMN is_holiday BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN holiday_name VARCHAR(64),
+    ADD COLUMN is_school_day BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN is_dst_transition BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Type de jour (filtre `day_type`) : un jour férié tombant un week-end reste férié
+ALTER TABLE dim_date
+    ADD COLUMN day_type VARCHAR(8) NOT NULL GENERATED ALWAYS AS (
+        CASE WHEN is_holiday THEN 'holiday' WHEN is_weekend THEN 'weekend' ELSE 'workday' END
+    ) STORED;
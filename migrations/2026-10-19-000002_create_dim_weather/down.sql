@@ -0,0 +1 @@
+DROP TABLE dim_weather;
@@ -0,0 +1,17 @@
+-- Observations météo (NOAA) par date et heure, importées par
+-- `cargo run --bin import_weather`. Une observation journalière couvre les
+-- 24 heures de la date ; une observation horaire la remplace pour son heure.
+CREATE TABLE dim_weather (
+    weather_date DATE NOT NULL,
+    hour INT NOT NULL CHECK (hour BETWEEN 0 AND 23),
+    station VARCHAR(32) NOT NULL,
+    temperature_c DOUBLE PRECISION,
+    precipitation_mm DOUBLE PRECISION,
+    snowfall_mm DOUBLE PRECISION,
+    wind_speed_ms DOUBLE PRECISION,
+    -- dry, rain, heavy_rain, snow, fog ou unknown
+    condition VARCHAR(16) NOT NULL,
+    -- daily ou hourly
+    source VARCHAR(8) NOT NULL,
+    PRIMARY KEY (weather_date, hour)
+);
@@ -0,0 +1,323 @@
+// Trajets desservant les aéroports JFK, LaGuardia et Newark.
+//
+// Chaque trajet est rattaché à l'aéroport de départ (`from`) et/ou d'arrivée
+// (`to`) : un trajet JFK -> LGA compte donc dans les deux sens.
+use std::collections::BTreeMap;
+
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use diesel::sql_types::{Array, BigInt, Float8, Integer, Text};
+
+use crate::cube::{Bind, Join, SqlBuilder};
+use crate::db::DbPool;
+use crate::filters;
+use crate::handlers::AppError;
+use crate::models::{
+    AirportCode, AirportDurationDistribution, AirportFareTypeStats, AirportHourlyActivity, AirportParams,
+    AirportSummary, TripFilters,
+};
+
+/// `rate_code_id` du forfait JFK - Manhattan.
+pub const FLAT_RATE_CODE: i32 = 2;
+/// `rate_code_id` du tarif standard au compteur.
+pub const METERED_RATE_CODE: i32 = 1;
+/// Largeur des classes de l'histogramme des durées, en minutes.
+pub const DURATION_BUCKET_MINUTES: i32 = 5;
+/// Nombre de classes ; la dernière regroupe les durées au-delà de 2 h.
+pub const DURATION_BUCKETS: i32 = 25;
+
+// CTE `legs` : un enregistrement par trajet et par aéroport desservi
+fn legs(filters: &TripFilters, params: &AirportParams) -> SqlBuilder {
+    let mut builder = SqlBuilder::default();
+    let from_where = filters::from_where(
+        filters,
+        &mut builder,
+        &[Join::Date, Join::PickupLocation, Join::DropoffLocation, Join::RateCode],
+    );
+    let ids = AirportCode::ALL.iter().map(|a| a.location_id().to_string()).collect::<Vec<_>>().join(", ");
+    let airports = AirportCode::ALL
+        .iter()
+        .map(|a| format!("('{}', {})", a.code(), a.location_id()))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let mut conditions = Vec::new();
+    if let Some(airport) = params.airport {
+        conditions.push(format!("airport = {}", builder.bind(Bind::Text(airport.code().to_string()))));
+    }
+    if let Some(direction) = params.direction {
+        conditions.push(format!("direction = {}", builder.bind(Bind::Text(direction.as_str().to_string()))));
+    }
+    let legs_where = if conditions.is_empty() { String::new() } else { format!("WHERE {}", conditions.join(" AND ")) };
+
+    builder.sql = format!(
+        "WITH trips AS ( \
+            SELECT pul.location_id AS pickup_id, dol.location_id AS dropoff_id, \
+                d.day_of_week, EXTRACT(HOUR FROM f.tpep_pickup_datetime)::int AS hour_of_day, \
+                rc.rate_code_id, f.fare_amount, f.total_amount, f.trip_distance, \
+                EXTRACT(EPOCH FROM f.trip_duration) AS duration_seconds, \
+                COALESCE(f.tolls_amount, 0) AS tolls_amount, COALESCE(f.airport_fee, 0) AS airport_fee \
+            {from_where} AND (pul.location_id IN ({ids}) OR dol.location_id IN ({ids})) \
+        ), airports (airport, location_id) AS (VALUES {airports}), \
+        legs AS ( \
+            SELECT * FROM ( \
+                SELECT a.airport, 'from' AS direction, t.* FROM trips t JOIN airports a ON a.location_id = t.pickup_id \
+                UNION ALL \
+                SELECT a.airport, 'to' AS direction, t.* FROM trips t JOIN airports a ON a.location_id = t.dropoff_id \
+            ) l {legs_where} \
+        ) "
+    );
+    builder
+}
+
+/// Volumes, tarifs, péages et taxe aéroport par aéroport et par sens.
+pub fn airport_summary(
+    conn: &mut PgConnection,
+    filters: &TripFilters,
+    params: &AirportParams,
+) -> QueryResult<Vec<AirportSummary>> {
+    let mut builder = legs(filters, params);
+    builder.sql.push_str(&format!(
+        "SELECT \
+            airport, direction, \
+            COUNT(*)::bigint AS trip_count, \
+            COALESCE(AVG(fare_amount), 0.0)::float8 AS avg_fare, \
+            COALESCE(AVG(total_amount), 0.0)::float8 AS avg_total_amount, \
+            COALESCE(AVG(trip_distance), 0.0)::float8 AS avg_distance, \
+            COALESCE(AVG(duration_seconds), 0.0)::float8 AS avg_duration_seconds, \
+            COALESCE(AVG(tolls_amount), 0.0)::float8 AS avg_tolls, \
+            COALESCE(AVG(airport_fee), 0.0)::float8 AS avg_airport_fee, \
+            COALESCE(SUM(tolls_amount) / NULLIF(SUM(total_amount), 0), 0.0)::float8 AS tolls_share, \
+            COALESCE(SUM(airport_fee) / NULLIF(SUM(total_amount), 0), 0.0)::float8 AS airport_fee_share, \
+            AVG((rate_code_id IS NOT DISTINCT FROM {FLAT_RATE_CODE})::int)::float8 AS flat_rate_share \
+        FROM legs \
+        GROUP BY airport, direction \
+        ORDER BY airport, direction"
+    ));
+    builder.into_query().load::<AirportSummary>(conn)
+}
+
+/// Trajets par jour de la semaine et heure de prise en charge (trajets sans heure
+/// de prise en charge exclus).
+pub fn airport_hourly_activity(
+    conn: &mut PgConnection,
+    filters: &TripFilters,
+    params: &AirportParams,
+) -> QueryResult<Vec<AirportHourlyActivity>> {
+    let mut builder = legs(filters, params);
+    builder.sql.push_str(
+        "SELECT \
+            airport, direction, day_of_week, hour_of_day, \
+            COUNT(*)::bigint AS trip_count, \
+            COALESCE(AVG(fare_amount), 0.0)::float8 AS avg_fare, \
+            COALESCE(AVG(duration_seconds), 0.0)::float8 AS avg_duration_seconds \
+        FROM legs \
+        WHERE hour_of_day IS NOT NULL \
+        GROUP BY airport, direction, day_of_week, hour_of_day \
+        ORDER BY airport, direction, day_of_week, hour_of_day",
+    );
+    builder.into_query().load::<AirportHourlyActivity>(conn)
+}
+
+/// Comparaison forfait / compteur / autres tarifs.
+pub fn airport_fare_types(
+    conn: &mut PgConnection,
+    filters: &TripFilters,
+    params: &AirportParams,
+) -> QueryResult<Vec<AirportFareTypeStats>> {
+    let mut builder = legs(filters, params);
+    builder.sql.push_str(&format!(
+        "SELECT \
+            airport, direction, \
+            CASE rate_code_id WHEN {FLAT_RATE_CODE} THEN 'flat_rate' WHEN {METERED_RATE_CODE} THEN 'metered' ELSE 'other' END AS fare_type, \
+            COUNT(*)::bigint AS trip_count, \
+            COALESCE(AVG(fare_amount), 0.0)::float8 AS avg_fare, \
+            COALESCE(AVG(total_amount), 0.0)::float8 AS avg_total_amount, \
+            COALESCE(AVG(trip_distance), 0.0)::float8 AS avg_distance, \
+            COALESCE(AVG(duration_seconds), 0.0)::float8 AS avg_duration_seconds, \
+            (SUM(fare_amount) / NULLIF(SUM(trip_distance), 0))::float8 AS fare_per_mile \
+        FROM legs \
+        GROUP BY 1, 2, 3 \
+        ORDER BY 1, 2, 3"
+    ));
+    builder.into_query().load::<AirportFareTypeStats>(conn)
+}
+
+#[derive(QueryableByName)]
+struct DurationQuantiles {
+    #[diesel(sql_type = Text)]
+    airport: String,
+    #[diesel(sql_type = Text)]
+    direction: String,
+    #[diesel(sql_type = BigInt)]
+    trip_count: i64,
+    #[diesel(sql_type = Array<Float8>)]
+    quantiles: Vec<f64>,
+}
+
+#[derive(QueryableByName)]
+struct DurationBucket {
+    #[diesel(sql_type = Text)]
+    airport: String,
+    #[diesel(sql_type = Text)]
+    direction: String,
+    #[diesel(sql_type = Integer)]
+    bucket: i32,
+    #[diesel(sql_type = BigInt)]
+    trip_count: i64,
+}
+
+/// Quantiles et histogramme des durées (trajets de durée positive).
+pub fn airport_duration_distribution(
+    conn: &mut PgConnection,
+    filters: &TripFilters,
+    params: &AirportParams,
+) -> QueryResult<Vec<AirportDurationDistribution>> {
+    let mut builder = legs(filters, params);
+    builder.sql.push_str(
+        "SELECT \
+            airport, direction, COUNT(*)::bigint AS trip_count, \
+            percentile_cont(ARRAY[0.1, 0.25, 0.5, 0.75, 0.9]) WITHIN GROUP (ORDER BY duration_seconds / 60.0)::float8[] AS quantiles \
+        FROM legs \
+        WHERE duration_seconds > 0 \
+        GROUP BY airport, direction \
+        ORDER BY airport, direction",
+    );
+    let quantiles = builder.into_query().load::<DurationQuantiles>(conn)?;
+
+    let mut builder = legs(filters, params);
+    builder.sql.push_str(&format!(
+        "SELECT \
+            airport, direction, \
+            LEAST(FLOOR(duration_seconds / 60.0 / {DURATION_BUCKET_MINUTES}), {last})::int AS bucket, \
+            COUNT(*)::bigint AS trip_count \
+        FROM legs \
+        WHERE duration_seconds > 0 \
+        GROUP BY 1, 2, 3",
+        last = DURATION_BUCKETS - 1
+    ));
+    let mut histograms: BTreeMap<(String, String), Vec<i64>> = BTreeMap::new();
+    for bucket in builder.into_query().load::<DurationBucket>(conn)? {
+        let histogram = histograms
+            .entry((bucket.airport, bucket.direction))
+            .or_insert_with(|| vec![0; DURATION_BUCKETS as usize]);
+        histogram[bucket.bucket as usize] = bucket.trip_count;
+    }
+
+    Ok(quantiles
+        .into_iter()
+        .map(|q| {
+            let histogram = histograms.remove(&(q.airport.clone(), q.direction.clone())).unwrap_or_default();
+            let quantile = |i: usize| q.quantiles.get(i).copied().unwrap_or(0.0);
+            AirportDurationDistribution {
+                p10_minutes: quantile(0),
+                p25_minutes: quantile(1),
+                p50_minutes: quantile(2),
+                p75_minutes: quantile(3),
+                p90_minutes: quantile(4),
+                airport: q.airport,
+                direction: q.direction,
+                trip_count: q.trip_count,
+                bucket_minutes: DURATION_BUCKET_MINUTES,
+                histogram,
+            }
+        })
+        .collect())
+}
+
+// Handler pour la vue d'ensemble des trajets aéroport
+#[utoipa::path(
+    get,
+    path = "/api/v1/airports/summary",
+    tag = "airports",
+    params(TripFilters, AirportParams),
+    responses(
+        (status = 200, description = "Volumes, tarifs, péages et taxe aéroport par aéroport et par sens", body = Vec<AirportSummary>),
+        (status = 400, description = "Paramètres invalides", body = String),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
+pub async fn get_airport_summary(
+    State(pool): State<DbPool>,
+    Query(filters): Query<TripFilters>,
+    Query(params): Query<AirportParams>,
+) -> Result<Json<Vec<AirportSummary>>, AppError> {
+    filters::validate(&filters)?;
+    let mut conn = pool.get().map_err(|e| AppError(anyhow::Error::new(e)))?;
+    let results = tokio::task::spawn_blocking(move || airport_summary(&mut conn, &filters, &params)).await??;
+    Ok(Json(results))
+}
+
+// Handler pour l'activité aéroport par jour et par heure
+#[utoipa::path(
+    get,
+    path = "/api/v1/airports/hourly",
+    tag = "airports",
+    params(TripFilters, AirportParams),
+    responses(
+        (status = 200, description = "Trajets par jour de la semaine et heure", body = Vec<AirportHourlyActivity>),
+        (status = 400, description = "Paramètres invalides", body = String),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
+pub async fn get_airport_hourly_activity(
+    State(pool): State<DbPool>,
+    Query(filters): Query<TripFilters>,
+    Query(params): Query<AirportParams>,
+) -> Result<Json<Vec<AirportHourlyActivity>>, AppError> {
+    filters::validate(&filters)?;
+    let mut conn = pool.get().map_err(|e| AppError(anyhow::Error::new(e)))?;
+    let results = tokio::task::spawn_blocking(move || airport_hourly_activity(&mut conn, &filters, &params)).await??;
+    Ok(Json(results))
+}
+
+// Handler pour la comparaison forfait / compteur
+#[utoipa::path(
+    get,
+    path = "/api/v1/airports/fare_types",
+    tag = "airports",
+    params(TripFilters, AirportParams),
+    responses(
+        (status = 200, description = "Forfait JFK, compteur et autres tarifs", body = Vec<AirportFareTypeStats>),
+        (status = 400, description = "Paramètres invalides", body = String),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
+pub async fn get_airport_fare_types(
+    State(pool): State<DbPool>,
+    Query(filters): Query<TripFilters>,
+    Query(params): Query<AirportParams>,
+) -> Result<Json<Vec<AirportFareTypeStats>>, AppError> {
+    filters::validate(&filters)?;
+    let mut conn = pool.get().map_err(|e| AppError(anyhow::Error::new(e)))?;
+    let results = tokio::task::spawn_blocking(move || airport_fare_types(&mut conn, &filters, &params)).await??;
+    Ok(Json(results))
+}
+
+// Handler pour la distribution des durées des trajets aéroport
+#[utoipa::path(
+    get,
+    path = "/api/v1/airports/durations",
+    tag = "airports",
+    params(TripFilters, AirportParams),
+    responses(
+        (status = 200, description = "Quantiles et histogramme des durées (minutes)", body = Vec<AirportDurationDistribution>),
+        (status = 400, description = "Paramètres invalides", body = String),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
+pub async fn get_airport_durations(
+    State(pool): State<DbPool>,
+    Query(filters): Query<TripFilters>,
+    Query(params): Query<AirportParams>,
+) -> Result<Json<Vec<AirportDurationDistribution>>, AppError> {
+    filters::validate(&filters)?;
+    let mut conn = pool.get().map_err(|e| AppError(anyhow::Error::new(e)))?;
+    let results =
+        tokio::task::spawn_blocking(move || airport_duration_distribution(&mut conn, &filters, &params)).await??;
+    Ok(Json(results))
+}
@@ -0,0 +1,312 @@
+// Détection d'anomalies sur les séries journalières de trajets et de recettes.
+//
+// Deux méthodes, toutes deux robustes aux valeurs extrêmes :
+// - `seasonal` : tendance par médiane glissante centrée, saisonnalité par jour
+//   de la semaine (médiane des écarts à la tendance), score du résidu ;
+// - `robust_z` : comparaison de chaque jour à la médiane des mêmes jours de la
+//   semaine sur la fenêtre précédente (détection « en ligne »).
+// Les scores sont exprimés en écarts-types robustes (1,4826 x MAD).
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use chrono::{Datelike, Duration, NaiveDate};
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Date, Float8};
+
+use crate::cube::{Join, SqlBuilder};
+use crate::db::DbPool;
+use crate::filters;
+use crate::handlers::AppError;
+use crate::models::{Anomaly, AnomalyMethod, AnomalyMetric, AnomalyParams, AnomalyReport, TripFilters};
+
+const DEFAULT_THRESHOLD: f64 = 3.5;
+const DEFAULT_WINDOW_DAYS: i64 = 28;
+/// Historique minimal par jour de la semaine pour la méthode `robust_z`.
+pub const MIN_HISTORY: usize = 3;
+// Facteur MAD -> écart-type pour une loi normale
+const MAD_SCALE: f64 = 1.4826;
+// Dispersion minimale, relative à la valeur attendue (séries quasi constantes)
+const MIN_RELATIVE_SCALE: f64 = 0.01;
+
+#[derive(QueryableByName)]
+struct DailyTotals {
+    #[diesel(sql_type = Date)]
+    date: NaiveDate,
+    #[diesel(sql_type = BigInt)]
+    trip_count: i64,
+    #[diesel(sql_type = Float8)]
+    revenue: f64,
+}
+
+/// Série journalière de `metric`, jours sans trajet inclus (valeur nulle)
+/// entre le premier et le dernier jour observés.
+pub fn daily_series(
+    conn: &mut PgConnection,
+    filters: &TripFilters,
+    metric: AnomalyMetric,
+) -> QueryResult<Vec<(NaiveDate, f64)>> {
+    let mut builder = SqlBuilder::default();
+    let from_where = filters::from_where(filters, &mut builder, &[Join::Date]);
+    builder.sql = format!(
+        "SELECT \
+            d.full_date AS date, \
+            COUNT(f.trip_id)::bigint AS trip_count, \
+            COALESCE(SUM(f.total_amount), 0.0)::float8 AS revenue \
+        {from_where} \
+        GROUP BY d.full_date \
+        ORDER BY d.full_date"
+    );
+    let rows = builder.into_query().load::<DailyTotals>(conn)?;
+
+    let mut series: Vec<(NaiveDate, f64)> = Vec::with_capacity(rows.len());
+    for row in rows {
+        if let Some(&(last, _)) = series.last() {
+            let mut day = last + Duration::days(1);
+            while day < row.date {
+                series.push((day, 0.0));
+                day += Duration::days(1);
+            }
+        }
+        let value = match metric {
+            AnomalyMetric::TripCount => row.trip_count as f64,
+            AnomalyMetric::Revenue => row.revenue,
+        };
+        series.push((row.date, value));
+    }
+    Ok(series)
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+// Écart-type robuste des valeurs autour de leur médiane, borné par le bas
+// par une fraction de `reference`
+fn robust_scale(values: &[f64], reference: f64) -> f64 {
+    let center = median(&mut values.to_vec());
+    let mut deviations: Vec<f64> = values.iter().map(|v| (v - center).abs()).collect();
+    (MAD_SCALE * median(&mut deviations))
+        .max(MIN_RELATIVE_SCALE * reference.abs())
+        .max(f64::EPSILON)
+}
+
+fn weekday(date: NaiveDate) -> usize {
+    date.weekday().num_days_from_monday() as usize
+}
+
+// Valeur attendue et écart-type robuste de chaque jour (None : historique insuffisant)
+fn seasonal_expectations(series: &[(NaiveDate, f64)], window_days: usize) -> Vec<Option<(f64, f64)>> {
+    let values: Vec<f64> = series.iter().map(|&(_, v)| v).collect();
+    // Fenêtre de semaines entières, décalée plutôt que tronquée aux extrémités :
+    // une fenêtre tronquée surreprésente certains jours de la semaine et biaise
+    // la tendance des premiers et derniers jours
+    let len = (window_days / 7 * 7).max(7).min(values.len());
+    let half = len / 2;
+    let trend: Vec<f64> = (0..values.len())
+        .map(|i| {
+            let start = i.saturating_sub(half).min(values.len() - len);
+            median(&mut values[start..start + len].to_vec())
+        })
+        .collect();
+
+    let mut by_weekday: [Vec<f64>; 7] = Default::default();
+    for (i, &(date, value)) in series.iter().enumerate() {
+        by_weekday[weekday(date)].push(value - trend[i]);
+    }
+    let seasonal: Vec<f64> = by_weekday.iter_mut().map(|r| median(r)).collect();
+
+    let expected: Vec<f64> = series
+        .iter()
+        .enumerate()
+        .map(|(i, &(date, _))| trend[i] + seasonal[weekday(date)])
+        .collect();
+    let residuals: Vec<f64> = values.iter().zip(&expected).map(|(v, e)| v - e).collect();
+    let scale = robust_scale(&residuals, median(&mut values.clone()));
+    expected.into_iter().map(|e| Some((e, scale))).collect()
+}
+
+// Valeur attendue : médiane des mêmes jours de la semaine sur la fenêtre
+// précédente ; dispersion : écarts de tous les jours de la fenêtre à la
+// médiane de leur jour de la semaine (plus stable que 4 valeurs seulement)
+fn trailing_expectations(series: &[(NaiveDate, f64)], window_days: usize) -> Vec<Option<(f64, f64)>> {
+    (0..series.len())
+        .map(|i| {
+            // Série sans trou : l'indice modulo 7 identifie le jour de la semaine
+            let history = &series[i.saturating_sub(window_days)..i];
+            let mut by_weekday: [Vec<f64>; 7] = Default::default();
+            for (j, &(_, value)) in history.iter().enumerate() {
+                by_weekday[(history.len() - j) % 7].push(value);
+            }
+            if by_weekday.iter().any(|values| values.len() < MIN_HISTORY) {
+                return None;
+            }
+            let medians: Vec<f64> = by_weekday.iter().map(|values| median(&mut values.clone())).collect();
+            let residuals: Vec<f64> = by_weekday
+                .iter()
+                .zip(&medians)
+                .flat_map(|(values, m)| values.iter().map(move |v| v - m))
+                .collect();
+            let expected = medians[0];
+            Some((expected, robust_scale(&residuals, expected)))
+        })
+        .collect()
+}
+
+/// Jours dont l'écart à la valeur attendue dépasse `threshold` écarts-types robustes.
+pub fn detect_anomalies(
+    series: &[(NaiveDate, f64)],
+    method: AnomalyMethod,
+    threshold: f64,
+    window_days: usize,
+) -> Vec<Anomaly> {
+    let expectations = match method {
+        AnomalyMethod::Seasonal => seasonal_expectations(series, window_days),
+        AnomalyMethod::RobustZ => trailing_expectations(series, window_days),
+    };
+    series
+        .iter()
+        .zip(expectations)
+        .filter_map(|(&(date, value), expectation)| {
+            let (expected, scale) = expectation?;
+            let deviation = value - expected;
+            let score = deviation / scale;
+            (score.abs() >= threshold).then(|| Anomaly {
+                date,
+                value,
+                expected,
+                deviation,
+                relative_deviation: (expected != 0.0).then(|| deviation / expected),
+                score,
+            })
+        })
+        .collect()
+}
+
+// Handler pour la détection d'anomalies journalières
+#[utoipa::path(
+    get,
+    path = "/api/v1/anomalies",
+    tag = "analytics",
+    params(TripFilters, AnomalyParams),
+    responses(
+        (status = 200, description = "Jours anormaux avec valeur attendue, écart et score robuste", body = AnomalyReport),
+        (status = 400, description = "Paramètres invalides", body = String),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
+pub async fn get_anomalies(
+    State(pool): State<DbPool>,
+    Query(filters): Query<TripFilters>,
+    Query(params): Query<AnomalyParams>,
+) -> Result<Json<AnomalyReport>, AppError> {
+    filters::validate(&filters)?;
+    let threshold = params.threshold.unwrap_or(DEFAULT_THRESHOLD);
+    if !threshold.is_finite() || threshold <= 0.0 {
+        return Err(AppError::bad_request("threshold strictement positif requis"));
+    }
+    let metric = params.metric.unwrap_or_default();
+    let method = params.method.unwrap_or_default();
+    // `robust_z` : au moins MIN_HISTORY semaines d'historique
+    let min_window = match method {
+        AnomalyMethod::Seasonal => 7,
+        AnomalyMethod::RobustZ => 7 * MIN_HISTORY as i64,
+    };
+    let window_days = params.window_days.unwrap_or(DEFAULT_WINDOW_DAYS);
+    if !(min_window..=365).contains(&window_days) {
+        return Err(AppError::bad_request(format!("window_days entre {min_window} et 365 requis")));
+    }
+    let mut conn = pool.get().map_err(|e| AppError(anyhow::Error::new(e)))?;
+    let series = tokio::task::spawn_blocking(move || daily_series(&mut conn, &filters, metric)).await??;
+    Ok(Json(AnomalyReport {
+        metric,
+        method,
+        threshold,
+        window_days,
+        days: series.len() as i64,
+        anomalies: detect_anomalies(&series, method, threshold, window_days as usize),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WEEK: [f64; 7] = [1000.0, 950.0, 980.0, 1010.0, 1100.0, 1300.0, 1200.0];
+    const SPIKE_DAY: usize = 45;
+
+    // Série hebdomadaire sur `days` jours à partir d'un lundi, bruit déterministe de ±20
+    fn series(days: usize) -> Vec<(NaiveDate, f64)> {
+        let start = NaiveDate::from_ymd_opt(2024, 9, 2).unwrap();
+        (0..days)
+            .map(|i| {
+                let noise = ((i as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 32) as f64 % 41.0 - 20.0;
+                (start + Duration::days(i as i64), WEEK[i % 7] + noise)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn median_of_odd_and_even_lengths() {
+        assert_eq!(median(&mut [3.0, 1.0, 2.0]), 2.0);
+        assert_eq!(median(&mut [4.0, 1.0, 3.0, 2.0]), 2.5);
+        assert_eq!(median(&mut [7.0]), 7.0);
+        assert_eq!(median(&mut []), 0.0);
+    }
+
+    #[test]
+    fn flat_series_uses_relative_floor() {
+        // MAD nulle : dispersion ramenée à 1 % de la référence, puis à EPSILON
+        assert_eq!(robust_scale(&[100.0; 10], 100.0), MIN_RELATIVE_SCALE * 100.0);
+        assert_eq!(robust_scale(&[0.0; 10], 0.0), f64::EPSILON);
+
+        let start = NaiveDate::from_ymd_opt(2024, 9, 2).unwrap();
+        let flat: Vec<_> = (0..56).map(|i| (start + Duration::days(i), 500.0)).collect();
+        for method in [AnomalyMethod::Seasonal, AnomalyMethod::RobustZ] {
+            assert!(detect_anomalies(&flat, method, DEFAULT_THRESHOLD, 28).is_empty());
+        }
+    }
+
+    #[test]
+    fn single_spike_is_flagged_by_both_methods() {
+        let mut data = series(63);
+        let baseline = data[SPIKE_DAY].1;
+        data[SPIKE_DAY].1 += 600.0;
+        for method in [AnomalyMethod::Seasonal, AnomalyMethod::RobustZ] {
+            let anomalies = detect_anomalies(&data, method, DEFAULT_THRESHOLD, 28);
+            assert_eq!(anomalies.len(), 1, "{method:?}: {anomalies:?}");
+            let spike = &anomalies[0];
+            assert_eq!(spike.date, data[SPIKE_DAY].0);
+            assert!(spike.score > DEFAULT_THRESHOLD);
+            assert!((spike.expected - baseline).abs() < 50.0, "{method:?}: {}", spike.expected);
+        }
+    }
+
+    // Y compris les premiers et derniers jours, où la fenêtre de tendance est décalée
+    #[test]
+    fn noisy_weekly_series_has_no_anomaly() {
+        let data = series(63);
+        for method in [AnomalyMethod::Seasonal, AnomalyMethod::RobustZ] {
+            assert!(detect_anomalies(&data, method, DEFAULT_THRESHOLD, 28).is_empty(), "{method:?}");
+        }
+    }
+
+    #[test]
+    fn robust_z_needs_history() {
+        let data = series(63);
+        let expectations = trailing_expectations(&data, 28);
+        // MIN_HISTORY semaines complètes avant la première valeur attendue
+        assert!(expectations[..7 * MIN_HISTORY].iter().all(Option::is_none));
+        assert!(expectations[7 * MIN_HISTORY..].iter().all(Option::is_some));
+    }
+}
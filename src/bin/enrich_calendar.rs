@@ -0,0 +1,21 @@
+// Renseigne les jours fériés, jours de classe et changements d'heure de
+// `dim_date` (migration `add_dim_date_calendar` appliquée au préalable).
+//
+//     cargo run --bin enrich_calendar
+use data_viz::{calendar, db};
+
+fn main() -> anyhow::Result<()> {
+    let pool = db::create_pool();
+    let mut conn = pool.get()?;
+    let dates = calendar::dates(&mut conn)?;
+    let rows = calendar::compute(&dates);
+    let stored = calendar::store(&mut conn, &rows)?;
+
+    let holidays = rows.iter().filter(|r| r.is_holiday).count();
+    let school_days = rows.iter().filter(|r| r.is_school_day).count();
+    println!(
+        "{} dates mises à jour ({} fériés, {} jours de classe)",
+        stored, holidays, school_days
+    );
+    Ok(())
+}
@@ -0,0 +1,33 @@
+// Calcule centroïdes, superficies et voisinages des zones de taxi et les
+// enregistre dans `dim_location_geo`.
+//
+//     cargo run --bin enrich_locations [chemin/vers/taxi_zones.shp|.geojson]
+//
+// Sans argument, utilise `TAXI_ZONES_PATH` ou les fichiers de `data/`.
+use std::path::PathBuf;
+
+use anyhow::Context;
+use data_viz::{db, location_geo, taxi_zones};
+
+fn main() -> anyhow::Result<()> {
+    let pool = db::create_pool();
+    let path = std::env::args()
+        .nth(1)
+        .map(PathBuf::from)
+        .or_else(taxi_zones::configured_path)
+        .with_context(|| format!("aucun fichier de zones ({} ou TAXI_ZONES_PATH)", taxi_zones::DEFAULT_PATHS.join(", ")))?;
+
+    let zones = taxi_zones::load(&path)?;
+    let rows = location_geo::compute(&zones);
+    let mut conn = pool.get()?;
+    let stored = location_geo::store(&mut conn, &rows)?;
+
+    let isolated = rows.iter().filter(|r| r.neighbours.is_empty()).count();
+    println!(
+        "{} zones enregistrées depuis {} ({} sans voisin)",
+        stored,
+        path.display(),
+        isolated
+    );
+    Ok(())
+}
@@ -0,0 +1,51 @@
+// Importe des observations météo NOAA (CSV GHCN-Daily ou LCD horaires) dans
+// `dim_weather` (migration `create_dim_weather` appliquée au préalable).
+//
+//     cargo run --bin import_weather [--station USW00094728] [--metric] [fichier.csv ...]
+//
+// Sans fichier, importe les `.csv` de `data/weather/`. Les fichiers
+// journaliers sont importés avant les horaires, qui les précisent.
+use std::path::PathBuf;
+
+use anyhow::Context;
+use data_viz::{db, weather};
+
+fn main() -> anyhow::Result<()> {
+    let mut station = None;
+    let mut units = weather::Units::Standard;
+    let mut paths = Vec::new();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--station" => station = Some(args.next().context("--station attend un identifiant")?),
+            "--metric" => units = weather::Units::Metric,
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+    if paths.is_empty() {
+        for entry in std::fs::read_dir(weather::DEFAULT_DIR).with_context(|| format!("lecture de {}", weather::DEFAULT_DIR))? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|e| e.eq_ignore_ascii_case("csv")) {
+                paths.push(path);
+            }
+        }
+    }
+
+    let mut observations = Vec::new();
+    for path in &paths {
+        let rows = weather::load(path, station.as_deref(), units)?;
+        println!("{} : {} heures", path.display(), rows.len());
+        observations.push(rows);
+    }
+    // Journalier d'abord : les observations horaires le remplacent
+    observations.sort_by_key(|rows| rows.first().map(|r| r.source == "hourly"));
+
+    let pool = db::create_pool();
+    let mut conn = pool.get()?;
+    let mut stored = 0;
+    for rows in &observations {
+        stored += weather::store(&mut conn, rows)?;
+    }
+    println!("{} heures enregistrées dans dim_weather", stored);
+    Ok(())
+}
@@ -0,0 +1,223 @@
+// Enrichissement calendaire de `dim_date` : jours fériés fédéraux et de
+// l'État de New York, jours de classe des écoles publiques de la ville et
+// changements d'heure (voir `src/bin/enrich_calendar.rs`). La colonne
+// `day_type` (workday / weekend / holiday) en est dérivée par PostgreSQL.
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+
+use crate::schema::dim_date;
+
+// Fin d'année scolaire (jour de juin, ou dernier jour de semaine qui le précède)
+const SCHOOL_YEAR_LAST_JUNE_DAY: u32 = 26;
+
+#[derive(Debug, Clone, PartialEq, AsChangeset)]
+#[diesel(table_name = dim_date, primary_key(full_date), treat_none_as_null = true)]
+pub struct CalendarDay {
+    pub full_date: NaiveDate,
+    pub is_holiday: bool,
+    pub holiday_name: Option<String>,
+    pub is_school_day: bool,
+    pub is_dst_transition: bool,
+}
+
+fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).expect("date valide")
+}
+
+// n-ième `weekday` du mois (n >= 1)
+fn nth_weekday(year: i32, month: u32, weekday: Weekday, n: u8) -> NaiveDate {
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, n).expect("jour existant")
+}
+
+fn last_weekday(year: i32, month: u32, weekday: Weekday) -> NaiveDate {
+    let mut day = nth_weekday(year, month, weekday, 4);
+    while (day + Duration::days(7)).month() == month {
+        day += Duration::days(7);
+    }
+    day
+}
+
+// Dimanche de Pâques (algorithme grégorien anonyme)
+fn easter(year: i32) -> NaiveDate {
+    let (a, b, c) = (year % 19, year / 100, year % 100);
+    let (d, e) = (b / 4, b % 4);
+    let g = (8 * b + 13) / 25;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let (i, k) = (c / 4, c % 4);
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 19 * l) / 433;
+    let month = (h + l - 7 * m + 90) / 25;
+    let day = (h + l - 7 * m + 33 * month + 19) % 32;
+    date(year, month as u32, day as u32)
+}
+
+/// Jours fériés de l'année : date effective et, pour les fêtes à date fixe
+/// tombant un week-end, jour chômé (vendredi ou lundi, « (observed) »).
+pub fn holidays(year: i32) -> Vec<(NaiveDate, String)> {
+    let mut fixed = vec![
+        (date(year, 1, 1), "New Year's Day"),
+        // État de New York
+        (date(year, 2, 12), "Lincoln's Birthday"),
+        (date(year, 7, 4), "Independence Day"),
+        (date(year, 11, 11), "Veterans Day"),
+        (date(year, 12, 25), "Christmas Day"),
+    ];
+    if year >= 2021 {
+        fixed.push((date(year, 6, 19), "Juneteenth"));
+    }
+    let mut days: Vec<(NaiveDate, String)> = vec![
+        (nth_weekday(year, 1, Weekday::Mon, 3), "Martin Luther King Jr. Day".into()),
+        (nth_weekday(year, 2, Weekday::Mon, 3), "Washington's Birthday".into()),
+        (last_weekday(year, 5, Weekday::Mon), "Memorial Day".into()),
+        (nth_weekday(year, 9, Weekday::Mon, 1), "Labor Day".into()),
+        (nth_weekday(year, 10, Weekday::Mon, 2), "Columbus Day".into()),
+        // État de New York : mardi suivant le premier lundi de novembre
+        (nth_weekday(year, 11, Weekday::Mon, 1) + Duration::days(1), "Election Day".into()),
+        (nth_weekday(year, 11, Weekday::Thu, 4), "Thanksgiving Day".into()),
+    ];
+    for (day, name) in fixed {
+        days.push((day, name.into()));
+        let observed = match day.weekday() {
+            Weekday::Sat => Some(day - Duration::days(1)),
+            Weekday::Sun => Some(day + Duration::days(1)),
+            _ => None,
+        };
+        if let Some(observed) = observed {
+            days.push((observed, format!("{name} (observed)")));
+        }
+    }
+    days.sort();
+    days
+}
+
+// Vacances scolaires (approximation du calendrier du NYC DOE) : fin d'année,
+// semaine de Washington's Birthday, semaine précédant Pâques, lendemain de
+// Thanksgiving
+fn is_school_recess(day: NaiveDate) -> bool {
+    let year = day.year();
+    let presidents_day = nth_weekday(year, 2, Weekday::Mon, 3);
+    let holy_monday = easter(year) - Duration::days(6);
+    let winter = (day.month() == 12 && day.day() >= 24) || (day.month() == 1 && day.day() == 1);
+    winter
+        || (presidents_day..presidents_day + Duration::days(5)).contains(&day)
+        || (holy_monday..holy_monday + Duration::days(5)).contains(&day)
+        || day == nth_weekday(year, 11, Weekday::Thu, 4) + Duration::days(1)
+}
+
+// Année scolaire : du jeudi suivant Labor Day au 26 juin suivant
+fn is_school_term(day: NaiveDate) -> bool {
+    let start_year = if day.month() >= 7 { day.year() } else { day.year() - 1 };
+    let start = nth_weekday(start_year, 9, Weekday::Mon, 1) + Duration::days(3);
+    let end = date(start_year + 1, 6, SCHOOL_YEAR_LAST_JUNE_DAY);
+    (start..=end).contains(&day)
+}
+
+// Passage à l'heure d'été (2e dimanche de mars) ou d'hiver (1er dimanche de novembre)
+fn is_dst_transition(day: NaiveDate) -> bool {
+    day == nth_weekday(day.year(), 3, Weekday::Sun, 2) || day == nth_weekday(day.year(), 11, Weekday::Sun, 1)
+}
+
+/// Attributs calendaires des dates données, dans le même ordre.
+pub fn compute(dates: &[NaiveDate]) -> Vec<CalendarDay> {
+    let mut names: BTreeMap<NaiveDate, Vec<String>> = BTreeMap::new();
+    let years = dates.iter().map(|d| d.year());
+    if let (Some(first), Some(last)) = (years.clone().min(), years.max()) {
+        // Années voisines : 1er janvier chômé le 31 décembre précédent
+        for year in first - 1..=last + 1 {
+            for (day, name) in holidays(year) {
+                names.entry(day).or_default().push(name);
+            }
+        }
+    }
+    dates
+        .iter()
+        .map(|&day| {
+            let holiday_name = names.get(&day).map(|n| n.join(", "));
+            let weekend = matches!(day.weekday(), Weekday::Sat | Weekday::Sun);
+            CalendarDay {
+                full_date: day,
+                is_holiday: holiday_name.is_some(),
+                is_school_day: !weekend && holiday_name.is_none() && is_school_term(day) && !is_school_recess(day),
+                holiday_name,
+                is_dst_transition: is_dst_transition(day),
+            }
+        })
+        .collect()
+}
+
+/// Dates présentes dans `dim_date`.
+pub fn dates(conn: &mut PgConnection) -> QueryResult<Vec<NaiveDate>> {
+    dim_date::table.select(dim_date::full_date).order(dim_date::full_date).load(conn)
+}
+
+/// Met à jour les attributs calendaires de `dim_date` ; renvoie le nombre de
+/// lignes modifiées.
+pub fn store(conn: &mut PgConnection, rows: &[CalendarDay]) -> QueryResult<usize> {
+    conn.transaction(|conn| {
+        let mut updated = 0;
+        for row in rows {
+            updated += diesel::update(dim_date::table.filter(dim_date::full_date.eq(row.full_date)))
+                .set(row)
+                .execute(conn)?;
+        }
+        Ok(updated)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn holiday_named(year: i32, name: &str) -> Vec<NaiveDate> {
+        holidays(year).into_iter().filter(|(_, n)| n == name).map(|(d, _)| d).collect()
+    }
+
+    #[test]
+    fn easter_dates() {
+        assert_eq!(easter(2000), date(2000, 4, 23));
+        assert_eq!(easter(2024), date(2024, 3, 31));
+        assert_eq!(easter(2025), date(2025, 4, 20));
+        assert_eq!(easter(2038), date(2038, 4, 25));
+    }
+
+    #[test]
+    fn floating_holidays_2024() {
+        assert_eq!(holiday_named(2024, "Thanksgiving Day"), vec![date(2024, 11, 28)]);
+        assert_eq!(holiday_named(2024, "Election Day"), vec![date(2024, 11, 5)]);
+        assert_eq!(holiday_named(2024, "Memorial Day"), vec![date(2024, 5, 27)]);
+        assert_eq!(holiday_named(2024, "Martin Luther King Jr. Day"), vec![date(2024, 1, 15)]);
+    }
+
+    #[test]
+    fn weekend_holidays_are_observed() {
+        // 12 février 2022 : samedi, chômé le vendredi
+        assert_eq!(holiday_named(2022, "Lincoln's Birthday (observed)"), vec![date(2022, 2, 11)]);
+        // 12 février 2023 : dimanche, chômé le lundi
+        assert_eq!(holiday_named(2023, "Lincoln's Birthday (observed)"), vec![date(2023, 2, 13)]);
+        // 12 février 2024 : lundi, pas de jour chômé distinct
+        assert!(holiday_named(2024, "Lincoln's Birthday (observed)").is_empty());
+        assert!(holiday_named(2020, "Juneteenth").is_empty());
+    }
+
+    #[test]
+    fn dst_transitions_2024() {
+        assert!(is_dst_transition(date(2024, 3, 10)));
+        assert!(is_dst_transition(date(2024, 11, 3)));
+        assert!(!is_dst_transition(date(2024, 3, 3)));
+        assert!(!is_dst_transition(date(2024, 11, 10)));
+    }
+
+    #[test]
+    fn compute_flags() {
+        let rows = compute(&[date(2024, 11, 28), date(2024, 11, 29), date(2024, 12, 2), date(2025, 1, 4)]);
+        assert_eq!(rows[0].holiday_name.as_deref(), Some("Thanksgiving Day"));
+        assert!(!rows[0].is_school_day);
+        // Lendemain de Thanksgiving : vacances scolaires
+        assert!(!rows[1].is_holiday && !rows[1].is_school_day);
+        assert!(rows[2].is_school_day);
+        assert!(!rows[3].is_school_day);
+    }
+}
@@ -0,0 +1,244 @@
+// Comparaison A / B de deux segments de trajets définis par deux jeux de
+// filtres : effectifs, revenu, indicateurs par trajet (moyenne, écart-type,
+// quantiles) et significativité des écarts.
+//
+// Les moments et quantiles sont calculés en SQL sur tous les trajets ; le
+// test de Mann-Whitney porte sur un échantillon déterministe (un trajet sur
+// k selon `hashtext(trip_id)`) d'au plus `MAX_SAMPLE` trajets par segment.
+// L'ensemble des requêtes s'exécute dans une transaction bornée par
+// `cube::STATEMENT_TIMEOUT`.
+use axum::{extract::rejection::JsonRejection, extract::State, Json};
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use diesel::sql_types::{Array, BigInt, Float8, Nullable};
+
+use crate::cube::{SqlBuilder, STATEMENT_TIMEOUT};
+use crate::db::DbPool;
+use crate::filters;
+use crate::handlers::AppError;
+use crate::models::{
+    CompareMetric, CompareRequest, CompareResponse, MannWhitneyTest, MetricComparison, MetricStats, SegmentSummary,
+    TripFilters, WelchTest,
+};
+use crate::stats::{self, Moments};
+
+// Taille maximale de l'échantillon de chaque segment pour Mann-Whitney
+const MAX_SAMPLE: i64 = 50_000;
+
+// Valeur par trajet de l'indicateur (NULL quand il n'est pas défini)
+fn metric_sql(metric: CompareMetric) -> &'static str {
+    match metric {
+        CompareMetric::FareAmount => "f.fare_amount",
+        CompareMetric::TotalAmount => "f.total_amount",
+        CompareMetric::TipRate => "CASE WHEN f.fare_amount > 0 THEN f.tip_amount / f.fare_amount END",
+        CompareMetric::TripDuration => {
+            "CASE WHEN EXTRACT(EPOCH FROM f.trip_duration) > 0 THEN EXTRACT(EPOCH FROM f.trip_duration)::float8 END"
+        }
+        CompareMetric::TripDistance => "f.trip_distance",
+    }
+}
+
+fn metric_columns() -> String {
+    CompareMetric::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, m)| format!("({})::float8 AS m{i}", metric_sql(*m)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[derive(QueryableByName)]
+struct Summary {
+    #[diesel(sql_type = BigInt)]
+    trip_count: i64,
+    #[diesel(sql_type = Float8)]
+    total_revenue: f64,
+    #[diesel(sql_type = Nullable<Float8>)]
+    tip_rate: Option<f64>,
+}
+
+#[derive(QueryableByName)]
+struct MetricRow {
+    #[diesel(sql_type = BigInt)]
+    metric: i64,
+    #[diesel(sql_type = BigInt)]
+    count: i64,
+    #[diesel(sql_type = Nullable<Float8>)]
+    mean: Option<f64>,
+    #[diesel(sql_type = Nullable<Float8>)]
+    stddev: Option<f64>,
+    #[diesel(sql_type = Nullable<Array<Nullable<Float8>>>)]
+    quantiles: Option<Vec<Option<f64>>>,
+}
+
+#[derive(QueryableByName)]
+struct SampleRow {
+    #[diesel(sql_type = Nullable<Float8>)]
+    m0: Option<f64>,
+    #[diesel(sql_type = Nullable<Float8>)]
+    m1: Option<f64>,
+    #[diesel(sql_type = Nullable<Float8>)]
+    m2: Option<f64>,
+    #[diesel(sql_type = Nullable<Float8>)]
+    m3: Option<f64>,
+    #[diesel(sql_type = Nullable<Float8>)]
+    m4: Option<f64>,
+}
+
+// Agrégats et échantillon d'un segment
+struct Segment {
+    summary: Summary,
+    // Dans l'ordre de `CompareMetric::ALL`
+    stats: Vec<MetricStats>,
+    samples: Vec<Vec<f64>>,
+}
+
+fn empty_stats() -> MetricStats {
+    MetricStats { count: 0, mean: None, stddev: None, p25: None, p50: None, p75: None, p90: None }
+}
+
+fn load_segment(conn: &mut PgConnection, filters: &TripFilters) -> QueryResult<Segment> {
+    let mut builder = SqlBuilder::default();
+    let from_where = filters::from_where(filters, &mut builder, &[]);
+    builder.sql = format!(
+        "SELECT \
+            COUNT(*)::bigint AS trip_count, \
+            COALESCE(SUM(f.total_amount), 0)::float8 AS total_revenue, \
+            (SUM(f.tip_amount) / NULLIF(SUM(f.fare_amount), 0))::float8 AS tip_rate \
+        {from_where}"
+    );
+    let summary = builder.into_query().get_result::<Summary>(conn)?;
+
+    let values = (0..CompareMetric::ALL.len())
+        .map(|i| format!("({i}, t.m{i})"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut builder = SqlBuilder::default();
+    let from_where = filters::from_where(filters, &mut builder, &[]);
+    builder.sql = format!(
+        "WITH trips AS (SELECT {columns} {from_where}) \
+        SELECT \
+            m.metric::bigint AS metric, \
+            COUNT(m.value)::bigint AS count, \
+            AVG(m.value)::float8 AS mean, \
+            STDDEV_SAMP(m.value)::float8 AS stddev, \
+            PERCENTILE_CONT(ARRAY[0.25, 0.5, 0.75, 0.9]) WITHIN GROUP (ORDER BY m.value) AS quantiles \
+        FROM trips t \
+        CROSS JOIN LATERAL (VALUES {values}) m(metric, value) \
+        WHERE m.value IS NOT NULL \
+        GROUP BY m.metric",
+        columns = metric_columns(),
+    );
+    let mut stats: Vec<MetricStats> = CompareMetric::ALL.iter().map(|_| empty_stats()).collect();
+    for row in builder.into_query().load::<MetricRow>(conn)? {
+        let quantile = |i: usize| row.quantiles.as_ref().and_then(|q| q.get(i).copied().flatten());
+        stats[row.metric as usize] = MetricStats {
+            count: row.count,
+            mean: row.mean,
+            stddev: row.stddev,
+            p25: quantile(0),
+            p50: quantile(1),
+            p75: quantile(2),
+            p90: quantile(3),
+        };
+    }
+
+    // Un trajet sur `step`, pour ne pas trier tout le segment
+    let step = ((summary.trip_count + MAX_SAMPLE - 1) / MAX_SAMPLE).max(1);
+    let mut builder = SqlBuilder::default();
+    let from_where = filters::from_where(filters, &mut builder, &[]);
+    builder.sql = format!(
+        "SELECT {columns} {from_where} AND hashtext(f.trip_id::text) % {step} = 0 \
+        ORDER BY f.trip_id LIMIT {MAX_SAMPLE}",
+        columns = metric_columns(),
+    );
+    let mut samples = vec![Vec::new(); CompareMetric::ALL.len()];
+    for row in builder.into_query().load::<SampleRow>(conn)? {
+        for (sample, value) in samples.iter_mut().zip([row.m0, row.m1, row.m2, row.m3, row.m4]) {
+            sample.extend(value.filter(|v| v.is_finite()));
+        }
+    }
+
+    Ok(Segment { summary, stats, samples })
+}
+
+fn moments(stats: &MetricStats) -> Option<Moments> {
+    Some(Moments { count: stats.count as f64, mean: stats.mean?, variance: stats.stddev?.powi(2) })
+}
+
+fn relative(diff: f64, base: f64) -> Option<f64> {
+    (base != 0.0).then(|| diff / base)
+}
+
+/// Compare les segments A et B : résumé de chacun, puis pour chaque
+/// indicateur les statistiques descriptives, l'écart des moyennes (B - A) et
+/// les tests de Welch et de Mann-Whitney.
+pub fn compare(conn: &mut PgConnection, request: &CompareRequest) -> QueryResult<CompareResponse> {
+    let (a, b) = conn.transaction(|conn| {
+        diesel::sql_query(format!("SET LOCAL statement_timeout = '{}'", STATEMENT_TIMEOUT)).execute(conn)?;
+        Ok::<_, diesel::result::Error>((load_segment(conn, &request.a)?, load_segment(conn, &request.b)?))
+    })?;
+
+    let metrics = CompareMetric::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, &metric)| {
+            let (stats_a, stats_b) = (a.stats[i].clone(), b.stats[i].clone());
+            let abs_diff = stats_a.mean.zip(stats_b.mean).map(|(ma, mb)| mb - ma);
+            let rel_diff = abs_diff.zip(stats_a.mean).and_then(|(d, ma)| relative(d, ma));
+            let welch = moments(&stats_a)
+                .zip(moments(&stats_b))
+                .and_then(|(ma, mb)| stats::welch_t_test(ma, mb))
+                .map(|(t, df, p_value)| WelchTest { t, df, p_value });
+            let (sample_a, sample_b) = (&a.samples[i], &b.samples[i]);
+            let mann_whitney = stats::mann_whitney_u(sample_a, sample_b).map(|test| MannWhitneyTest {
+                u: test.u,
+                z: test.z,
+                p_value: test.p_value,
+                sample_a: sample_a.len() as i64,
+                sample_b: sample_b.len() as i64,
+                prob_b_greater: test.prob_b_greater,
+            });
+            MetricComparison { metric, a: stats_a, b: stats_b, abs_diff, rel_diff, welch, mann_whitney }
+        })
+        .collect();
+
+    let trip_count_diff = b.summary.trip_count - a.summary.trip_count;
+    let summary = |segment: &Segment, label: &Option<String>, default: &str| SegmentSummary {
+        label: label.clone().unwrap_or_else(|| default.to_string()),
+        trip_count: segment.summary.trip_count,
+        total_revenue: segment.summary.total_revenue,
+        tip_rate: segment.summary.tip_rate,
+    };
+    Ok(CompareResponse {
+        a: summary(&a, &request.label_a, "A"),
+        b: summary(&b, &request.label_b, "B"),
+        trip_count_diff,
+        trip_count_rel_diff: relative(trip_count_diff as f64, a.summary.trip_count as f64),
+        metrics,
+    })
+}
+
+// Handler pour la comparaison de deux segments
+#[utoipa::path(
+    post,
+    path = "/api/v1/compare",
+    tag = "analytics",
+    request_body = CompareRequest,
+    responses(
+        (status = 200, description = "Indicateurs des segments A et B, écarts et tests de significativité", body = CompareResponse),
+        (status = 400, description = "Paramètres invalides", body = String),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
+pub async fn compare_segments(
+    State(pool): State<DbPool>,
+    request: Result<Json<CompareRequest>, JsonRejection>,
+) -> Result<Json<CompareResponse>, AppError> {
+    let Json(request) = request.map_err(|e| AppError::bad_request(e.body_text()))?;
+    filters::validate(&request.a)?;
+    filters::validate(&request.b)?;
+    let mut conn = pool.get().map_err(|e| AppError(anyhow::Error::new(e)))?;
+    let response = tokio::task::spawn_blocking(move || compare(&mut conn, &request)).await??;
+    Ok(Json(response))
+}
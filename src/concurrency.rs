@@ -0,0 +1,372 @@
+// Trajets simultanés : approximation de l'offre à partir des horodatages de
+// prise en charge et de dépose, `fact_trips` ne portant pas d'identifiant de
+// chauffeur.
+//
+// Un trajet est actif pendant chaque minute qu'il touche, de la minute de
+// prise en charge à celle de la dépose incluses. Les variations du nombre de
+// trajets actifs (départs et fins par minute) sont agrégées en SQL puis
+// cumulées ici minute par minute, en sautant les minutes sans trajet actif.
+// Les trajets sans dépose postérieure à la prise en charge ou de plus de
+// `MAX_TRIP_HOURS` heures sont ignorés ; ceux partis avant la période filtrée
+// n'y sont pas comptés.
+use std::collections::BTreeMap;
+
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use chrono::{Duration, NaiveDateTime, Timelike};
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use diesel::sql_types::{Array, BigInt, Integer, Nullable, Text, Timestamp};
+
+use crate::cube::{Join, SqlBuilder};
+use crate::db::DbPool;
+use crate::filters;
+use crate::handlers::AppError;
+use crate::models::{
+    ConcurrencyGranularity, ConcurrencyParams, ConcurrencyPoint, ConcurrencySeries, TripFilters, ZoneConcurrency,
+    ZoneConcurrencyParams, ZoneRef,
+};
+
+const MAX_TRIP_HOURS: i32 = 6;
+// Étendue maximale (en jours) de la série selon la granularité
+fn max_span_days(granularity: ConcurrencyGranularity) -> i64 {
+    match granularity {
+        ConcurrencyGranularity::Minute => 7,
+        ConcurrencyGranularity::Hour => 366,
+        ConcurrencyGranularity::Day => 731,
+    }
+}
+const DEFAULT_ZONE_LIMIT: i64 = 50;
+const MAX_ZONE_LIMIT: i64 = 300;
+
+// Départs et fins de trajets d'une minute (et d'une zone de départ)
+#[derive(QueryableByName)]
+struct MinuteEvents {
+    #[diesel(sql_type = Nullable<Integer>)]
+    location_id: Option<i32>,
+    #[diesel(sql_type = Timestamp)]
+    minute: NaiveDateTime,
+    #[diesel(sql_type = BigInt)]
+    starts: i64,
+    #[diesel(sql_type = BigInt)]
+    ends: i64,
+}
+
+#[derive(QueryableByName)]
+struct ZoneRow {
+    #[diesel(sql_type = Integer)]
+    location_id: i32,
+    #[diesel(sql_type = Nullable<Text>)]
+    zone: Option<String>,
+    #[diesel(sql_type = Nullable<Text>)]
+    borough: Option<String>,
+}
+
+fn minute_events(conn: &mut PgConnection, filters: &TripFilters, by_zone: bool) -> QueryResult<Vec<MinuteEvents>> {
+    let joins: &[Join] = if by_zone { &[Join::PickupLocation] } else { &[] };
+    let (location, zone_condition) = if by_zone {
+        ("pul.location_id", " AND pul.location_id IS NOT NULL")
+    } else {
+        ("NULL::int4", "")
+    };
+    let mut builder = SqlBuilder::default();
+    let from_where = filters::from_where(filters, &mut builder, joins);
+    builder.sql = format!(
+        "WITH trips AS ( \
+            SELECT \
+                {location} AS location_id, \
+                date_trunc('minute', f.tpep_pickup_datetime) AS start_minute, \
+                date_trunc('minute', f.tpep_dropoff_datetime) + INTERVAL '1 minute' AS end_minute \
+            {from_where}{zone_condition} \
+                AND f.tpep_dropoff_datetime > f.tpep_pickup_datetime \
+                AND f.tpep_dropoff_datetime - f.tpep_pickup_datetime <= INTERVAL '{MAX_TRIP_HOURS} hours' \
+        ) \
+        SELECT location_id, minute, SUM(starts)::bigint AS starts, SUM(ends)::bigint AS ends \
+        FROM ( \
+            SELECT location_id, start_minute AS minute, 1 AS starts, 0 AS ends FROM trips \
+            UNION ALL \
+            SELECT location_id, end_minute, 0, 1 FROM trips \
+        ) events \
+        GROUP BY 1, 2 \
+        ORDER BY 1, 2"
+    );
+    builder.into_query().load::<MinuteEvents>(conn)
+}
+
+fn truncate(minute: NaiveDateTime, granularity: ConcurrencyGranularity) -> NaiveDateTime {
+    match granularity {
+        ConcurrencyGranularity::Minute => minute,
+        ConcurrencyGranularity::Hour => minute.date().and_hms_opt(minute.hour(), 0, 0).unwrap_or(minute),
+        ConcurrencyGranularity::Day => minute.date().and_hms_opt(0, 0, 0).unwrap_or(minute),
+    }
+}
+
+fn period_length(granularity: ConcurrencyGranularity) -> Duration {
+    match granularity {
+        ConcurrencyGranularity::Minute => Duration::minutes(1),
+        ConcurrencyGranularity::Hour => Duration::hours(1),
+        ConcurrencyGranularity::Day => Duration::days(1),
+    }
+}
+
+/// Série des trajets simultanés (moyenne et pic par période), avec la flotte
+/// estimée (pic global) et le taux d'utilisation qui s'en déduit.
+pub fn concurrency_series(
+    conn: &mut PgConnection,
+    filters: &TripFilters,
+    granularity: ConcurrencyGranularity,
+) -> QueryResult<ConcurrencySeries> {
+    let events = minute_events(conn, filters, false)?;
+    let mut points: Vec<ConcurrencyPoint> = Vec::new();
+    let mut level = 0_i64;
+    let (mut fleet_estimate, mut fleet_peak_at) = (0_i64, None);
+    let mut total_active = 0_i64;
+
+    // Les minutes sans trajet actif sont sautées : on passe directement à la
+    // minute de l'événement suivant
+    let mut pending = events.iter().peekable();
+    let first = events.first().map(|e| e.minute);
+    let mut last = first;
+    let mut minute = first;
+    while let Some(current) = minute {
+        last = Some(current);
+        let mut starts = 0;
+        while let Some(event) = pending.next_if(|e| e.minute == current) {
+            level += event.starts - event.ends;
+            starts += event.starts;
+        }
+        if level == 0 {
+            minute = pending.peek().map(|e| e.minute);
+            continue;
+        }
+
+        let period = truncate(current, granularity);
+        if points.last().map(|p| p.period) != Some(period) {
+            points.push(ConcurrencyPoint {
+                period,
+                trip_starts: 0,
+                avg_active: 0.0,
+                peak_active: 0,
+                peak_at: current,
+                active_trip_minutes: 0,
+                utilisation: None,
+            });
+        }
+        if let Some(point) = points.last_mut() {
+            point.trip_starts += starts;
+            point.active_trip_minutes += level;
+            if level > point.peak_active {
+                point.peak_active = level;
+                point.peak_at = current;
+            }
+        }
+        if level > fleet_estimate {
+            fleet_estimate = level;
+            fleet_peak_at = Some(current);
+        }
+        total_active += level;
+        minute = Some(current + Duration::minutes(1));
+    }
+
+    // Moyennes sur les minutes de la période comprises dans l'étendue de la
+    // série, minutes sans trajet incluses
+    let (first, last) = (first.unwrap_or_default(), last.unwrap_or_default());
+    for point in &mut points {
+        let covered = (last.min(point.period + period_length(granularity)) - first.max(point.period)).num_minutes();
+        point.avg_active = point.active_trip_minutes as f64 / covered.max(1) as f64;
+    }
+    let total_minutes = (last - first).num_minutes();
+
+    let utilisation = |avg_active: f64| (fleet_estimate > 0).then(|| avg_active / fleet_estimate as f64);
+    for point in &mut points {
+        point.utilisation = utilisation(point.avg_active);
+    }
+    let avg_active = if total_minutes > 0 { total_active as f64 / total_minutes as f64 } else { 0.0 };
+    Ok(ConcurrencySeries {
+        granularity,
+        fleet_estimate,
+        fleet_peak_at,
+        avg_active,
+        utilisation: utilisation(avg_active),
+        points,
+    })
+}
+
+// Cumul des trajets actifs d'une zone
+struct ZoneAccumulator {
+    trip_count: i64,
+    level: i64,
+    // Minute de la dernière variation
+    since: Option<NaiveDateTime>,
+    active_trip_minutes: i64,
+    peak_active: i64,
+    peak_at: Option<NaiveDateTime>,
+    hourly_peak: [i64; 24],
+}
+
+impl ZoneAccumulator {
+    fn new() -> Self {
+        ZoneAccumulator {
+            trip_count: 0,
+            level: 0,
+            since: None,
+            active_trip_minutes: 0,
+            peak_active: 0,
+            peak_at: None,
+            hourly_peak: [0; 24],
+        }
+    }
+
+    // Clôt le palier [since, minute[ au niveau courant
+    fn close(&mut self, minute: NaiveDateTime) {
+        let Some(since) = self.since else { return };
+        if self.level == 0 {
+            return;
+        }
+        self.active_trip_minutes += self.level * (minute - since).num_minutes();
+        let mut hour = since;
+        for _ in 0..24 {
+            if hour >= minute {
+                break;
+            }
+            let peak = &mut self.hourly_peak[hour.hour() as usize];
+            *peak = (*peak).max(self.level);
+            hour = truncate(hour, ConcurrencyGranularity::Hour) + Duration::hours(1);
+        }
+    }
+
+    fn apply(&mut self, event: &MinuteEvents) {
+        self.close(event.minute);
+        self.trip_count += event.starts;
+        self.level += event.starts - event.ends;
+        self.since = Some(event.minute);
+        if self.level > self.peak_active {
+            self.peak_active = self.level;
+            self.peak_at = Some(event.minute);
+        }
+    }
+}
+
+/// Pic et moyenne de trajets simultanés par zone de départ, avec le pic par
+/// heure de la journée ; zones par pic décroissant.
+pub fn zone_concurrency(conn: &mut PgConnection, filters: &TripFilters, limit: i64) -> QueryResult<Vec<ZoneConcurrency>> {
+    let events = minute_events(conn, filters, true)?;
+    let mut zones: BTreeMap<i32, ZoneAccumulator> = BTreeMap::new();
+    for event in &events {
+        if let Some(location_id) = event.location_id {
+            zones.entry(location_id).or_insert_with(ZoneAccumulator::new).apply(event);
+        }
+    }
+    // Moyennes sur l'étendue commune à toutes les zones
+    let span_minutes = match (events.iter().map(|e| e.minute).min(), events.iter().map(|e| e.minute).max()) {
+        (Some(first), Some(last)) => (last - first).num_minutes().max(1),
+        _ => 1,
+    };
+
+    let mut ranked: Vec<(i32, ZoneAccumulator)> = zones.into_iter().collect();
+    ranked.sort_by(|a, b| {
+        (b.1.peak_active, b.1.trip_count)
+            .cmp(&(a.1.peak_active, a.1.trip_count))
+            .then(a.0.cmp(&b.0))
+    });
+    ranked.truncate(limit as usize);
+
+    let ids: Vec<i32> = ranked.iter().map(|(id, _)| *id).collect();
+    let labels: BTreeMap<i32, ZoneRow> = diesel::sql_query(
+        "SELECT DISTINCT ON (location_id) location_id, zone, borough \
+        FROM dim_location \
+        WHERE location_id = ANY($1) \
+        ORDER BY location_id",
+    )
+    .bind::<Array<Integer>, _>(ids)
+    .load::<ZoneRow>(conn)?
+    .into_iter()
+    .map(|z| (z.location_id, z))
+    .collect();
+
+    Ok(ranked
+        .into_iter()
+        .filter_map(|(location_id, zone)| {
+            let label = labels.get(&location_id);
+            Some(ZoneConcurrency {
+                zone: ZoneRef {
+                    location_id,
+                    zone: label.and_then(|l| l.zone.clone()),
+                    borough: label.and_then(|l| l.borough.clone()),
+                },
+                trip_count: zone.trip_count,
+                peak_active: zone.peak_active,
+                peak_at: zone.peak_at?,
+                avg_active: zone.active_trip_minutes as f64 / span_minutes as f64,
+                hourly_peak: zone.hourly_peak.to_vec(),
+            })
+        })
+        .collect())
+}
+
+// Période bornée requise : les événements sont chargés minute par minute
+fn require_span(filters: &TripFilters, max_days: i64) -> Result<(), AppError> {
+    let span = filters.start_date.zip(filters.end_date).map(|(start, end)| (end - start).num_days() + 1);
+    if span.is_none_or(|days| days > max_days) {
+        return Err(AppError::bad_request(format!(
+            "start_date et end_date requis, sur {max_days} jours au plus"
+        )));
+    }
+    Ok(())
+}
+
+// Handler pour la série des trajets simultanés
+#[utoipa::path(
+    get,
+    path = "/api/v1/concurrency",
+    tag = "analytics",
+    params(TripFilters, ConcurrencyParams),
+    responses(
+        (status = 200, description = "Trajets simultanés moyens et maximaux par minute, heure ou jour, flotte estimée et utilisation", body = ConcurrencySeries),
+        (status = 400, description = "Paramètres invalides", body = String),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
+pub async fn get_concurrency(
+    State(pool): State<DbPool>,
+    Query(filters): Query<TripFilters>,
+    Query(params): Query<ConcurrencyParams>,
+) -> Result<Json<ConcurrencySeries>, AppError> {
+    filters::validate(&filters)?;
+    let granularity = params.granularity.unwrap_or_default();
+    require_span(&filters, max_span_days(granularity))?;
+    let mut conn = pool.get().map_err(|e| AppError(anyhow::Error::new(e)))?;
+    let series = tokio::task::spawn_blocking(move || concurrency_series(&mut conn, &filters, granularity)).await??;
+    Ok(Json(series))
+}
+
+// Handler pour les trajets simultanés par zone de départ
+#[utoipa::path(
+    get,
+    path = "/api/v1/concurrency/zones",
+    tag = "analytics",
+    params(TripFilters, ZoneConcurrencyParams),
+    responses(
+        (status = 200, description = "Pic, moyenne et pic horaire de trajets simultanés par zone de départ", body = Vec<ZoneConcurrency>),
+        (status = 400, description = "Paramètres invalides ou période absente / supérieure à 7 jours", body = String),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
+pub async fn get_zone_concurrency(
+    State(pool): State<DbPool>,
+    Query(filters): Query<TripFilters>,
+    Query(params): Query<ZoneConcurrencyParams>,
+) -> Result<Json<Vec<ZoneConcurrency>>, AppError> {
+    filters::validate(&filters)?;
+    // Une ligne par (zone, minute) : même borne que la série à la minute
+    require_span(&filters, max_span_days(ConcurrencyGranularity::Minute))?;
+    let limit = params.limit.unwrap_or(DEFAULT_ZONE_LIMIT);
+    if !(1..=MAX_ZONE_LIMIT).contains(&limit) {
+        return Err(AppError::bad_request(format!("limit entre 1 et {MAX_ZONE_LIMIT} requis")));
+    }
+    let mut conn = pool.get().map_err(|e| AppError(anyhow::Error::new(e)))?;
+    let zones = tokio::task::spawn_blocking(move || zone_concurrency(&mut conn, &filters, limit)).await??;
+    Ok(Json(zones))
+}
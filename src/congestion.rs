@@ -0,0 +1,191 @@
+// Surcharge de congestion (`fact_trips.congestion_surcharge`) : recettes par
+// jour, heure ou zone, part des trajets facturés, et comparaison avant / après
+// une date de changement de politique.
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use chrono::{Duration, NaiveDate};
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+
+use crate::cube::{Bind, Join, SqlBuilder, PICKUP_HOUR_KEY};
+use crate::db::DbPool;
+use crate::filters;
+use crate::handlers::AppError;
+use crate::models::{
+    CongestionGrouping, CongestionParams, CongestionPeriodStats, CongestionPolicyImpact, CongestionPolicyParams,
+    CongestionSurchargeStats, TripFilters,
+};
+
+/// Date de politique par défaut : `CONGESTION_POLICY_DATE` (AAAA-MM-JJ), sinon
+/// l'entrée en vigueur du péage urbain de Manhattan (5 janvier 2025).
+pub fn default_policy_date() -> NaiveDate {
+    std::env::var("CONGESTION_POLICY_DATE")
+        .ok()
+        .and_then(|d| d.parse().ok())
+        .unwrap_or_else(|| NaiveDate::from_ymd_opt(2025, 1, 5).unwrap())
+}
+
+/// Recettes de surcharge et part des trajets facturés par jour, heure ou zone.
+pub fn congestion_surcharge(
+    conn: &mut PgConnection,
+    filters: &TripFilters,
+    grouping: CongestionGrouping,
+) -> QueryResult<Vec<CongestionSurchargeStats>> {
+    let mut builder = SqlBuilder::default();
+    let (joins, key, label, order): (&[Join], &str, &str, &str) = match grouping {
+        CongestionGrouping::Day => (&[Join::Date], "d.full_date::text", "NULL::text", "key"),
+        CongestionGrouping::Hour => (&[], PICKUP_HOUR_KEY, "NULL::text", "key"),
+        // Zones les plus contributrices en premier
+        CongestionGrouping::Zone => (
+            &[Join::PickupLocation],
+            "COALESCE(pul.location_id::text, 'Inconnu')",
+            "MIN(pul.zone || ', ' || pul.borough)",
+            "surcharge_revenue DESC, key",
+        ),
+    };
+    let from_where = filters::from_where(filters, &mut builder, joins);
+    builder.sql = format!(
+        "SELECT \
+            {key} AS key, \
+            {label} AS label, \
+            COUNT(f.trip_id)::bigint AS trip_count, \
+            COUNT(*) FILTER (WHERE f.congestion_surcharge > 0)::bigint AS charged_trips, \
+            COALESCE(AVG((f.congestion_surcharge > 0)::int), 0.0)::float8 AS charged_share, \
+            COALESCE(SUM(f.congestion_surcharge), 0.0)::float8 AS surcharge_revenue, \
+            COALESCE(AVG(f.congestion_surcharge) FILTER (WHERE f.congestion_surcharge > 0), 0.0)::float8 AS avg_surcharge \
+        {from_where} \
+        GROUP BY 1 \
+        ORDER BY {order}"
+    );
+    builder.into_query().load::<CongestionSurchargeStats>(conn)
+}
+
+// Indicateurs sur [start, end) (bornes optionnelles)
+fn period_stats(
+    conn: &mut PgConnection,
+    filters: &TripFilters,
+    start: Option<NaiveDate>,
+    end: Option<NaiveDate>,
+) -> QueryResult<CongestionPeriodStats> {
+    let mut builder = SqlBuilder::default();
+    let mut from_where = filters::from_where(filters, &mut builder, &[Join::Date]);
+    if let Some(start) = start {
+        from_where.push_str(&format!(" AND d.full_date >= {}::date", builder.bind(Bind::Text(start.to_string()))));
+    }
+    if let Some(end) = end {
+        from_where.push_str(&format!(" AND d.full_date < {}::date", builder.bind(Bind::Text(end.to_string()))));
+    }
+    builder.sql = format!(
+        "SELECT \
+            MIN(d.full_date) AS start_date, \
+            MAX(d.full_date) AS end_date, \
+            COUNT(DISTINCT d.full_date)::bigint AS days, \
+            COUNT(f.trip_id)::bigint AS trip_count, \
+            COALESCE(COUNT(f.trip_id)::float8 / NULLIF(COUNT(DISTINCT d.full_date), 0), 0.0)::float8 AS trips_per_day, \
+            COALESCE(AVG((f.congestion_surcharge > 0)::int), 0.0)::float8 AS charged_share, \
+            COALESCE(AVG(f.congestion_surcharge) FILTER (WHERE f.congestion_surcharge > 0), 0.0)::float8 AS avg_surcharge, \
+            COALESCE(SUM(f.congestion_surcharge) / NULLIF(COUNT(DISTINCT d.full_date), 0), 0.0)::float8 AS surcharge_revenue_per_day, \
+            COALESCE(AVG(f.fare_amount), 0.0)::float8 AS avg_fare, \
+            COALESCE(AVG(f.total_amount), 0.0)::float8 AS avg_total_amount \
+        {from_where}"
+    );
+    builder.into_query().get_result::<CongestionPeriodStats>(conn)
+}
+
+// Variation relative, indéfinie si l'une des périodes est sans trajet (une
+// période vide n'est pas une baisse de 100 %) ou si la référence est nulle
+fn relative_change(
+    before: &CongestionPeriodStats,
+    after: &CongestionPeriodStats,
+    metric: fn(&CongestionPeriodStats) -> f64,
+) -> Option<f64> {
+    if before.trip_count == 0 || after.trip_count == 0 || metric(before) == 0.0 {
+        return None;
+    }
+    Some(metric(after) / metric(before) - 1.0)
+}
+
+/// Comparaison des périodes avant et après `policy_date`.
+pub fn congestion_policy_impact(
+    conn: &mut PgConnection,
+    filters: &TripFilters,
+    policy_date: NaiveDate,
+    window_days: Option<i64>,
+) -> QueryResult<CongestionPolicyImpact> {
+    let window = window_days.map(Duration::days);
+    let before = period_stats(conn, filters, window.map(|w| policy_date - w), Some(policy_date))?;
+    let after = period_stats(conn, filters, Some(policy_date), window.map(|w| policy_date + w))?;
+    Ok(CongestionPolicyImpact {
+        policy_date,
+        trips_per_day_change: relative_change(&before, &after, |p| p.trips_per_day),
+        charged_share_change: relative_change(&before, &after, |p| p.charged_share),
+        surcharge_revenue_per_day_change: relative_change(&before, &after, |p| p.surcharge_revenue_per_day),
+        avg_total_amount_change: relative_change(&before, &after, |p| p.avg_total_amount),
+        before,
+        after,
+    })
+}
+
+// Handler pour les recettes de surcharge de congestion
+#[utoipa::path(
+    get,
+    path = "/api/v1/congestion_surcharge",
+    tag = "analytics",
+    params(TripFilters, CongestionParams),
+    responses(
+        (status = 200, description = "Recettes et part des trajets facturés par jour, heure ou zone", body = Vec<CongestionSurchargeStats>),
+        (status = 400, description = "Paramètres invalides", body = String),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
+pub async fn get_congestion_surcharge(
+    State(pool): State<DbPool>,
+    Query(filters): Query<TripFilters>,
+    Query(params): Query<CongestionParams>,
+) -> Result<Json<Vec<CongestionSurchargeStats>>, AppError> {
+    filters::validate(&filters)?;
+    let grouping = params.group_by.unwrap_or_default();
+    let mut conn = pool.get().map_err(|e| AppError(anyhow::Error::new(e)))?;
+    let results = tokio::task::spawn_blocking(move || congestion_surcharge(&mut conn, &filters, grouping)).await??;
+    Ok(Json(results))
+}
+
+// Handler pour la comparaison avant / après la date de politique
+#[utoipa::path(
+    get,
+    path = "/api/v1/congestion_surcharge/policy_impact",
+    tag = "analytics",
+    params(TripFilters, CongestionPolicyParams),
+    responses(
+        (status = 200, description = "Indicateurs avant / après la date de politique ; variations nulles si une période est sans trajet", body = CongestionPolicyImpact),
+        (status = 400, description = "Paramètres invalides ou policy_date hors de la période filtrée", body = String),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
+pub async fn get_congestion_policy_impact(
+    State(pool): State<DbPool>,
+    Query(filters): Query<TripFilters>,
+    Query(params): Query<CongestionPolicyParams>,
+) -> Result<Json<CongestionPolicyImpact>, AppError> {
+    filters::validate(&filters)?;
+    if params.window_days.is_some_and(|w| !(1..=3650).contains(&w)) {
+        return Err(AppError::bad_request("window_days entre 1 et 3650 requis"));
+    }
+    let policy_date = params.policy_date.unwrap_or_else(default_policy_date);
+    // Avant : [.., policy_date), après : [policy_date, ..] ; aucune des deux ne doit être vide
+    if filters.start_date.is_some_and(|start| policy_date <= start)
+        || filters.end_date.is_some_and(|end| policy_date > end)
+    {
+        return Err(AppError::bad_request(format!(
+            "policy_date ({policy_date}) doit être comprise dans ]start_date, end_date]"
+        )));
+    }
+    let mut conn = pool.get().map_err(|e| AppError(anyhow::Error::new(e)))?;
+    let impact = tokio::task::spawn_blocking(move || {
+        congestion_policy_impact(&mut conn, &filters, policy_date, params.window_days)
+    })
+    .await??;
+    Ok(Json(impact))
+}
@@ -0,0 +1,819 @@
+// Compilation des requêtes cube (mesures x dimensions) en SQL paramétré sur
+// fact_trips et les tables dim_*. Seules les expressions de la liste blanche
+// ci-dessous sont injectées dans le SQL ; les valeurs fournies par l'appelant
+// passent toujours par des paramètres liés.
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+
+use chrono::NaiveDate;
+use diesel::pg::{Pg, PgConnection};
+use diesel::prelude::*;
+use diesel::query_builder::BoxedSqlQuery;
+use diesel::sql_types::{Array, Text};
+
+use crate::models::{Aggregate, CubeDimension, CubeMeasure, CubeRow, FilterField, FilterOp, MeasureColumn, QueryMeasure, QuerySpec, SortDirection};
+
+/// Nombre maximal de dimensions par requête.
+pub const MAX_DIMENSIONS: usize = 4;
+/// Nombre maximal de mesures par requête.
+pub const MAX_MEASURES: usize = 12;
+/// Nombre maximal de filtres par requête.
+pub const MAX_FILTERS: usize = 16;
+/// Nombre maximal de valeurs dans un filtre `in` / `not_in`.
+pub const MAX_FILTER_VALUES: usize = 500;
+/// Nombre maximal de lignes renvoyées.
+pub const MAX_ROWS: i64 = 10_000;
+/// Coût maximal accepté (voir [`CubeQuery::cost`]).
+pub const MAX_COST: i32 = 80;
+/// Durée maximale d'exécution côté PostgreSQL.
+pub const STATEMENT_TIMEOUT: &str = "15s";
+
+// Jointures nécessaires, dans l'ordre où elles apparaissent dans le FROM
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Join {
+    Date,
+    PickupLocation,
+    DropoffLocation,
+    Vendor,
+    PaymentType,
+    RateCode,
+}
+
+impl Join {
+    pub(crate) fn sql(self) -> &'static str {
+        match self {
+            Join::Date => "JOIN dim_date d ON f.pickup_date_key = d.date_key",
+            Join::PickupLocation => "LEFT JOIN dim_location pul ON f.pickup_location_key = pul.location_key",
+            Join::DropoffLocation => "LEFT JOIN dim_location dol ON f.dropoff_location_key = dol.location_key",
+            Join::Vendor => "LEFT JOIN dim_vendor v ON f.vendor_key = v.vendor_key",
+            Join::PaymentType => "LEFT JOIN dim_payment_type pt ON f.payment_type_key = pt.payment_type_key",
+            Join::RateCode => "LEFT JOIN dim_rate_code rc ON f.rate_code_key = rc.rate_code_key",
+        }
+    }
+}
+
+// Type SQL d'une dimension, utilisé pour valider et caster les valeurs de filtre
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ValueKind {
+    Text,
+    Int,
+    Bool,
+    Date,
+}
+
+impl ValueKind {
+    fn cast(self) -> &'static str {
+        match self {
+            ValueKind::Text => "text",
+            ValueKind::Int => "int",
+            ValueKind::Bool => "bool",
+            ValueKind::Date => "date",
+        }
+    }
+
+    // Vérifie côté Rust qu'une valeur est convertible, pour répondre 400
+    // plutôt que de laisser PostgreSQL échouer sur le cast
+    fn check(self, value: &str) -> Result<(), CubeError> {
+        let ok = match self {
+            ValueKind::Text => true,
+            ValueKind::Int => value.parse::<i32>().is_ok(),
+            ValueKind::Bool => value.parse::<bool>().is_ok(),
+            ValueKind::Date => value.parse::<NaiveDate>().is_ok(),
+        };
+        if ok {
+            Ok(())
+        } else {
+            Err(CubeError(format!("valeur '{}' invalide pour un champ de type {}", value, self.cast())))
+        }
+    }
+}
+
+/// Clé de regroupement par heure de prise en charge (`00` à `23`), `Inconnu`
+/// pour les trajets sans heure de prise en charge.
+pub const PICKUP_HOUR_KEY: &str =
+    "COALESCE(LPAD(EXTRACT(HOUR FROM f.tpep_pickup_datetime)::int::text, 2, '0'), 'Inconnu')";
+
+// Expression SQL, type et jointure de chaque dimension
+pub(crate) fn dimension_sql(dimension: CubeDimension) -> (&'static str, ValueKind, Option<Join>) {
+    use CubeDimension::*;
+    match dimension {
+        Date => ("d.full_date", ValueKind::Date, Some(Join::Date)),
+        Year => ("d.year", ValueKind::Int, Some(Join::Date)),
+        Quarter => ("d.quarter", ValueKind::Int, Some(Join::Date)),
+        Month => ("d.month", ValueKind::Int, Some(Join::Date)),
+        Day => ("d.day", ValueKind::Int, Some(Join::Date)),
+        DayOfWeek => ("d.day_of_week", ValueKind::Int, Some(Join::Date)),
+        DayName => ("d.day_name", ValueKind::Text, Some(Join::Date)),
+        MonthName => ("d.month_name", ValueKind::Text, Some(Join::Date)),
+        IsWeekend => ("d.is_weekend", ValueKind::Bool, Some(Join::Date)),
+        IsHoliday => ("d.is_holiday", ValueKind::Bool, Some(Join::Date)),
+        HolidayName => ("d.holiday_name", ValueKind::Text, Some(Join::Date)),
+        IsSchoolDay => ("d.is_school_day", ValueKind::Bool, Some(Join::Date)),
+        IsDstTransition => ("d.is_dst_transition", ValueKind::Bool, Some(Join::Date)),
+        DayType => ("d.day_type", ValueKind::Text, Some(Join::Date)),
+        HourOfDay => ("EXTRACT(HOUR FROM f.tpep_pickup_datetime)::int", ValueKind::Int, None),
+        PickupBorough => ("pul.borough", ValueKind::Text, Some(Join::PickupLocation)),
+        PickupZone => ("pul.zone", ValueKind::Text, Some(Join::PickupLocation)),
+        PickupServiceZone => ("pul.service_zone", ValueKind::Text, Some(Join::PickupLocation)),
+        DropoffBorough => ("dol.borough", ValueKind::Text, Some(Join::DropoffLocation)),
+        DropoffZone => ("dol.zone", ValueKind::Text, Some(Join::DropoffLocation)),
+        DropoffServiceZone => ("dol.service_zone", ValueKind::Text, Some(Join::DropoffLocation)),
+        Vendor => ("COALESCE(NULLIF(TRIM(v.vendor_name), ''), 'Vendor ' || v.vendor_key::text)", ValueKind::Text, Some(Join::Vendor)),
+        PaymentType => ("pt.payment_type_name", ValueKind::Text, Some(Join::PaymentType)),
+        RateCode => ("rc.rate_code_name", ValueKind::Text, Some(Join::RateCode)),
+    }
+}
+
+// Expression SQL d'une colonne numérique de fact_trips
+pub(crate) fn column_sql(column: MeasureColumn) -> &'static str {
+    use MeasureColumn::*;
+    match column {
+        PassengerCount => "f.passenger_count",
+        TripDistance => "f.trip_distance",
+        FareAmount => "f.fare_amount",
+        Extra => "f.extra",
+        MtaTax => "f.mta_tax",
+        TipAmount => "f.tip_amount",
+        TollsAmount => "f.tolls_amount",
+        ImprovementSurcharge => "f.improvement_surcharge",
+        CongestionSurcharge => "f.congestion_surcharge",
+        AirportFee => "f.airport_fee",
+        TotalAmount => "f.total_amount",
+        TripDuration => "EXTRACT(EPOCH FROM f.trip_duration)",
+    }
+}
+
+// Nom d'une valeur d'énumération tel qu'exposé dans l'API (snake_case)
+pub(crate) fn api_name(value: impl serde::Serialize) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// Requête refusée avant exécution (liste blanche, limites de coût).
+#[derive(Debug, Clone, PartialEq)]
+pub struct CubeError(pub String);
+
+impl fmt::Display for CubeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for CubeError {}
+
+// Paramètre lié, typé côté SQL par un cast explicite
+#[derive(Debug, Clone)]
+pub(crate) enum Bind {
+    Text(String),
+    TextArray(Vec<String>),
+}
+
+/// SQL paramétré : texte avec `$1, $2, ...` et valeurs associées.
+#[derive(Debug, Default)]
+pub(crate) struct SqlBuilder {
+    pub(crate) sql: String,
+    binds: Vec<Bind>,
+}
+
+impl SqlBuilder {
+    // Ajoute un paramètre et renvoie son marqueur (`$n`)
+    pub(crate) fn bind(&mut self, value: Bind) -> String {
+        self.binds.push(value);
+        format!("${}", self.binds.len())
+    }
+
+    pub(crate) fn into_query(self) -> BoxedSqlQuery<'static, Pg, diesel::query_builder::SqlQuery> {
+        let mut query = diesel::sql_query(self.sql).into_boxed::<Pg>();
+        for bind in self.binds {
+            query = match bind {
+                Bind::Text(value) => query.bind::<Text, _>(value),
+                Bind::TextArray(values) => query.bind::<Array<Text>, _>(values),
+            };
+        }
+        query
+    }
+}
+
+/// Mesure d'une requête cube : mesure prédéfinie (GraphQL) ou agrégat libre
+/// sur une colonne de la liste blanche (`/api/v1/query`).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Measure {
+    Preset(CubeMeasure),
+    Aggregate { agg: Aggregate, column: Option<MeasureColumn>, percentile: Option<f64> },
+}
+
+impl Measure {
+    fn cost(&self) -> i32 {
+        use CubeMeasure::*;
+        match self {
+            // Les percentiles imposent un tri par groupe
+            Measure::Preset(DurationP25 | DurationP50 | DurationP75 | DurationP90) => 4,
+            Measure::Aggregate { agg: Aggregate::Percentile, .. } => 4,
+            _ => 1,
+        }
+    }
+
+    fn validate(&self) -> Result<(), CubeError> {
+        if let Measure::Aggregate { agg, column, percentile } = self {
+            if *agg != Aggregate::Count && column.is_none() {
+                return Err(CubeError(format!("l'agrégat {} requiert une colonne", api_name(agg))));
+            }
+            match (agg, percentile) {
+                (Aggregate::Percentile, Some(p)) if (0.0..=1.0).contains(p) => {}
+                (Aggregate::Percentile, _) => {
+                    return Err(CubeError("percentile requiert une fraction entre 0 et 1".into()));
+                }
+                (_, Some(_)) => return Err(CubeError("percentile n'est valable qu'avec l'agrégat percentile".into())),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn to_sql(&self, builder: &mut SqlBuilder) -> String {
+        use CubeMeasure::*;
+        let duration = column_sql(MeasureColumn::TripDuration);
+        match self {
+            Measure::Preset(preset) => match preset {
+                TripCount => "COUNT(f.trip_id)".into(),
+                TotalRevenue => "SUM(f.total_amount)".into(),
+                AvgFare => "AVG(f.fare_amount)".into(),
+                AvgTotalAmount => "AVG(f.total_amount)".into(),
+                AvgTip => "AVG(f.tip_amount)".into(),
+                TipRatio => "SUM(f.tip_amount) / NULLIF(SUM(f.fare_amount), 0)".into(),
+                AvgDistance => "AVG(f.trip_distance)".into(),
+                AvgDurationSeconds => format!("AVG({})", duration),
+                DurationP25 => format!("PERCENTILE_CONT(0.25) WITHIN GROUP (ORDER BY {})", duration),
+                DurationP50 => format!("PERCENTILE_CONT(0.50) WITHIN GROUP (ORDER BY {})", duration),
+                DurationP75 => format!("PERCENTILE_CONT(0.75) WITHIN GROUP (ORDER BY {})", duration),
+                DurationP90 => format!("PERCENTILE_CONT(0.90) WITHIN GROUP (ORDER BY {})", duration),
+            },
+            Measure::Aggregate { agg, column, percentile } => {
+                let expr = column.map(column_sql).unwrap_or("f.trip_id");
+                match agg {
+                    Aggregate::Count => format!("COUNT({})", expr),
+                    Aggregate::Sum => format!("SUM({})", expr),
+                    Aggregate::Avg => format!("AVG({})", expr),
+                    Aggregate::Min => format!("MIN({})", expr),
+                    Aggregate::Max => format!("MAX({})", expr),
+                    Aggregate::Percentile => {
+                        let marker = builder.bind(Bind::Text(percentile.unwrap_or(0.5).to_string()));
+                        format!("PERCENTILE_CONT({}::float8) WITHIN GROUP (ORDER BY {})", marker, expr)
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl From<CubeMeasure> for Measure {
+    fn from(measure: CubeMeasure) -> Self {
+        Measure::Preset(measure)
+    }
+}
+
+/// Condition du WHERE, sur une dimension (valeurs typées par la dimension)
+/// ou sur une colonne numérique.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    pub field: FilterField,
+    pub op: FilterOp,
+    pub values: Vec<String>,
+}
+
+impl Condition {
+    pub fn dimension(dimension: CubeDimension, op: FilterOp, values: Vec<String>) -> Self {
+        Condition { field: FilterField::Dimension(dimension), op, values }
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), CubeError> {
+        let expected = match self.op {
+            FilterOp::IsNull | FilterOp::NotNull => Some(0),
+            FilterOp::Between => Some(2),
+            FilterOp::In | FilterOp::NotIn => None,
+            _ => Some(1),
+        };
+        match expected {
+            Some(n) if self.values.len() != n => {
+                return Err(CubeError(format!("l'opérateur {} attend {} valeur(s)", api_name(self.op), n)));
+            }
+            None if self.values.is_empty() || self.values.len() > MAX_FILTER_VALUES => {
+                return Err(CubeError(format!("l'opérateur {} attend entre 1 et {} valeurs", api_name(self.op), MAX_FILTER_VALUES)));
+            }
+            _ => {}
+        }
+        for value in &self.values {
+            match self.field {
+                FilterField::Column(_) => {
+                    if value.parse::<f64>().map(|v| !v.is_finite()).unwrap_or(true) {
+                        return Err(CubeError(format!("valeur '{}' invalide pour une colonne numérique", value)));
+                    }
+                }
+                FilterField::Dimension(dimension) => dimension_sql(dimension).1.check(value)?,
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn to_sql(&self, builder: &mut SqlBuilder, joins: &mut BTreeSet<Join>) -> String {
+        let (expr, cast) = match self.field {
+            FilterField::Dimension(dimension) => {
+                let (expr, kind, join) = dimension_sql(dimension);
+                joins.extend(join);
+                (expr, kind.cast())
+            }
+            FilterField::Column(column) => (column_sql(column), "float8"),
+        };
+        let scalar = |builder: &mut SqlBuilder, i: usize| {
+            format!("{}::{}", builder.bind(Bind::Text(self.values[i].clone())), cast)
+        };
+        match self.op {
+            FilterOp::Eq => format!("{} = {}", expr, scalar(builder, 0)),
+            FilterOp::Ne => format!("{} <> {}", expr, scalar(builder, 0)),
+            FilterOp::Gt => format!("{} > {}", expr, scalar(builder, 0)),
+            FilterOp::Gte => format!("{} >= {}", expr, scalar(builder, 0)),
+            FilterOp::Lt => format!("{} < {}", expr, scalar(builder, 0)),
+            FilterOp::Lte => format!("{} <= {}", expr, scalar(builder, 0)),
+            FilterOp::Between => {
+                let low = scalar(builder, 0);
+                let high = scalar(builder, 1);
+                format!("{} BETWEEN {} AND {}", expr, low, high)
+            }
+            FilterOp::In | FilterOp::NotIn => {
+                let marker = builder.bind(Bind::TextArray(self.values.clone()));
+                let any = format!("{} = ANY({}::{}[])", expr, marker, cast);
+                if self.op == FilterOp::In { any } else { format!("NOT ({})", any) }
+            }
+            FilterOp::IsNull => format!("{} IS NULL", expr),
+            FilterOp::NotNull => format!("{} IS NOT NULL", expr),
+        }
+    }
+}
+
+// Clé de tri : indice d'une dimension ou d'une mesure de la requête
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortKey {
+    Dimension(usize),
+    Measure(usize),
+}
+
+/// Requête cube validée puis compilée en SQL.
+#[derive(Debug, Clone)]
+pub struct CubeQuery {
+    pub measures: Vec<Measure>,
+    pub dimensions: Vec<CubeDimension>,
+    pub conditions: Vec<Condition>,
+    pub sort: Vec<(SortKey, SortDirection)>,
+    pub limit: i64,
+}
+
+impl CubeQuery {
+    /// Construit une requête à partir de la spécification JSON de
+    /// `/api/v1/query`. Renvoie aussi l'alias de chaque mesure.
+    pub fn from_spec(spec: &QuerySpec) -> Result<(Self, Vec<String>), CubeError> {
+        let aliases = spec.measures.iter().map(measure_alias).collect::<Result<Vec<_>, _>>()?;
+        let distinct: HashSet<_> = aliases.iter().collect();
+        if distinct.len() != aliases.len() {
+            return Err(CubeError("alias de mesure en double".into()));
+        }
+
+        let measures = spec
+            .measures
+            .iter()
+            .map(|m| Measure::Aggregate { agg: m.agg, column: m.column, percentile: m.percentile })
+            .collect();
+        let conditions = spec
+            .filters
+            .iter()
+            .map(|f| Ok(Condition { field: f.field, op: f.op, values: filter_values(&f.value)? }))
+            .collect::<Result<Vec<_>, CubeError>>()?;
+        let sort = spec
+            .sort
+            .iter()
+            .map(|s| {
+                let key = if let Some(i) = aliases.iter().position(|a| *a == s.by) {
+                    SortKey::Measure(i)
+                } else if let Some(i) = spec.group_by.iter().position(|d| api_name(d) == s.by) {
+                    SortKey::Dimension(i)
+                } else {
+                    return Err(CubeError(format!("tri sur '{}' : ni une mesure ni une dimension de group_by", s.by)));
+                };
+                Ok((key, s.direction))
+            })
+            .collect::<Result<Vec<_>, CubeError>>()?;
+
+        let query = CubeQuery {
+            measures,
+            dimensions: spec.group_by.clone(),
+            conditions,
+            sort,
+            limit: spec.limit.unwrap_or(1000),
+        };
+        Ok((query, aliases))
+    }
+
+    /// Coût estimé : somme des coûts des mesures, multipliée par le nombre
+    /// de niveaux de regroupement (1 + nombre de dimensions).
+    pub fn cost(&self) -> i32 {
+        let measures: i32 = self.measures.iter().map(Measure::cost).sum();
+        measures * (1 + self.dimensions.len() as i32)
+    }
+
+    pub fn validate(&self) -> Result<(), CubeError> {
+        if self.measures.is_empty() {
+            return Err(CubeError("au moins une mesure est requise".into()));
+        }
+        if self.measures.len() > MAX_MEASURES {
+            return Err(CubeError(format!("{} mesures demandées, maximum {}", self.measures.len(), MAX_MEASURES)));
+        }
+        if self.dimensions.len() > MAX_DIMENSIONS {
+            return Err(CubeError(format!("{} dimensions demandées, maximum {}", self.dimensions.len(), MAX_DIMENSIONS)));
+        }
+        if self.conditions.len() > MAX_FILTERS {
+            return Err(CubeError(format!("{} filtres, maximum {}", self.conditions.len(), MAX_FILTERS)));
+        }
+        let distinct: HashSet<_> = self.dimensions.iter().collect();
+        if distinct.len() != self.dimensions.len() {
+            return Err(CubeError("dimension demandée plusieurs fois".into()));
+        }
+        if !(1..=MAX_ROWS).contains(&self.limit) {
+            return Err(CubeError(format!("limit doit être entre 1 et {}", MAX_ROWS)));
+        }
+        for measure in &self.measures {
+            measure.validate()?;
+        }
+        for condition in &self.conditions {
+            condition.validate()?;
+        }
+        let cost = self.cost();
+        if cost > MAX_COST {
+            return Err(CubeError(format!("coût de la requête {} supérieur à la limite {}", cost, MAX_COST)));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn to_sql(&self) -> SqlBuilder {
+        let mut builder = SqlBuilder::default();
+        let mut joins = BTreeSet::new();
+
+        let dims: Vec<&'static str> = self
+            .dimensions
+            .iter()
+            .map(|d| {
+                let (expr, _, join) = dimension_sql(*d);
+                joins.extend(join);
+                expr
+            })
+            .collect();
+        let measures: Vec<String> = self.measures.iter().map(|m| m.to_sql(&mut builder)).collect();
+        let conditions: Vec<String> = self
+            .conditions
+            .iter()
+            .map(|c| c.to_sql(&mut builder, &mut joins))
+            .collect();
+
+        let dims_select = if dims.is_empty() {
+            "ARRAY[]::text[]".to_string()
+        } else {
+            format!("ARRAY[{}]", dims.iter().map(|e| format!("({})::text", e)).collect::<Vec<_>>().join(", "))
+        };
+        let measures_select = measures
+            .iter()
+            .map(|m| format!("({})::float8", m))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        builder.sql = format!(
+            "SELECT {} AS dimensions, ARRAY[{}] AS measures FROM fact_trips f",
+            dims_select, measures_select
+        );
+        for join in joins {
+            builder.sql.push(' ');
+            builder.sql.push_str(join.sql());
+        }
+        if !conditions.is_empty() {
+            builder.sql.push_str(" WHERE ");
+            builder.sql.push_str(&conditions.join(" AND "));
+        }
+        if !dims.is_empty() {
+            builder.sql.push_str(&format!(" GROUP BY {}", dims.join(", ")));
+        }
+
+        let mut order_by: Vec<String> = self
+            .sort
+            .iter()
+            .map(|(key, direction)| {
+                let expr = match key {
+                    SortKey::Dimension(i) => dims[*i].to_string(),
+                    SortKey::Measure(i) => measures[*i].clone(),
+                };
+                let direction = match direction {
+                    SortDirection::Asc => "ASC NULLS LAST",
+                    SortDirection::Desc => "DESC NULLS LAST",
+                };
+                format!("{} {}", expr, direction)
+            })
+            .collect();
+        // Ordre stable par défaut sur les dimensions
+        order_by.extend(dims.iter().map(|e| e.to_string()));
+        if !order_by.is_empty() {
+            builder.sql.push_str(&format!(" ORDER BY {}", order_by.join(", ")));
+        }
+        builder.sql.push_str(&format!(" LIMIT {}", self.limit));
+        builder
+    }
+}
+
+// Alias d'une mesure : fourni par l'appelant ou dérivé (`avg_fare_amount`, `p90_trip_duration`)
+fn measure_alias(measure: &QueryMeasure) -> Result<String, CubeError> {
+    if let Some(alias) = &measure.alias {
+        let valid = !alias.is_empty()
+            && alias.len() <= 64
+            && alias.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+        return if valid {
+            Ok(alias.clone())
+        } else {
+            Err(CubeError(format!("alias '{}' invalide ([a-z0-9_], 64 caractères maximum)", alias)))
+        };
+    }
+    let agg = match measure.agg {
+        Aggregate::Percentile => format!("p{}", (measure.percentile.unwrap_or(0.5) * 100.0).round()),
+        other => api_name(other),
+    };
+    Ok(match measure.column {
+        Some(column) => format!("{}_{}", agg, api_name(column)),
+        None => agg,
+    })
+}
+
+// Valeurs d'un filtre JSON (scalaire ou liste) converties en texte
+fn filter_values(value: &serde_json::Value) -> Result<Vec<String>, CubeError> {
+    use serde_json::Value;
+    let scalar = |v: &Value| match v {
+        Value::String(s) => Ok(s.clone()),
+        Value::Number(n) => Ok(n.to_string()),
+        Value::Bool(b) => Ok(b.to_string()),
+        _ => Err(CubeError(format!("valeur de filtre non scalaire : {}", v))),
+    };
+    match value {
+        Value::Null => Ok(Vec::new()),
+        Value::Array(items) => items.iter().map(scalar).collect(),
+        other => Ok(vec![scalar(other)?]),
+    }
+}
+
+/// Exécute une requête cube (préalablement validée) avec un délai maximal.
+pub fn run(conn: &mut PgConnection, query: &CubeQuery) -> QueryResult<Vec<CubeRow>> {
+    let sql = query.to_sql();
+    conn.transaction(|conn| {
+        diesel::sql_query(format!("SET LOCAL statement_timeout = '{}'", STATEMENT_TIMEOUT)).execute(conn)?;
+        sql.into_query().load::<CubeRow>(conn)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const INJECTION: &str = "x'; DROP TABLE fact_trips; --";
+
+    fn spec(value: serde_json::Value) -> Result<QuerySpec, serde_json::Error> {
+        serde_json::from_value(value)
+    }
+
+    fn query(measures: Vec<Measure>, dimensions: Vec<CubeDimension>, conditions: Vec<Condition>) -> CubeQuery {
+        CubeQuery { measures, dimensions, conditions, sort: Vec::new(), limit: 100 }
+    }
+
+    fn count() -> Measure {
+        Measure::Aggregate { agg: Aggregate::Count, column: None, percentile: None }
+    }
+
+    fn rejected(query: &CubeQuery) -> String {
+        query.validate().expect_err("requête acceptée").0
+    }
+
+    #[test]
+    fn fields_outside_whitelist_do_not_deserialize() {
+        let count = json!({ "agg": "count" });
+        assert!(spec(json!({ "measures": [count], "group_by": ["f.trip_id"] })).is_err());
+        assert!(spec(json!({ "measures": [{ "agg": "sum", "column": "fare_amount); DROP TABLE fact_trips; --" }] }))
+            .is_err());
+        assert!(spec(json!({ "measures": [{ "agg": "stddev", "column": "fare_amount" }] })).is_err());
+        assert!(spec(json!({ "measures": [count], "filters": [{ "field": "pg_sleep(10)", "op": "eq", "value": 1 }] }))
+            .is_err());
+        assert!(spec(json!({ "measures": [count], "filters": [{ "field": "vendor", "op": "like", "value": "%" }] }))
+            .is_err());
+        assert!(spec(json!({ "measures": [count], "group_by": ["pickup_borough"], "filters": [{ "field": "fare_amount", "op": "gt", "value": 10 }] })).is_ok());
+    }
+
+    #[test]
+    fn aliases_and_sort_keys_are_checked() {
+        let with_alias = spec(json!({ "measures": [{ "agg": "count", "alias": INJECTION }] })).unwrap();
+        assert!(CubeQuery::from_spec(&with_alias).is_err());
+        let duplicate = spec(json!({ "measures": [{ "agg": "count" }, { "agg": "count" }] })).unwrap();
+        assert!(CubeQuery::from_spec(&duplicate).is_err());
+        let bad_sort = spec(json!({ "measures": [{ "agg": "count" }], "sort": [{ "by": "f.total_amount" }] })).unwrap();
+        assert!(CubeQuery::from_spec(&bad_sort).is_err());
+
+        let ok = spec(json!({
+            "measures": [{ "agg": "avg", "column": "fare_amount" }, { "agg": "percentile", "column": "trip_duration", "percentile": 0.9 }],
+            "group_by": ["hour_of_day"],
+            "sort": [{ "by": "avg_fare_amount", "direction": "desc" }, { "by": "hour_of_day" }]
+        }))
+        .unwrap();
+        let (query, aliases) = CubeQuery::from_spec(&ok).unwrap();
+        assert_eq!(aliases, ["avg_fare_amount", "p90_trip_duration"]);
+        assert_eq!(
+            query.sort,
+            [(SortKey::Measure(0), SortDirection::Desc), (SortKey::Dimension(0), SortDirection::Asc)]
+        );
+        assert_eq!(query.limit, 1000);
+    }
+
+    #[test]
+    fn limits_are_enforced() {
+        let dimensions = vec![
+            CubeDimension::Month,
+            CubeDimension::DayOfWeek,
+            CubeDimension::HourOfDay,
+            CubeDimension::PickupBorough,
+        ];
+        let eq = |v: &str| Condition::dimension(CubeDimension::PickupBorough, FilterOp::Eq, vec![v.to_string()]);
+
+        assert!(query(vec![count(); MAX_MEASURES], Vec::new(), Vec::new()).validate().is_ok());
+        assert!(rejected(&query(vec![count(); MAX_MEASURES + 1], Vec::new(), Vec::new())).contains("mesures"));
+        assert!(rejected(&query(Vec::new(), Vec::new(), Vec::new())).contains("mesure"));
+
+        assert!(query(vec![count()], dimensions.clone(), Vec::new()).validate().is_ok());
+        let mut too_many = dimensions.clone();
+        too_many.push(CubeDimension::Vendor);
+        assert!(rejected(&query(vec![count()], too_many, Vec::new())).contains("dimensions"));
+        let repeated = vec![CubeDimension::Month, CubeDimension::Month];
+        assert!(rejected(&query(vec![count()], repeated, Vec::new())).contains("plusieurs fois"));
+
+        assert!(query(vec![count()], Vec::new(), vec![eq("Queens"); MAX_FILTERS]).validate().is_ok());
+        assert!(rejected(&query(vec![count()], Vec::new(), vec![eq("Queens"); MAX_FILTERS + 1])).contains("filtres"));
+
+        let values = |n: usize| (0..n).map(|i| i.to_string()).collect::<Vec<_>>();
+        let within = Condition::dimension(CubeDimension::HourOfDay, FilterOp::In, values(MAX_FILTER_VALUES));
+        let beyond = Condition::dimension(CubeDimension::HourOfDay, FilterOp::In, values(MAX_FILTER_VALUES + 1));
+        let empty = Condition::dimension(CubeDimension::HourOfDay, FilterOp::In, Vec::new());
+        assert!(query(vec![count()], Vec::new(), vec![within]).validate().is_ok());
+        assert!(query(vec![count()], Vec::new(), vec![beyond]).validate().is_err());
+        assert!(query(vec![count()], Vec::new(), vec![empty]).validate().is_err());
+
+        for limit in [0, MAX_ROWS + 1] {
+            let q = CubeQuery { limit, ..query(vec![count()], Vec::new(), Vec::new()) };
+            assert!(rejected(&q).contains("limit"));
+        }
+        let q = CubeQuery { limit: MAX_ROWS, ..query(vec![count()], Vec::new(), Vec::new()) };
+        assert!(q.validate().is_ok());
+    }
+
+    #[test]
+    fn cost_limit_weights_percentiles_and_dimensions() {
+        let dimensions = vec![
+            CubeDimension::Month,
+            CubeDimension::DayOfWeek,
+            CubeDimension::HourOfDay,
+            CubeDimension::PickupBorough,
+        ];
+        let percentile = Measure::Aggregate {
+            agg: Aggregate::Percentile,
+            column: Some(MeasureColumn::FareAmount),
+            percentile: Some(0.5),
+        };
+        // 4 percentiles x (1 + 4 dimensions) = 80 : à la limite
+        let at_limit = query(vec![percentile.clone(); 4], dimensions.clone(), Vec::new());
+        assert_eq!(at_limit.cost(), MAX_COST);
+        assert!(at_limit.validate().is_ok());
+        let mut measures = vec![percentile; 4];
+        measures.push(Measure::Preset(CubeMeasure::TripCount));
+        let over = query(measures, dimensions, Vec::new());
+        assert_eq!(over.cost(), 85);
+        assert!(rejected(&over).contains("coût"));
+    }
+
+    #[test]
+    fn measures_and_conditions_are_validated() {
+        let invalid_measures = [
+            Measure::Aggregate { agg: Aggregate::Sum, column: None, percentile: None },
+            Measure::Aggregate { agg: Aggregate::Percentile, column: Some(MeasureColumn::TipAmount), percentile: None },
+            Measure::Aggregate {
+                agg: Aggregate::Percentile,
+                column: Some(MeasureColumn::TipAmount),
+                percentile: Some(1.5),
+            },
+            Measure::Aggregate { agg: Aggregate::Avg, column: Some(MeasureColumn::TipAmount), percentile: Some(0.5) },
+        ];
+        for measure in invalid_measures {
+            assert!(measure.validate().is_err(), "{measure:?}");
+        }
+
+        let invalid_conditions = [
+            Condition::dimension(CubeDimension::HourOfDay, FilterOp::Eq, vec!["8 OR 1=1".into()]),
+            Condition::dimension(CubeDimension::Date, FilterOp::Gte, vec!["2024-13-01".into()]),
+            Condition::dimension(CubeDimension::IsWeekend, FilterOp::Eq, vec!["oui".into()]),
+            Condition::dimension(CubeDimension::Date, FilterOp::Between, vec!["2024-10-01".into()]),
+            Condition::dimension(CubeDimension::PickupZone, FilterOp::IsNull, vec!["x".into()]),
+            Condition {
+                field: FilterField::Column(MeasureColumn::FareAmount),
+                op: FilterOp::Gt,
+                values: vec!["NaN".into()],
+            },
+            Condition {
+                field: FilterField::Column(MeasureColumn::FareAmount),
+                op: FilterOp::Gt,
+                values: vec![INJECTION.into()],
+            },
+        ];
+        for condition in invalid_conditions {
+            assert!(condition.validate().is_err(), "{condition:?}");
+        }
+        // Les dimensions texte acceptent toute valeur : elle n'est jamais injectée dans le SQL
+        assert!(Condition::dimension(CubeDimension::PickupZone, FilterOp::Eq, vec![INJECTION.into()])
+            .validate()
+            .is_ok());
+    }
+
+    #[test]
+    fn user_values_only_reach_sql_as_bind_parameters() {
+        let q = CubeQuery {
+            measures: vec![
+                Measure::Preset(CubeMeasure::AvgFare),
+                Measure::Aggregate {
+                    agg: Aggregate::Percentile,
+                    column: Some(MeasureColumn::TripDuration),
+                    percentile: Some(0.9),
+                },
+            ],
+            dimensions: vec![CubeDimension::PickupBorough],
+            conditions: vec![
+                Condition::dimension(CubeDimension::PickupZone, FilterOp::Eq, vec![INJECTION.into()]),
+                Condition::dimension(CubeDimension::Vendor, FilterOp::NotIn, vec![INJECTION.into(), "b".into()]),
+                Condition::dimension(
+                    CubeDimension::Date,
+                    FilterOp::Between,
+                    vec!["2024-10-01".into(), "2024-10-31".into()],
+                ),
+                Condition {
+                    field: FilterField::Column(MeasureColumn::FareAmount),
+                    op: FilterOp::Gt,
+                    values: vec!["12.5".into()],
+                },
+                Condition::dimension(CubeDimension::HolidayName, FilterOp::NotNull, Vec::new()),
+            ],
+            sort: vec![(SortKey::Measure(1), SortDirection::Desc)],
+            limit: 50,
+        };
+        q.validate().unwrap();
+        let builder = q.to_sql();
+        let sql = &builder.sql;
+
+        for value in [INJECTION, "DROP", "2024-10", "12.5", "0.9"] {
+            assert!(!sql.contains(value), "{value} dans {sql}");
+        }
+        assert_eq!(builder.binds.len(), 6);
+        for i in 1..=6 {
+            assert!(sql.contains(&format!("${i}")), "${i} absent de {sql}");
+        }
+        assert!(matches!(&builder.binds[0], Bind::Text(p) if p == "0.9"));
+        assert!(matches!(&builder.binds[1], Bind::Text(v) if v == INJECTION));
+        assert!(matches!(&builder.binds[2], Bind::TextArray(v) if v.len() == 2));
+
+        assert!(sql.contains("pul.zone = $2::text"));
+        assert!(sql.contains(
+            "NOT (COALESCE(NULLIF(TRIM(v.vendor_name), ''), 'Vendor ' || v.vendor_key::text) = ANY($3::text[]))"
+        ));
+        assert!(sql.contains("d.full_date BETWEEN $4::date AND $5::date"));
+        assert!(sql.contains("f.fare_amount > $6::float8"));
+        assert!(sql.contains("d.holiday_name IS NOT NULL"));
+        // Une seule jointure par table, dans l'ordre de `Join`
+        let joins: Vec<_> =
+            [Join::Date, Join::PickupLocation, Join::Vendor].iter().map(|j| sql.find(j.sql()).unwrap()).collect();
+        assert!(joins.is_sorted());
+        assert_eq!(sql.matches("JOIN dim_date").count(), 1);
+        assert!(!sql.contains("dim_payment_type"));
+        assert!(sql.contains("GROUP BY pul.borough ORDER BY PERCENTILE_CONT($1::float8)"));
+        assert!(sql.ends_with("DESC NULLS LAST, pul.borough LIMIT 50"));
+    }
+
+    #[test]
+    fn preset_measures_compile_without_binds() {
+        let mut builder = SqlBuilder::default();
+        assert_eq!(Measure::Preset(CubeMeasure::TripCount).to_sql(&mut builder), "COUNT(f.trip_id)");
+        assert_eq!(
+            Measure::Preset(CubeMeasure::DurationP90).to_sql(&mut builder),
+            "PERCENTILE_CONT(0.90) WITHIN GROUP (ORDER BY EXTRACT(EPOCH FROM f.trip_duration))"
+        );
+        assert_eq!(count().to_sql(&mut builder), "COUNT(f.trip_id)");
+        assert!(builder.binds.is_empty());
+
+        let sql = query(vec![count()], Vec::new(), Vec::new()).to_sql().sql;
+        assert_eq!(sql, "SELECT ARRAY[]::text[] AS dimensions, ARRAY[(COUNT(f.trip_id))::float8] AS measures FROM fact_trips f LIMIT 100");
+    }
+}
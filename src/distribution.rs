@@ -0,0 +1,334 @@
+// Distribution des champs numériques de `fact_trips` : histogramme (classes
+// fixes, logarithmiques ou par quantiles), percentiles, moments et valeurs
+// aberrantes, sur les trajets retenus par les filtres communs.
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use diesel::sql_types::{Array, BigInt, Float8, Integer, Nullable};
+
+use crate::cube::{self, Bind, SqlBuilder};
+use crate::db::DbPool;
+use crate::filters;
+use crate::handlers::AppError;
+use crate::models::{
+    BinningMode, Distribution, DistributionField, DistributionParams, HistogramBin, MeasureColumn, OutlierCounts,
+    PercentileValue, TripFilters,
+};
+
+const DEFAULT_BINS: i32 = 20;
+/// Nombre maximal de classes de l'histogramme.
+pub const MAX_BINS: i32 = 200;
+/// Nombre maximal de percentiles demandés.
+pub const MAX_PERCENTILES: usize = 20;
+const DEFAULT_PERCENTILES: [f64; 7] = [0.01, 0.05, 0.25, 0.5, 0.75, 0.95, 0.99];
+
+// Colonne et unité de chaque champ
+fn field_sql(field: DistributionField) -> (&'static str, &'static str) {
+    let (column, unit) = match field {
+        DistributionField::TripDistance => (MeasureColumn::TripDistance, "miles"),
+        DistributionField::FareAmount => (MeasureColumn::FareAmount, "usd"),
+        DistributionField::TipAmount => (MeasureColumn::TipAmount, "usd"),
+        DistributionField::TotalAmount => (MeasureColumn::TotalAmount, "usd"),
+        DistributionField::TripDuration => (MeasureColumn::TripDuration, "seconds"),
+        DistributionField::PassengerCount => (MeasureColumn::PassengerCount, "passengers"),
+    };
+    (cube::column_sql(column), unit)
+}
+
+// Sous-requête des valeurs filtrées (colonne `v`)
+fn values_sql(field: DistributionField, filters: &TripFilters, builder: &mut SqlBuilder) -> String {
+    let from_where = filters::from_where(filters, builder, &[]);
+    format!("SELECT ({})::float8 AS v {from_where}", field_sql(field).0)
+}
+
+// Littéral de tableau PostgreSQL (`{1.5,2.5}`) pour un paramètre `::float8[]`
+fn float_array(values: &[f64]) -> String {
+    let items: Vec<String> = values.iter().map(f64::to_string).collect();
+    format!("{{{}}}", items.join(","))
+}
+
+/// Liste de percentiles séparés par des virgules, chacun entre 0 et 1.
+pub fn parse_percentiles(list: &str) -> Result<Vec<f64>, String> {
+    let percentiles = list
+        .split(',')
+        .map(|p| match p.trim().parse::<f64>() {
+            Ok(p) if (0.0..=1.0).contains(&p) => Ok(p),
+            _ => Err(format!("percentile '{}' invalide (attendu entre 0 et 1)", p.trim())),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if percentiles.len() > MAX_PERCENTILES {
+        return Err(format!("au plus {MAX_PERCENTILES} percentiles"));
+    }
+    Ok(percentiles)
+}
+
+#[derive(QueryableByName)]
+struct MomentRow {
+    #[diesel(sql_type = BigInt)]
+    count: i64,
+    #[diesel(sql_type = BigInt)]
+    null_count: i64,
+    #[diesel(sql_type = Nullable<Float8>)]
+    min: Option<f64>,
+    #[diesel(sql_type = Nullable<Float8>)]
+    max: Option<f64>,
+    #[diesel(sql_type = Nullable<Float8>)]
+    min_positive: Option<f64>,
+    #[diesel(sql_type = Nullable<Float8>)]
+    mean: Option<f64>,
+    #[diesel(sql_type = Nullable<Float8>)]
+    stddev: Option<f64>,
+    // Moments centrés d'ordre 2 et 3 (population)
+    #[diesel(sql_type = Nullable<Float8>)]
+    m2: Option<f64>,
+    #[diesel(sql_type = Nullable<Float8>)]
+    m3: Option<f64>,
+    #[diesel(sql_type = Nullable<Array<Float8>>)]
+    quantiles: Option<Vec<f64>>,
+}
+
+#[derive(QueryableByName)]
+struct BinCount {
+    #[diesel(sql_type = Integer)]
+    bucket: i32,
+    #[diesel(sql_type = BigInt)]
+    trip_count: i64,
+}
+
+#[derive(QueryableByName)]
+struct OutlierRow {
+    #[diesel(sql_type = BigInt)]
+    low_count: i64,
+    #[diesel(sql_type = BigInt)]
+    high_count: i64,
+}
+
+// Bornes des classes selon le mode ; `quantile_edges` : quantiles k / bins
+fn bin_edges(
+    binning: BinningMode,
+    bins: usize,
+    lower: f64,
+    upper: f64,
+    quantile_edges: &[f64],
+) -> Vec<f64> {
+    match binning {
+        BinningMode::Fixed => {
+            let width = (upper - lower) / bins as f64;
+            let mut edges: Vec<f64> = (0..bins).map(|i| lower + width * i as f64).collect();
+            edges.push(upper);
+            edges
+        }
+        BinningMode::Log => {
+            let (log_lower, log_upper) = (lower.ln(), upper.ln());
+            let step = (log_upper - log_lower) / bins as f64;
+            let mut edges: Vec<f64> = (0..bins).map(|i| (log_lower + step * i as f64).exp()).collect();
+            edges[0] = lower;
+            edges.push(upper);
+            edges
+        }
+        // Bornes confondues (valeurs discrètes) fusionnées
+        BinningMode::Quantile => {
+            let mut edges = quantile_edges.to_vec();
+            edges.dedup();
+            if edges.len() == 1 {
+                edges.push(edges[0]);
+            }
+            edges
+        }
+    }
+}
+
+/// Distribution de `field` : moments, percentiles demandés, valeurs hors des
+/// clôtures de Tukey et histogramme de `bins` classes.
+///
+/// Sans bornes explicites, l'histogramme va du minimum observé (plus petite
+/// valeur positive en échelle log) à `min(max, Q3 + 3 IQR)` ; les bornes sont
+/// ignorées en mode quantile.
+pub fn distribution(
+    conn: &mut PgConnection,
+    filters: &TripFilters,
+    field: DistributionField,
+    params: &DistributionParams,
+    percentiles: &[f64],
+) -> QueryResult<Distribution> {
+    let binning = params.binning.unwrap_or_default();
+    let bins = params.bins.unwrap_or(DEFAULT_BINS) as usize;
+
+    // Percentiles demandés, puis Q1 / Q3, puis bornes des classes par quantiles
+    let mut quantile_levels = percentiles.to_vec();
+    quantile_levels.extend([0.25, 0.75]);
+    if binning == BinningMode::Quantile {
+        quantile_levels.extend((0..=bins).map(|k| k as f64 / bins as f64));
+    }
+
+    let mut builder = SqlBuilder::default();
+    let values = values_sql(field, filters, &mut builder);
+    let levels = builder.bind(Bind::Text(float_array(&quantile_levels)));
+    builder.sql = format!(
+        "WITH vals AS ({values}), \
+        mean AS (SELECT AVG(v) AS mu FROM vals) \
+        SELECT \
+            COUNT(v)::bigint AS count, \
+            (COUNT(*) - COUNT(v))::bigint AS null_count, \
+            MIN(v)::float8 AS min, \
+            MAX(v)::float8 AS max, \
+            (MIN(v) FILTER (WHERE v > 0))::float8 AS min_positive, \
+            MIN(mu)::float8 AS mean, \
+            STDDEV_SAMP(v)::float8 AS stddev, \
+            AVG(POWER(v - mu, 2))::float8 AS m2, \
+            AVG(POWER(v - mu, 3))::float8 AS m3, \
+            percentile_cont({levels}::float8[]) WITHIN GROUP (ORDER BY v)::float8[] AS quantiles \
+        FROM vals, mean"
+    );
+    let moments = builder.into_query().get_result::<MomentRow>(conn)?;
+
+    let quantiles = moments.quantiles.unwrap_or_default();
+    let mut distribution = Distribution {
+        field,
+        unit: field_sql(field).1.to_string(),
+        count: moments.count,
+        null_count: moments.null_count,
+        min: moments.min,
+        max: moments.max,
+        mean: moments.mean,
+        stddev: moments.stddev,
+        skewness: match (moments.m2, moments.m3) {
+            (Some(m2), Some(m3)) if m2 > 0.0 => Some(m3 / m2.powf(1.5)),
+            _ => None,
+        },
+        percentiles: percentiles
+            .iter()
+            .zip(&quantiles)
+            .map(|(&percentile, &value)| PercentileValue { percentile, value })
+            .collect(),
+        outliers: None,
+        binning,
+        bins: Vec::new(),
+        underflow: 0,
+        overflow: 0,
+    };
+    let (Some(min), Some(max), [q1, q3, quantile_edges @ ..]) =
+        (moments.min, moments.max, quantiles.get(percentiles.len()..).unwrap_or_default())
+    else {
+        return Ok(distribution);
+    };
+    let (q1, q3) = (*q1, *q3);
+    let iqr = q3 - q1;
+
+    // Valeurs aberrantes
+    let (lower_fence, upper_fence) = (q1 - 1.5 * iqr, q3 + 1.5 * iqr);
+    let mut builder = SqlBuilder::default();
+    let values = values_sql(field, filters, &mut builder);
+    let low = builder.bind(Bind::Text(lower_fence.to_string()));
+    let high = builder.bind(Bind::Text(upper_fence.to_string()));
+    builder.sql = format!(
+        "SELECT \
+            COUNT(*) FILTER (WHERE v < {low}::float8)::bigint AS low_count, \
+            COUNT(*) FILTER (WHERE v > {high}::float8)::bigint AS high_count \
+        FROM ({values}) vals"
+    );
+    let outliers = builder.into_query().get_result::<OutlierRow>(conn)?;
+    distribution.outliers = Some(OutlierCounts {
+        q1,
+        q3,
+        lower_fence,
+        upper_fence,
+        low_count: outliers.low_count,
+        high_count: outliers.high_count,
+    });
+
+    // Histogramme
+    let default_lower = match binning {
+        BinningMode::Log => moments.min_positive.unwrap_or(1.0),
+        _ => min,
+    };
+    let lower = params.min.unwrap_or(default_lower);
+    let mut upper = params.max.unwrap_or_else(|| max.min(q3 + 3.0 * iqr).max(lower));
+    if upper <= lower {
+        upper = match binning {
+            BinningMode::Log => lower * 10.0,
+            _ => lower + 1.0,
+        };
+    }
+    let edges = bin_edges(binning, bins, lower, upper, quantile_edges);
+    let bin_count = edges.len() - 1;
+
+    // width_bucket : 0 sous la première borne, bin_count + 1 à partir de la
+    // dernière ; la dernière borne est incluse dans la dernière classe
+    let mut builder = SqlBuilder::default();
+    let values = values_sql(field, filters, &mut builder);
+    let last = builder.bind(Bind::Text(edges[bin_count].to_string()));
+    let thresholds = builder.bind(Bind::Text(float_array(&edges)));
+    builder.sql = format!(
+        "SELECT \
+            (CASE WHEN v = {last}::float8 THEN {bin_count} ELSE width_bucket(v, {thresholds}::float8[]) END)::int AS bucket, \
+            COUNT(*)::bigint AS trip_count \
+        FROM ({values}) vals \
+        WHERE v IS NOT NULL \
+        GROUP BY 1"
+    );
+    let mut counts = vec![0; bin_count];
+    for row in builder.into_query().load::<BinCount>(conn)? {
+        match row.bucket as usize {
+            0 => distribution.underflow += row.trip_count,
+            b if b <= bin_count => counts[b - 1] = row.trip_count,
+            _ => distribution.overflow += row.trip_count,
+        }
+    }
+    distribution.bins = edges
+        .windows(2)
+        .zip(counts)
+        .map(|(edge, count)| HistogramBin { lower: edge[0], upper: edge[1], count })
+        .collect();
+    Ok(distribution)
+}
+
+// Handler pour la distribution d'un champ numérique
+#[utoipa::path(
+    get,
+    path = "/api/v1/distribution/{field}",
+    tag = "analytics",
+    params(
+        ("field" = DistributionField, Path, description = "Champ analysé"),
+        TripFilters,
+        DistributionParams
+    ),
+    responses(
+        (status = 200, description = "Histogramme, percentiles, moments et valeurs aberrantes", body = Distribution),
+        (status = 400, description = "Paramètres invalides", body = String),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
+pub async fn get_distribution(
+    State(pool): State<DbPool>,
+    Path(field): Path<DistributionField>,
+    Query(filters): Query<TripFilters>,
+    Query(params): Query<DistributionParams>,
+) -> Result<Json<Distribution>, AppError> {
+    filters::validate(&filters)?;
+    if params.bins.is_some_and(|b| !(1..=MAX_BINS).contains(&b)) {
+        return Err(AppError::bad_request(format!("bins entre 1 et {MAX_BINS} requis")));
+    }
+    if params.min.into_iter().chain(params.max).any(|v| !v.is_finite()) {
+        return Err(AppError::bad_request("min et max doivent être des nombres finis"));
+    }
+    if let (Some(min), Some(max)) = (params.min, params.max) {
+        if min >= max {
+            return Err(AppError::bad_request("min doit être inférieur à max"));
+        }
+    }
+    if params.binning == Some(BinningMode::Log) && params.min.is_some_and(|m| m <= 0.0) {
+        return Err(AppError::bad_request("min strictement positif requis en échelle log"));
+    }
+    let percentiles = match params.percentiles.as_deref() {
+        Some(list) => parse_percentiles(list).map_err(AppError::bad_request)?,
+        None => DEFAULT_PERCENTILES.to_vec(),
+    };
+    let mut conn = pool.get().map_err(|e| AppError(anyhow::Error::new(e)))?;
+    let result =
+        tokio::task::spawn_blocking(move || distribution(&mut conn, &filters, field, &params, &percentiles)).await??;
+    Ok(Json(result))
+}
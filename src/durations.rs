@@ -0,0 +1,89 @@
+// Statistiques de durée des trajets ventilées par borough de départ, paire
+// de boroughs, heure, jour de la semaine, mois ou type de jour (trajets de
+// durée positive, comme `/trip_duration_stats`).
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+
+use crate::cube::{Join, SqlBuilder, PICKUP_HOUR_KEY};
+use crate::db::DbPool;
+use crate::filters;
+use crate::handlers::AppError;
+use crate::models::{DurationBreakdownParams, DurationDimension, TripDurationBreakdown, TripFilters};
+
+const DURATION_SQL: &str = "EXTRACT(EPOCH FROM f.trip_duration)";
+
+/// Quantiles de durée par groupe de `dimension`.
+pub fn trip_duration_breakdown(
+    conn: &mut PgConnection,
+    filters: &TripFilters,
+    dimension: DurationDimension,
+) -> QueryResult<Vec<TripDurationBreakdown>> {
+    let (joins, key, label, order): (&[Join], &str, &str, &str) = match dimension {
+        DurationDimension::PickupBorough => (
+            &[Join::PickupLocation],
+            "COALESCE(pul.borough, 'Inconnu')",
+            "NULL::text",
+            "key",
+        ),
+        // Paires les plus fréquentes en premier
+        DurationDimension::OdBoroughPair => (
+            &[Join::PickupLocation, Join::DropoffLocation],
+            "COALESCE(pul.borough, 'Inconnu') || ' -> ' || COALESCE(dol.borough, 'Inconnu')",
+            "NULL::text",
+            "trip_count DESC, key",
+        ),
+        DurationDimension::Hour => (&[], PICKUP_HOUR_KEY, "NULL::text", "key"),
+        DurationDimension::DayOfWeek => (&[Join::Date], "d.day_of_week::text", "MIN(d.day_name)", "key"),
+        DurationDimension::Month => (&[Join::Date], "TO_CHAR(d.full_date, 'YYYY-MM')", "NULL::text", "key"),
+        DurationDimension::DayType => (&[Join::Date], "d.day_type", "NULL::text", "key"),
+    };
+    let mut builder = SqlBuilder::default();
+    let from_where = filters::from_where(filters, &mut builder, joins);
+    builder.sql = format!(
+        "SELECT \
+            {key} AS key, \
+            {label} AS label, \
+            COUNT(*)::bigint AS trip_count, \
+            AVG({DURATION_SQL})::float8 AS avg_duration_seconds, \
+            MIN({DURATION_SQL})::float8 AS min_duration_seconds, \
+            PERCENTILE_CONT(0.10) WITHIN GROUP (ORDER BY {DURATION_SQL})::float8 AS p10_duration_seconds, \
+            PERCENTILE_CONT(0.25) WITHIN GROUP (ORDER BY {DURATION_SQL})::float8 AS p25_duration_seconds, \
+            PERCENTILE_CONT(0.50) WITHIN GROUP (ORDER BY {DURATION_SQL})::float8 AS p50_duration_seconds, \
+            PERCENTILE_CONT(0.75) WITHIN GROUP (ORDER BY {DURATION_SQL})::float8 AS p75_duration_seconds, \
+            PERCENTILE_CONT(0.90) WITHIN GROUP (ORDER BY {DURATION_SQL})::float8 AS p90_duration_seconds, \
+            MAX({DURATION_SQL})::float8 AS max_duration_seconds \
+        {from_where} AND f.trip_duration IS NOT NULL AND {DURATION_SQL} > 0 \
+        GROUP BY 1 \
+        ORDER BY {order}"
+    );
+    builder.into_query().load::<TripDurationBreakdown>(conn)
+}
+
+// Handler pour les statistiques de durée par dimension
+#[utoipa::path(
+    get,
+    path = "/api/v1/trip_duration_stats/breakdown",
+    tag = "analytics",
+    params(TripFilters, DurationBreakdownParams),
+    responses(
+        (status = 200, description = "Quantiles de durée par borough, paire de boroughs, heure, jour ou mois", body = Vec<TripDurationBreakdown>),
+        (status = 400, description = "Paramètres invalides", body = String),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
+pub async fn get_trip_duration_breakdown(
+    State(pool): State<DbPool>,
+    Query(filters): Query<TripFilters>,
+    Query(params): Query<DurationBreakdownParams>,
+) -> Result<Json<Vec<TripDurationBreakdown>>, AppError> {
+    filters::validate(&filters)?;
+    let dimension = params.by.unwrap_or_default();
+    let mut conn = pool.get().map_err(|e| AppError(anyhow::Error::new(e)))?;
+    let results =
+        tokio::task::spawn_blocking(move || trip_duration_breakdown(&mut conn, &filters, dimension)).await??;
+    Ok(Json(results))
+}
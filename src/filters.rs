@@ -0,0 +1,65 @@
+// Traduction des filtres communs (`TripFilters`) en conditions SQL
+// paramétrées, réutilisant les expressions de la liste blanche du cube.
+use std::collections::BTreeSet;
+
+use crate::cube::{api_name, Condition, CubeError, Join, SqlBuilder};
+use crate::models::{CubeDimension, FilterOp, TripFilters};
+
+// Conditions équivalentes aux filtres renseignés
+pub(crate) fn conditions(filters: &TripFilters) -> Vec<Condition> {
+    let mut conditions = Vec::new();
+    let mut push = |dimension, op, value: Option<String>| {
+        if let Some(value) = value {
+            conditions.push(Condition::dimension(dimension, op, vec![value]));
+        }
+    };
+    push(CubeDimension::Date, FilterOp::Gte, filters.start_date.map(|d| d.to_string()));
+    push(CubeDimension::Date, FilterOp::Lte, filters.end_date.map(|d| d.to_string()));
+    push(CubeDimension::HourOfDay, FilterOp::Gte, filters.hour_from.map(|h| h.to_string()));
+    push(CubeDimension::HourOfDay, FilterOp::Lte, filters.hour_to.map(|h| h.to_string()));
+    push(CubeDimension::PickupBorough, FilterOp::Eq, filters.pickup_borough.clone());
+    push(CubeDimension::DropoffBorough, FilterOp::Eq, filters.dropoff_borough.clone());
+    push(CubeDimension::Vendor, FilterOp::Eq, filters.vendor.clone());
+    push(CubeDimension::PaymentType, FilterOp::Eq, filters.payment_type.clone());
+    push(CubeDimension::RateCode, FilterOp::Eq, filters.rate_code.clone());
+    push(CubeDimension::DayType, FilterOp::Eq, filters.day_type.map(api_name));
+    conditions
+}
+
+pub(crate) fn validate(filters: &TripFilters) -> Result<(), CubeError> {
+    if let (Some(start), Some(end)) = (filters.start_date, filters.end_date) {
+        if start > end {
+            return Err(CubeError("start_date est postérieure à end_date".into()));
+        }
+    }
+    for hour in [filters.hour_from, filters.hour_to].into_iter().flatten() {
+        if !(0..=23).contains(&hour) {
+            return Err(CubeError(format!("heure {} hors de l'intervalle 0-23", hour)));
+        }
+    }
+    Ok(())
+}
+
+/// Clause `FROM fact_trips f ... WHERE ...` avec les jointures demandées
+/// (alias `d`, `pul`, `dol`, `v`, `pt`, `rc`) et celles requises par les
+/// filtres. Se termine toujours par un WHERE, pour que l'appelant puisse
+/// ajouter ses propres conditions avec `AND`.
+pub(crate) fn from_where(filters: &TripFilters, builder: &mut SqlBuilder, joins: &[Join]) -> String {
+    let mut all_joins: BTreeSet<Join> = joins.iter().copied().collect();
+    let where_parts: Vec<String> = conditions(filters)
+        .iter()
+        .map(|c| c.to_sql(builder, &mut all_joins))
+        .collect();
+
+    let mut sql = String::from("FROM fact_trips f");
+    for join in all_joins {
+        sql.push(' ');
+        sql.push_str(join.sql());
+    }
+    sql.push_str(" WHERE TRUE");
+    for part in where_parts {
+        sql.push_str(" AND ");
+        sql.push_str(&part);
+    }
+    sql
+}
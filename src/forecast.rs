@@ -0,0 +1,428 @@
+// Prévision du nombre de trajets par jour ou par heure, au total ou par
+// borough de départ.
+//
+// Deux modèles :
+// - `holt_winters` : lissage exponentiel triple (niveau, tendance amortie,
+//   saisonnalité additive), paramètres choisis par grille sur l'erreur à un
+//   pas ;
+// - `seasonal_naive` : valeur de la même période de la saison précédente.
+// La saison couvre une semaine (7 jours ou 168 heures, qui porte aussi le
+// cycle journalier). Les intervalles supposent des erreurs normales, de
+// variance estimée sur les erreurs à un pas de l'historique. Le backtest
+// ajuste le modèle sans les `horizon` dernières périodes et les compare aux
+// prévisions.
+use std::collections::BTreeMap;
+
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use chrono::{Duration, NaiveDateTime};
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Text, Timestamp};
+
+use crate::cube::{Join, SqlBuilder};
+use crate::db::DbPool;
+use crate::filters;
+use crate::handlers::AppError;
+use crate::models::{
+    ForecastBacktest, ForecastGranularity, ForecastMethod, ForecastParams, ForecastPoint, ForecastReport,
+    ForecastSeries, ForecastSplit, TripFilters,
+};
+
+const DEFAULT_CONFIDENCE: f64 = 0.95;
+/// Historique retenu en granularité horaire (semaines les plus récentes).
+pub const HOURLY_HISTORY_WEEKS: usize = 12;
+// Amortissement de la tendance (1 = tendance linéaire)
+const DAMPING: f64 = 0.98;
+const ALPHAS: [f64; 6] = [0.05, 0.1, 0.2, 0.3, 0.5, 0.7];
+const BETAS: [f64; 4] = [0.0, 0.01, 0.05, 0.1];
+const GAMMAS: [f64; 4] = [0.05, 0.1, 0.2, 0.4];
+
+// Longueur de saison, pas de temps, horizon par défaut et horizon maximal
+fn settings(granularity: ForecastGranularity) -> (usize, Duration, i64, i64) {
+    match granularity {
+        ForecastGranularity::Daily => (7, Duration::days(1), 28, 120),
+        ForecastGranularity::Hourly => (168, Duration::hours(1), 168, 672),
+    }
+}
+
+#[derive(QueryableByName)]
+struct PeriodCount {
+    #[diesel(sql_type = Text)]
+    key: String,
+    #[diesel(sql_type = Timestamp)]
+    period: NaiveDateTime,
+    #[diesel(sql_type = BigInt)]
+    trip_count: i64,
+}
+
+/// Séries de trajets par clé, alignées sur les mêmes périodes.
+pub struct TripCountSeries {
+    /// Début de la première période observée.
+    pub start: NaiveDateTime,
+    /// Trajets par période, de la première à la dernière observée (zéro sans trajet).
+    pub values: BTreeMap<String, Vec<f64>>,
+}
+
+/// Séries de trajets par période, au total (`total`) ou par borough de
+/// départ ; `None` si aucun trajet ne correspond aux filtres.
+pub fn trip_count_series(
+    conn: &mut PgConnection,
+    filters: &TripFilters,
+    granularity: ForecastGranularity,
+    split: ForecastSplit,
+) -> QueryResult<Option<TripCountSeries>> {
+    let (joins, key): (&[Join], &str) = match split {
+        ForecastSplit::Total => (&[Join::Date], "'total'"),
+        ForecastSplit::PickupBorough => (&[Join::Date, Join::PickupLocation], "COALESCE(pul.borough, 'Inconnu')"),
+    };
+    let (period, condition) = match granularity {
+        ForecastGranularity::Daily => ("d.full_date::timestamp", "TRUE"),
+        ForecastGranularity::Hourly => (
+            "date_trunc('hour', f.tpep_pickup_datetime)",
+            "f.tpep_pickup_datetime IS NOT NULL",
+        ),
+    };
+    let mut builder = SqlBuilder::default();
+    let from_where = filters::from_where(filters, &mut builder, joins);
+    builder.sql = format!(
+        "SELECT \
+            {key} AS key, \
+            {period} AS period, \
+            COUNT(*)::bigint AS trip_count \
+        {from_where} AND {condition} \
+        GROUP BY 1, 2 \
+        ORDER BY 1, 2"
+    );
+    let rows = builder.into_query().load::<PeriodCount>(conn)?;
+
+    let (Some(first), Some(last)) = (
+        rows.iter().map(|r| r.period).min(),
+        rows.iter().map(|r| r.period).max(),
+    ) else {
+        return Ok(None);
+    };
+    let (_, step, _, _) = settings(granularity);
+    let periods = ((last - first).num_seconds() / step.num_seconds()) as usize + 1;
+    let mut values: BTreeMap<String, Vec<f64>> = BTreeMap::new();
+    for row in rows {
+        let index = ((row.period - first).num_seconds() / step.num_seconds()) as usize;
+        values.entry(row.key).or_insert_with(|| vec![0.0; periods])[index] = row.trip_count as f64;
+    }
+    Ok(Some(TripCountSeries { start: first, values }))
+}
+
+// Prévisions ponctuelles et écart-type de l'erreur à chaque horizon
+struct Fit {
+    forecasts: Vec<f64>,
+    std_errors: Vec<f64>,
+}
+
+// Lissage exponentiel triple ; renvoie la somme des carrés des erreurs à un pas
+// et, si `horizon` > 0, les prévisions
+fn holt_winters_run(values: &[f64], season: usize, alpha: f64, beta: f64, gamma: f64, horizon: usize) -> (f64, Fit) {
+    let first: f64 = values[..season].iter().sum::<f64>() / season as f64;
+    let second: f64 = values[season..2 * season].iter().sum::<f64>() / season as f64;
+    let mut level = first;
+    let mut trend = (second - first) / season as f64;
+    let mut seasonal: Vec<f64> = values[..season].iter().map(|v| v - first).collect();
+
+    let mut sse = 0.0;
+    for (t, &value) in values.iter().enumerate().skip(season) {
+        let s = seasonal[t % season];
+        let error = value - (level + DAMPING * trend + s);
+        sse += error * error;
+        let previous = level;
+        level = alpha * (value - s) + (1.0 - alpha) * (previous + DAMPING * trend);
+        trend = beta * (level - previous) + (1.0 - beta) * DAMPING * trend;
+        seasonal[t % season] = gamma * (value - level) + (1.0 - gamma) * s;
+    }
+
+    let sigma = (sse / (values.len() - season) as f64).sqrt();
+    let mut forecasts = Vec::with_capacity(horizon);
+    let mut std_errors = Vec::with_capacity(horizon);
+    let (mut damped, mut variance) = (0.0, 1.0_f64);
+    for h in 1..=horizon {
+        damped += DAMPING.powi(h as i32);
+        forecasts.push(level + damped * trend + seasonal[(values.len() + h - 1) % season]);
+        std_errors.push(sigma * variance.sqrt());
+        // Contribution de l'horizon h à la variance de l'horizon h + 1
+        let c = alpha * (1.0 + beta * damped) + if h.is_multiple_of(season) { gamma } else { 0.0 };
+        variance += c * c;
+    }
+    (sse, Fit { forecasts, std_errors })
+}
+
+fn holt_winters(values: &[f64], season: usize, horizon: usize) -> Fit {
+    let mut best = (f64::INFINITY, (ALPHAS[0], BETAS[0], GAMMAS[0]));
+    for alpha in ALPHAS {
+        for beta in BETAS {
+            for gamma in GAMMAS {
+                let (sse, _) = holt_winters_run(values, season, alpha, beta, gamma, 0);
+                if sse < best.0 {
+                    best = (sse, (alpha, beta, gamma));
+                }
+            }
+        }
+    }
+    let (alpha, beta, gamma) = best.1;
+    holt_winters_run(values, season, alpha, beta, gamma, horizon).1
+}
+
+fn seasonal_naive(values: &[f64], season: usize, horizon: usize) -> Fit {
+    let n = values.len();
+    let sse: f64 = (season..n).map(|t| (values[t] - values[t - season]).powi(2)).sum();
+    let sigma = (sse / (n - season) as f64).sqrt();
+    Fit {
+        forecasts: (1..=horizon).map(|h| values[n - season + (h - 1) % season]).collect(),
+        // Saisons répétées k fois : k + 1 erreurs indépendantes
+        std_errors: (1..=horizon).map(|h| sigma * ((((h - 1) / season) + 1) as f64).sqrt()).collect(),
+    }
+}
+
+fn fit(values: &[f64], method: ForecastMethod, season: usize, horizon: usize) -> Fit {
+    match method {
+        ForecastMethod::HoltWinters => holt_winters(values, season, horizon),
+        ForecastMethod::SeasonalNaive => seasonal_naive(values, season, horizon),
+    }
+}
+
+// Quantile de la loi normale centrée réduite pour p dans ]0,5 ; 1[
+// (Abramowitz et Stegun 26.2.23, erreur < 4,5e-4)
+fn normal_quantile(p: f64) -> f64 {
+    let t = (-2.0 * (1.0 - p).ln()).sqrt();
+    t - (2.515517 + 0.802853 * t + 0.010328 * t * t) / (1.0 + 1.432788 * t + 0.189269 * t * t + 0.001308 * t * t * t)
+}
+
+// Bornes de l'intervalle, tronquées à zéro (nombre de trajets)
+fn interval(forecast: f64, std_error: f64, z: f64) -> (f64, f64, f64) {
+    (forecast.max(0.0), (forecast - z * std_error).max(0.0), (forecast + z * std_error).max(0.0))
+}
+
+fn backtest(values: &[f64], method: ForecastMethod, season: usize, horizon: usize, z: f64) -> Option<ForecastBacktest> {
+    let holdout = horizon.min(values.len().saturating_sub(2 * season));
+    if holdout == 0 {
+        return None;
+    }
+    let (train, test) = values.split_at(values.len() - holdout);
+    let result = fit(train, method, season, holdout);
+    let (mut abs_error, mut pct_error, mut pct_count, mut covered) = (0.0, 0.0, 0, 0);
+    for ((&actual, &forecast), &std_error) in test.iter().zip(&result.forecasts).zip(&result.std_errors) {
+        let (forecast, lower, upper) = interval(forecast, std_error, z);
+        abs_error += (actual - forecast).abs();
+        if actual != 0.0 {
+            pct_error += ((actual - forecast) / actual).abs();
+            pct_count += 1;
+        }
+        if (lower..=upper).contains(&actual) {
+            covered += 1;
+        }
+    }
+    Some(ForecastBacktest {
+        holdout_periods: holdout as i64,
+        mae: abs_error / holdout as f64,
+        mape: (pct_count > 0).then(|| 100.0 * pct_error / pct_count as f64),
+        coverage: covered as f64 / holdout as f64,
+    })
+}
+
+/// Prévision et backtest d'une série commençant à `start`.
+pub fn forecast_series(
+    key: String,
+    start: NaiveDateTime,
+    values: &[f64],
+    granularity: ForecastGranularity,
+    method: ForecastMethod,
+    horizon: usize,
+    confidence: f64,
+) -> ForecastSeries {
+    let (season, step, _, _) = settings(granularity);
+    let z = normal_quantile((1.0 + confidence) / 2.0);
+    let last_observed = (!values.is_empty()).then(|| start + step * (values.len() as i32 - 1));
+    let mut series = ForecastSeries {
+        key,
+        history_periods: values.len() as i64,
+        last_observed,
+        backtest: None,
+        points: Vec::new(),
+    };
+    // Initialisation de Holt-Winters sur deux saisons complètes
+    let Some(last) = last_observed.filter(|_| values.len() >= 2 * season) else {
+        return series;
+    };
+    series.backtest = backtest(values, method, season, horizon, z);
+    let result = fit(values, method, season, horizon);
+    series.points = result
+        .forecasts
+        .iter()
+        .zip(&result.std_errors)
+        .enumerate()
+        .map(|(h, (&forecast, &std_error))| {
+            let (forecast, lower, upper) = interval(forecast, std_error, z);
+            ForecastPoint { period: last + step * (h as i32 + 1), forecast, lower, upper }
+        })
+        .collect();
+    series
+}
+
+// Handler pour la prévision de la demande
+#[utoipa::path(
+    get,
+    path = "/api/v1/forecast",
+    tag = "analytics",
+    params(TripFilters, ForecastParams),
+    responses(
+        (status = 200, description = "Prévisions de trajets avec intervalles et métriques de backtest (MAE, MAPE)", body = ForecastReport),
+        (status = 400, description = "Paramètres invalides", body = String),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
+pub async fn get_forecast(
+    State(pool): State<DbPool>,
+    Query(filters): Query<TripFilters>,
+    Query(params): Query<ForecastParams>,
+) -> Result<Json<ForecastReport>, AppError> {
+    filters::validate(&filters)?;
+    let granularity = params.granularity.unwrap_or_default();
+    let method = params.method.unwrap_or_default();
+    let split = params.by.unwrap_or_default();
+    let (season, step, default_horizon, max_horizon) = settings(granularity);
+    let horizon = params.horizon.unwrap_or(default_horizon);
+    if !(1..=max_horizon).contains(&horizon) {
+        return Err(AppError::bad_request(format!("horizon entre 1 et {max_horizon} requis")));
+    }
+    let confidence = params.confidence.unwrap_or(DEFAULT_CONFIDENCE);
+    if !(0.5..=0.99).contains(&confidence) {
+        return Err(AppError::bad_request("confidence entre 0.5 et 0.99 requis"));
+    }
+    let mut conn = pool.get().map_err(|e| AppError(anyhow::Error::new(e)))?;
+    let series = tokio::task::spawn_blocking(move || -> QueryResult<Vec<ForecastSeries>> {
+        let Some(TripCountSeries { start, values }) = trip_count_series(&mut conn, &filters, granularity, split)?
+        else {
+            return Ok(Vec::new());
+        };
+        Ok(values
+            .into_iter()
+            .map(|(key, values)| {
+                // Granularité horaire : semaines les plus récentes seulement
+                let skip = match granularity {
+                    ForecastGranularity::Daily => 0,
+                    ForecastGranularity::Hourly => values.len().saturating_sub(HOURLY_HISTORY_WEEKS * season),
+                };
+                let start = start + step * skip as i32;
+                forecast_series(key, start, &values[skip..], granularity, method, horizon as usize, confidence)
+            })
+            .collect())
+    })
+    .await??;
+    Ok(Json(ForecastReport {
+        granularity,
+        method,
+        horizon,
+        confidence,
+        season_length: season as i64,
+        series,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WEEK: [f64; 7] = [120.0, 80.0, 95.0, 100.0, 140.0, 210.0, 180.0];
+
+    fn weeks(n: usize) -> Vec<f64> {
+        WEEK.iter().copied().cycle().take(7 * n).collect()
+    }
+
+    fn start() -> NaiveDateTime {
+        NaiveDateTime::parse_from_str("2024-01-01T00:00:00", "%Y-%m-%dT%H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn periodic_series_fits_exactly() {
+        let values = weeks(8);
+        for (alpha, beta, gamma) in [(0.05, 0.0, 0.05), (0.7, 0.1, 0.4)] {
+            let (sse, fit) = holt_winters_run(&values, 7, alpha, beta, gamma, 14);
+            assert!(sse < 1e-9, "sse {sse}");
+            for (h, forecast) in fit.forecasts.iter().enumerate() {
+                assert!((forecast - WEEK[h % 7]).abs() < 1e-9, "h {h}: {forecast}");
+            }
+            assert!(fit.std_errors.iter().all(|s| *s < 1e-9));
+        }
+    }
+
+    #[test]
+    fn holt_winters_matches_seasonal_naive_on_periodic_series() {
+        let values = weeks(6);
+        let hw = holt_winters(&values, 7, 10);
+        let naive = seasonal_naive(&values, 7, 10);
+        assert_eq!(naive.forecasts.len(), 10);
+        for (a, b) in hw.forecasts.iter().zip(&naive.forecasts) {
+            assert!((a - b).abs() < 1e-9, "{a} != {b}");
+        }
+    }
+
+    #[test]
+    fn seasonal_naive_errors_grow_with_seasons() {
+        let mut values = weeks(4);
+        values[22] += 7.0;
+        let fit = seasonal_naive(&values, 7, 15);
+        assert_eq!(fit.forecasts[..7], values[21..28]);
+        let sigma = fit.std_errors[0];
+        assert!(sigma > 0.0);
+        assert_eq!(fit.std_errors[6], sigma);
+        assert!((fit.std_errors[7] - sigma * 2f64.sqrt()).abs() < 1e-12);
+        assert!((fit.std_errors[14] - sigma * 3f64.sqrt()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn backtest_of_periodic_series() {
+        let values = weeks(8);
+        let z = normal_quantile(0.975);
+        for method in [ForecastMethod::HoltWinters, ForecastMethod::SeasonalNaive] {
+            let result = backtest(&values, method, 7, 14, z).unwrap();
+            assert_eq!(result.holdout_periods, 14);
+            assert!(result.mae < 1e-9, "{method:?} : {}", result.mae);
+            assert!(result.mape.unwrap() < 1e-9);
+        }
+        let naive = backtest(&values, ForecastMethod::SeasonalNaive, 7, 14, z).unwrap();
+        assert_eq!(naive.coverage, 1.0);
+    }
+
+    #[test]
+    fn backtest_holdout_keeps_two_seasons() {
+        // 20 jours : 6 au plus hors de l'apprentissage
+        let values = &weeks(3)[..20];
+        let result = backtest(values, ForecastMethod::SeasonalNaive, 7, 28, 1.96).unwrap();
+        assert_eq!(result.holdout_periods, 6);
+    }
+
+    #[test]
+    fn short_series() {
+        let values = weeks(2);
+        assert!(backtest(&values, ForecastMethod::HoltWinters, 7, 28, 1.96).is_none());
+        let series = forecast_series(
+            "total".into(),
+            start(),
+            &values[..13],
+            ForecastGranularity::Daily,
+            ForecastMethod::HoltWinters,
+            28,
+            0.95,
+        );
+        assert!(series.backtest.is_none() && series.points.is_empty());
+        assert_eq!(series.history_periods, 13);
+        let empty =
+            forecast_series("total".into(), start(), &[], ForecastGranularity::Daily, ForecastMethod::SeasonalNaive, 7, 0.95);
+        assert_eq!(empty.last_observed, None);
+    }
+
+    #[test]
+    fn normal_quantiles() {
+        assert!((normal_quantile(0.975) - 1.959964).abs() < 5e-4);
+        assert!((normal_quantile(0.95) - 1.644854).abs() < 5e-4);
+    }
+}
@@ -0,0 +1,106 @@
+// Endpoint GraphQL : requêtes cube (mesures x dimensions) en un seul appel
+use std::sync::OnceLock;
+
+use async_graphql::http::GraphiQLSource;
+use async_graphql::{Context, EmptyMutation, EmptySubscription, Object, Schema};
+use axum::{
+    extract::{OriginalUri, State},
+    response::Html,
+    Json,
+};
+use chrono::NaiveDate;
+
+use crate::cube::{self, Condition, CubeQuery, Measure};
+use crate::db::DbPool;
+use crate::models::{CubeDimension, CubeMeasure, CubeResult, DimensionFilter, FilterOp};
+
+/// Profondeur maximale d'une requête GraphQL.
+pub const MAX_DEPTH: usize = 6;
+/// Complexité maximale (nombre de champs sélectionnés, pondéré).
+pub const MAX_COMPLEXITY: usize = 200;
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// Agrège `measures` par `dimensions` sur fact_trips. Les colonnes de
+    /// chaque ligne sont alignées sur l'ordre des listes demandées.
+    #[allow(clippy::too_many_arguments)]
+    async fn cube(
+        &self,
+        ctx: &Context<'_>,
+        measures: Vec<CubeMeasure>,
+        #[graphql(default)] dimensions: Vec<CubeDimension>,
+        #[graphql(default)] filters: Vec<DimensionFilter>,
+        date_from: Option<NaiveDate>,
+        date_to: Option<NaiveDate>,
+        #[graphql(default = 1000)] limit: i64,
+    ) -> async_graphql::Result<CubeResult> {
+        let pool = ctx.data::<DbPool>()?.clone();
+        let mut conditions: Vec<Condition> = filters
+            .into_iter()
+            .map(|f| Condition::dimension(f.dimension, FilterOp::In, f.values))
+            .collect();
+        if let Some(from) = date_from {
+            conditions.push(Condition::dimension(CubeDimension::Date, FilterOp::Gte, vec![from.to_string()]));
+        }
+        if let Some(to) = date_to {
+            conditions.push(Condition::dimension(CubeDimension::Date, FilterOp::Lte, vec![to.to_string()]));
+        }
+        let query = CubeQuery {
+            measures: measures.iter().copied().map(Measure::from).collect(),
+            dimensions,
+            conditions,
+            sort: Vec::new(),
+            limit,
+        };
+        query.validate()?;
+
+        let cost = query.cost();
+        let query_clone = query.clone();
+        let rows = tokio::task::spawn_blocking(move || {
+            let mut conn = pool.get()?;
+            cube::run(&mut conn, &query_clone).map_err(anyhow::Error::new)
+        })
+        .await??;
+
+        Ok(CubeResult {
+            dimensions: query.dimensions,
+            measures,
+            date_from,
+            date_to,
+            cost,
+            rows,
+        })
+    }
+}
+
+pub type CubeSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+pub fn build_schema() -> CubeSchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .limit_depth(MAX_DEPTH)
+        .limit_complexity(MAX_COMPLEXITY)
+        .finish()
+}
+
+// Le schéma ne dépend pas de l'état : construit une fois, le pool est
+// transmis à chaque requête
+fn schema() -> &'static CubeSchema {
+    static SCHEMA: OnceLock<CubeSchema> = OnceLock::new();
+    SCHEMA.get_or_init(build_schema)
+}
+
+// Handler POST /graphql
+pub async fn graphql_handler(
+    State(pool): State<DbPool>,
+    Json(request): Json<async_graphql::Request>,
+) -> Json<async_graphql::Response> {
+    Json(schema().execute(request.data(pool)).await)
+}
+
+// Handler GET /graphql : éditeur GraphiQL, qui envoie ses requêtes au chemin
+// où il est servi (préfixe de montage compris)
+pub async fn graphiql(OriginalUri(uri): OriginalUri) -> Html<String> {
+    Html(GraphiQLSource::build().endpoint(uri.path()).finish())
+}
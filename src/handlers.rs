@@ -1,17 +1,14 @@
 use axum::{
-    extract::State,
+    extract::{rejection::JsonRejection, Query, State},
     http::StatusCode,
     response::{Html, IntoResponse},
-    routing::get,
     Json,
-    Router,
-    http::{header, HeaderMap, HeaderValue},
 };
-use diesel::prelude::*;
 use crate::db::DbPool;
-use crate::models::{AggregatedTripStats, PaymentTypeAnalysis, HourlyWeekdayActivity, PassengerAnalysis, FinancialBreakdown, VendorAnalysis, KpiTrendData, TrendValue, ZoneActivity, RateCodeAnalysis, TripDurationStats, FareEfficiencyStats, BoroughFlowStats };
-use chrono::{NaiveDate, Duration, Datelike};
-use std::collections::HashMap;
+use crate::models::{AggregatedTripStats, PaymentTypeAnalysis, HourlyWeekdayActivity, PassengerAnalysis, FinancialBreakdown, VendorAnalysis, KpiTrendData, TrendValue, ZoneActivity, RateCodeAnalysis, TripDurationStats, FareEfficiencyStats, BoroughFlowStats, QuerySpec, QueryResponse, TripFilters };
+use crate::filters;
+use crate::queries;
+use crate::cube::{self, CubeError, CubeQuery};
 
 // Handler to serve the main HTML page
 pub async fn root_handler() -> Html<String> {
@@ -22,331 +19,297 @@ pub async fn root_handler() -> Html<String> {
 }
 
 // Updated handler for the API endpoint
+#[utoipa::path(
+    get,
+    path = "/api/v1/trip_volume",
+    tag = "analytics",
+    params(TripFilters),
+    responses(
+        (status = 200, description = "Volume de trajets et montants moyens par jour", body = [AggregatedTripStats]),
+        (status = 400, description = "Paramètres invalides", body = String),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
 pub async fn get_trip_volume_data(
     State(pool): State<DbPool>,
+    Query(filters): Query<TripFilters>,
 ) -> Result<Json<Vec<AggregatedTripStats>>, AppError> {
+    filters::validate(&filters)?;
     let mut conn = pool.get().map_err(|e| {
         tracing::error!("Failed to get DB connection from pool: {}", e);
         AppError(anyhow::Error::new(e))
     })?;
 
-    let results = tokio::task::spawn_blocking(move || {
-        diesel::sql_query(
-            "SELECT \
-                d.full_date::date as date, \
-                COUNT(f.trip_id)::bigint AS trip_count, \
-                COALESCE(AVG(f.total_amount), 0.0)::float8 AS avg_total_amount, \
-                COALESCE(AVG(f.tip_amount), 0.0)::float8 AS avg_tip_amount, \
-                COALESCE(AVG(f.trip_distance), 0.0)::float8 AS avg_trip_distance, \
-                COALESCE(AVG(EXTRACT(EPOCH FROM f.trip_duration)), 0.0)::float8 AS avg_trip_duration_seconds \
-            FROM fact_trips f \
-            JOIN dim_date d ON f.pickup_date_key = d.date_key \
-            GROUP BY d.full_date \
-            ORDER BY d.full_date"
-        )
-        .load::<AggregatedTripStats>(&mut conn)
-    })
-    .await
-    .map_err(|e| {
-        tracing::error!("Spawn blocking task failed: {}", e);
-        AppError(anyhow::Error::new(e))
-    })??;
+    let results = tokio::task::spawn_blocking(move || queries::trip_volume(&mut conn, &filters))
+        .await
+        .map_err(|e| {
+            tracing::error!("Spawn blocking task failed: {}", e);
+            AppError(anyhow::Error::new(e))
+        })??;
 
     Ok(Json(results))
 }
 
 // Nouveau handler pour l'analyse par type de paiement
+#[utoipa::path(
+    get,
+    path = "/api/v1/payment_analysis",
+    tag = "analytics",
+    params(TripFilters),
+    responses(
+        (status = 200, description = "Nombre de trajets et pourboire moyen par type de paiement", body = [PaymentTypeAnalysis]),
+        (status = 400, description = "Paramètres invalides", body = String),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
 pub async fn get_payment_analysis_data(
     State(pool): State<DbPool>,
+    Query(filters): Query<TripFilters>,
 ) -> Result<Json<Vec<PaymentTypeAnalysis>>, AppError> {
+    filters::validate(&filters)?;
     let mut conn = pool.get().map_err(|e| {
         tracing::error!("Failed to get DB connection from pool: {}", e);
         AppError(anyhow::Error::new(e))
     })?;
 
-    let results = tokio::task::spawn_blocking(move || {
-        diesel::sql_query(
-            "SELECT \
-                COALESCE(pt.payment_type_name, 'Inconnu') as payment_type_name, \
-                COUNT(f.trip_id)::bigint AS trip_count, \
-                COALESCE(AVG(f.tip_amount), 0.0)::float8 AS avg_tip_amount \
-            FROM fact_trips f \
-            LEFT JOIN dim_payment_type pt ON f.payment_type_key = pt.payment_type_key \
-            GROUP BY pt.payment_type_name \
-            ORDER BY trip_count DESC" // Trier par nombre de trajets
-        )
-        .load::<PaymentTypeAnalysis>(&mut conn)
-    })
-    .await
-    .map_err(|e| {
-        tracing::error!("Spawn blocking task failed: {}", e);
-        AppError(anyhow::Error::new(e))
-    })??;
+    let results = tokio::task::spawn_blocking(move || queries::payment_analysis(&mut conn, &filters))
+        .await
+        .map_err(|e| {
+            tracing::error!("Spawn blocking task failed: {}", e);
+            AppError(anyhow::Error::new(e))
+        })??;
 
     Ok(Json(results))
 }
 
 // Nouveau handler pour l'activité horaire/jour
+#[utoipa::path(
+    get,
+    path = "/api/v1/hourly_activity",
+    tag = "analytics",
+    params(TripFilters),
+    responses(
+        (status = 200, description = "Nombre de trajets par jour de la semaine et heure", body = [HourlyWeekdayActivity]),
+        (status = 400, description = "Paramètres invalides", body = String),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
 pub async fn get_hourly_activity_data(
     State(pool): State<DbPool>,
+    Query(filters): Query<TripFilters>,
 ) -> Result<Json<Vec<HourlyWeekdayActivity>>, AppError> {
+    filters::validate(&filters)?;
     let mut conn = pool.get().map_err(|e| {
         tracing::error!("Failed to get DB connection from pool: {}", e);
         AppError(anyhow::Error::new(e))
     })?;
 
-    let results = tokio::task::spawn_blocking(move || {
-        diesel::sql_query(
-            "SELECT \
-                EXTRACT(ISODOW FROM tpep_pickup_datetime)::INTEGER AS day_of_week, \
-                EXTRACT(HOUR FROM tpep_pickup_datetime)::INTEGER AS hour_of_day, \
-                COUNT(trip_id)::bigint AS trip_count \
-            FROM fact_trips \
-            GROUP BY day_of_week, hour_of_day \
-            ORDER BY day_of_week, hour_of_day"
-        )
-        .load::<HourlyWeekdayActivity>(&mut conn)
-    })
-    .await
-    .map_err(|e| {
-        tracing::error!("Spawn blocking task failed: {}", e);
-        AppError(anyhow::Error::new(e))
-    })??;
+    let results = tokio::task::spawn_blocking(move || queries::hourly_activity(&mut conn, &filters))
+        .await
+        .map_err(|e| {
+            tracing::error!("Spawn blocking task failed: {}", e);
+            AppError(anyhow::Error::new(e))
+        })??;
 
     Ok(Json(results))
 }
 
 // Handler pour analyse par nombre de passagers
+#[utoipa::path(
+    get,
+    path = "/api/v1/passenger_analysis",
+    tag = "analytics",
+    params(TripFilters),
+    responses(
+        (status = 200, description = "Nombre de trajets par nombre de passagers", body = [PassengerAnalysis]),
+        (status = 400, description = "Paramètres invalides", body = String),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
 pub async fn get_passenger_analysis_data(
     State(pool): State<DbPool>,
+    Query(filters): Query<TripFilters>,
 ) -> Result<Json<Vec<PassengerAnalysis>>, AppError> {
+    filters::validate(&filters)?;
     let mut conn = pool.get().map_err(|e| AppError(anyhow::Error::new(e)))?;
-    let results = tokio::task::spawn_blocking(move || {
-        diesel::sql_query(
-            "SELECT \
-                passenger_count, \
-                COUNT(trip_id)::bigint AS trip_count \
-            FROM fact_trips \
-            GROUP BY passenger_count \
-            ORDER BY passenger_count ASC"
-        )
-        .load::<PassengerAnalysis>(&mut conn)
-    })
-    .await??;
+    let results = tokio::task::spawn_blocking(move || queries::passenger_analysis(&mut conn, &filters))
+        .await??;
     Ok(Json(results))
 }
 
 // Handler pour la décomposition financière (par mois par défaut)
+#[utoipa::path(
+    get,
+    path = "/api/v1/financial_breakdown",
+    tag = "analytics",
+    params(TripFilters),
+    responses(
+        (status = 200, description = "Montants moyens par composante et par mois", body = [FinancialBreakdown]),
+        (status = 400, description = "Paramètres invalides", body = String),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
 pub async fn get_financial_breakdown_data(
     State(pool): State<DbPool>,
+    Query(filters): Query<TripFilters>,
 ) -> Result<Json<Vec<FinancialBreakdown>>, AppError> {
+    filters::validate(&filters)?;
     let mut conn = pool.get().map_err(|e| AppError(anyhow::Error::new(e)))?;
-    let results = tokio::task::spawn_blocking(move || {
-        diesel::sql_query(
-            "SELECT \
-                DATE_TRUNC('month', d.full_date)::date as date, \
-                COALESCE(AVG(f.fare_amount), 0.0)::float8 AS avg_fare_amount, \
-                COALESCE(AVG(f.tip_amount), 0.0)::float8 AS avg_tip_amount, \
-                COALESCE(AVG(f.tolls_amount), 0.0)::float8 AS avg_tolls_amount, \
-                COALESCE(AVG(f.mta_tax), 0.0)::float8 AS avg_mta_tax, \
-                COALESCE(AVG(f.improvement_surcharge), 0.0)::float8 AS avg_improvement_surcharge, \
-                COALESCE(AVG(f.extra), 0.0)::float8 AS avg_extra, \
-                COALESCE(AVG(f.total_amount), 0.0)::float8 AS avg_total_amount \
-            FROM fact_trips f \
-            JOIN dim_date d ON f.pickup_date_key = d.date_key \
-            GROUP BY DATE_TRUNC('month', d.full_date) \
-            ORDER BY date ASC"
-        )
-        .load::<FinancialBreakdown>(&mut conn)
-    })
-    .await??;
+    let results = tokio::task::spawn_blocking(move || queries::financial_breakdown(&mut conn, &filters))
+        .await??;
     Ok(Json(results))
 }
 
 // Handler pour analyse par vendeur (modifié)
+#[utoipa::path(
+    get,
+    path = "/api/v1/vendor_analysis",
+    tag = "analytics",
+    params(TripFilters),
+    responses(
+        (status = 200, description = "Activité par vendeur", body = [VendorAnalysis]),
+        (status = 400, description = "Paramètres invalides", body = String),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
 pub async fn get_vendor_analysis_data(
     State(pool): State<DbPool>,
+    Query(filters): Query<TripFilters>,
 ) -> Result<Json<Vec<VendorAnalysis>>, AppError> {
+    filters::validate(&filters)?;
     let mut conn = pool.get().map_err(|e| AppError(anyhow::Error::new(e)))?;
-    let results = tokio::task::spawn_blocking(move || {
-        diesel::sql_query(
-            "SELECT \
-                COALESCE(NULLIF(TRIM(v.vendor_name), ''), 'Vendor ' || v.vendor_key::text, 'Inconnu') as vendor_name, \
-                COUNT(f.trip_id)::bigint AS trip_count, \
-                COALESCE(AVG(f.total_amount), 0.0)::float8 AS avg_total_amount, \
-                COALESCE(AVG(f.trip_distance), 0.0)::float8 AS avg_trip_distance \
-            FROM fact_trips f \
-            LEFT JOIN dim_vendor v ON f.vendor_key = v.vendor_key \
-            GROUP BY v.vendor_key, v.vendor_name \
-            ORDER BY trip_count DESC"
-        )
-        .load::<VendorAnalysis>(&mut conn)
-    })
-    .await??;
+    let results = tokio::task::spawn_blocking(move || queries::vendor_analysis(&mut conn, &filters))
+        .await??;
     Ok(Json(results))
 }
 
 // Handler pour l'analyse par code tarifaire
+#[utoipa::path(
+    get,
+    path = "/api/v1/rate_code_analysis",
+    tag = "analytics",
+    params(TripFilters),
+    responses(
+        (status = 200, description = "Activité par code tarifaire", body = [RateCodeAnalysis]),
+        (status = 400, description = "Paramètres invalides", body = String),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
 pub async fn get_rate_code_analysis_data(
     State(pool): State<DbPool>,
+    Query(filters): Query<TripFilters>,
 ) -> Result<Json<Vec<RateCodeAnalysis>>, AppError> {
+    filters::validate(&filters)?;
     let mut conn = pool.get().map_err(|e| {
         tracing::error!("Failed to get DB connection from pool: {}", e);
         AppError(anyhow::Error::new(e))
     })?;
 
-    let results = tokio::task::spawn_blocking(move || {
-        diesel::sql_query(
-            "SELECT \
-                COALESCE(rc.rate_code_name, 'Inconnu') as rate_code_name, \
-                COUNT(f.trip_id)::bigint AS trip_count, \
-                COALESCE(AVG(f.total_amount), 0.0)::float8 AS avg_total_amount, \
-                COALESCE(AVG(f.trip_distance), 0.0)::float8 AS avg_trip_distance, \
-                COALESCE(AVG(f.tip_amount), 0.0)::float8 AS avg_tip_amount \
-            FROM fact_trips f \
-            LEFT JOIN dim_rate_code rc ON f.rate_code_key = rc.rate_code_key \
-            GROUP BY rc.rate_code_name \
-            ORDER BY trip_count DESC"
-        )
-        .load::<RateCodeAnalysis>(&mut conn)
-    })
-    .await
-    .map_err(|e| {
-        tracing::error!("Spawn blocking task failed: {}", e);
-        AppError(anyhow::Error::new(e))
-    })??;
+    let results = tokio::task::spawn_blocking(move || queries::rate_code_analysis(&mut conn, &filters))
+        .await
+        .map_err(|e| {
+            tracing::error!("Spawn blocking task failed: {}", e);
+            AppError(anyhow::Error::new(e))
+        })??;
 
     Ok(Json(results))
 }
 
 // Handler pour les statistiques sur la durée des trajets
+#[utoipa::path(
+    get,
+    path = "/api/v1/trip_duration_stats",
+    tag = "analytics",
+    params(TripFilters),
+    responses(
+        (status = 200, description = "Statistiques globales de durée des trajets", body = TripDurationStats),
+        (status = 400, description = "Paramètres invalides", body = String),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
 pub async fn get_trip_duration_stats_data(
     State(pool): State<DbPool>,
+    Query(filters): Query<TripFilters>,
 ) -> Result<Json<TripDurationStats>, AppError> {
+    filters::validate(&filters)?;
     let mut conn = pool.get().map_err(|e| {
         tracing::error!("Failed to get DB connection from pool: {}", e);
         AppError(anyhow::Error::new(e))
     })?;
 
-    let results = tokio::task::spawn_blocking(move || {
-        diesel::sql_query(
-            "SELECT \
-                COALESCE(AVG(EXTRACT(EPOCH FROM f.trip_duration)), 0.0)::float8 AS avg_duration_seconds, \
-                COALESCE(MIN(EXTRACT(EPOCH FROM f.trip_duration)), 0.0)::float8 AS min_duration_seconds, \
-                COALESCE(MAX(EXTRACT(EPOCH FROM f.trip_duration)), 0.0)::float8 AS max_duration_seconds, \
-                COALESCE(PERCENTILE_CONT(0.25) WITHIN GROUP (ORDER BY EXTRACT(EPOCH FROM f.trip_duration)), 0.0)::float8 AS p25_duration_seconds, \
-                COALESCE(PERCENTILE_CONT(0.50) WITHIN GROUP (ORDER BY EXTRACT(EPOCH FROM f.trip_duration)), 0.0)::float8 AS p50_duration_seconds, \
-                COALESCE(PERCENTILE_CONT(0.75) WITHIN GROUP (ORDER BY EXTRACT(EPOCH FROM f.trip_duration)), 0.0)::float8 AS p75_duration_seconds \
-            FROM fact_trips f \
-            WHERE f.trip_duration IS NOT NULL AND EXTRACT(EPOCH FROM f.trip_duration) > 0"
-        )
-        .get_result::<TripDurationStats>(&mut conn)
-    })
-    .await
-    .map_err(|e| {
-        tracing::error!("Spawn blocking task failed: {}", e);
-        AppError(anyhow::Error::new(e))
-    })??;
+    let results = tokio::task::spawn_blocking(move || queries::trip_duration_stats(&mut conn, &filters))
+        .await
+        .map_err(|e| {
+            tracing::error!("Spawn blocking task failed: {}", e);
+            AppError(anyhow::Error::new(e))
+        })??;
 
     Ok(Json(results))
 }
 
 // Handler pour les statistiques d'efficacité tarifaire
+#[utoipa::path(
+    get,
+    path = "/api/v1/fare_efficiency",
+    tag = "analytics",
+    params(TripFilters),
+    responses(
+        (status = 200, description = "Tarif moyen par km et par minute", body = FareEfficiencyStats),
+        (status = 400, description = "Paramètres invalides", body = String),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
 pub async fn get_fare_efficiency_stats_data(
     State(pool): State<DbPool>,
+    Query(filters): Query<TripFilters>,
 ) -> Result<Json<FareEfficiencyStats>, AppError> {
+    filters::validate(&filters)?;
     let mut conn = pool.get().map_err(|e| {
         tracing::error!("Failed to get DB connection from pool: {}", e);
         AppError(anyhow::Error::new(e))
     })?;
 
-    let results = tokio::task::spawn_blocking(move || {
-        diesel::sql_query(
-            "SELECT \
-                COALESCE(AVG(CASE WHEN f.trip_distance > 0 THEN f.fare_amount / f.trip_distance ELSE NULL END), 0.0)::float8 AS avg_fare_per_km, \
-                COALESCE(AVG(CASE WHEN EXTRACT(EPOCH FROM f.trip_duration) > 0 THEN f.fare_amount / (EXTRACT(EPOCH FROM f.trip_duration) / 60.0) ELSE NULL END), 0.0)::float8 AS avg_fare_per_minute \
-            FROM fact_trips f"
-        )
-        .get_result::<FareEfficiencyStats>(&mut conn)
-    })
-    .await
-    .map_err(|e| {
-        tracing::error!("Spawn blocking task failed for fare efficiency: {}", e);
-        AppError(anyhow::Error::new(e))
-    })??;
+    let results = tokio::task::spawn_blocking(move || queries::fare_efficiency_stats(&mut conn, &filters))
+        .await
+        .map_err(|e| {
+            tracing::error!("Spawn blocking task failed for fare efficiency: {}", e);
+            AppError(anyhow::Error::new(e))
+        })??;
 
     Ok(Json(results))
 }
 
 // Handler pour calculer les tendances KPI (Amélioré)
+#[utoipa::path(
+    get,
+    path = "/api/v1/kpi_trends",
+    tag = "analytics",
+    params(TripFilters),
+    responses(
+        (status = 200, description = "Indicateurs clés de la période filtrée (T4 2024 par défaut)", body = KpiTrendData),
+        (status = 400, description = "Paramètres invalides", body = String),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
 pub async fn get_kpi_trend_data(
     State(pool): State<DbPool>,
+    Query(filters): Query<TripFilters>,
 ) -> Result<Json<KpiTrendData>, AppError> {
+    filters::validate(&filters)?;
+    let months = queries::kpi_period_months(&filters);
     let pool_clone = pool.clone();
-    
-    let current_stats = tokio::task::spawn_blocking(move || {
+    let current_filters = filters.clone();
+    let current_stats = tokio::task::spawn_blocking(move || -> Result<AggregatedTripStats, AppError> {
         let mut conn = pool_clone.get().map_err(|e| AppError(anyhow::Error::new(e)))?;
-        
-        diesel::sql_query(
-            "SELECT \
-                COALESCE(MAX(d.full_date), CURRENT_DATE)::date as date, \
-                COALESCE(COUNT(f.trip_id), 0)::bigint AS trip_count, \
-                COALESCE(AVG(f.total_amount), 0.0)::float8 AS avg_total_amount, \
-                0.0::float8 AS avg_tip_amount, \
-                0.0::float8 AS avg_trip_distance, \
-                0.0::float8 AS avg_trip_duration_seconds \
-            FROM fact_trips f \
-            RIGHT JOIN dim_date d ON f.pickup_date_key = d.date_key \
-            WHERE d.year = 2024 AND d.month >= 10"
-        )
-        .get_result::<AggregatedTripStats>(&mut conn)
-        .map_err(|e| AppError(anyhow::Error::new(e)))
+        queries::kpi_current_period(&mut conn, &current_filters).map_err(|e| AppError(anyhow::Error::new(e)))
     })
     .await??;
-    
+
     let pool_clone = pool.clone();
     let monthly_stats = tokio::task::spawn_blocking(move || -> Result<AggregatedTripStats, AppError> {
         let mut conn = pool_clone.get().map_err(|e| AppError(anyhow::Error::new(e)))?;
-        
-        let result = diesel::sql_query(
-            "WITH monthly_data AS (
-                SELECT \
-                    DATE_TRUNC('month', d.full_date)::date as date, \
-                    COUNT(f.trip_id) AS trip_count, \
-                    AVG(f.total_amount) AS avg_total_amount, \
-                    AVG(f.tip_amount) AS avg_tip_amount, \
-                    AVG(f.trip_distance) AS avg_trip_distance, \
-                    AVG(EXTRACT(EPOCH FROM f.trip_duration)) AS avg_trip_duration_seconds \
-                FROM fact_trips f \
-                RIGHT JOIN dim_date d ON f.pickup_date_key = d.date_key \
-                WHERE d.year = 2024 AND d.month >= 10 \
-                GROUP BY DATE_TRUNC('month', d.full_date) \
-                ORDER BY trip_count DESC \
-                LIMIT 1
-            )
-            SELECT \
-                COALESCE(date, DATE_TRUNC('month', CURRENT_DATE)::date) as date, \
-                COALESCE(trip_count, 0)::bigint AS trip_count, \
-                COALESCE(avg_total_amount, 0.0)::float8 AS avg_total_amount, \
-                COALESCE(avg_tip_amount, 0.0)::float8 AS avg_tip_amount, \
-                COALESCE(avg_trip_distance, 0.0)::float8 AS avg_trip_distance, \
-                COALESCE(avg_trip_duration_seconds, 0.0)::float8 AS avg_trip_duration_seconds \
-            FROM monthly_data \
-            UNION ALL \
-            SELECT \
-                DATE_TRUNC('month', CURRENT_DATE)::date as date, \
-                0::bigint AS trip_count, \
-                0.0::float8 AS avg_total_amount, \
-                0.0::float8 AS avg_tip_amount, \
-                0.0::float8 AS avg_trip_distance, \
-                0.0::float8 AS avg_trip_duration_seconds \
-            WHERE NOT EXISTS (SELECT 1 FROM monthly_data) \
-            LIMIT 1"
-        )
-        .get_result::<AggregatedTripStats>(&mut conn)
-        .optional()
-        .map_err(|e| AppError(anyhow::Error::new(e)))?;
-        
+        let result = queries::kpi_busiest_month(&mut conn, &filters).map_err(|e| AppError(anyhow::Error::new(e)))?;
+
         Ok(result.unwrap_or_else(|| AggregatedTripStats {
             date: chrono::Local::now().date_naive(),
             trip_count: 0,
@@ -359,7 +322,7 @@ pub async fn get_kpi_trend_data(
     .await??;
     
     let total_trips = current_stats.trip_count as f64;
-    let avg_trips_per_month = if total_trips > 0.0 { total_trips / 3.0 } else { 0.0 };
+    let avg_trips_per_month = if total_trips > 0.0 { total_trips / months as f64 } else { 0.0 };
     
     let max_trips_per_month = monthly_stats.trip_count as f64;
 
@@ -390,72 +353,158 @@ pub async fn get_kpi_trend_data(
 }
 
 // Handler pour l'analyse par zone de départ
+#[utoipa::path(
+    get,
+    path = "/api/v1/zone_activity",
+    tag = "analytics",
+    params(TripFilters),
+    responses(
+        (status = 200, description = "Activité par zone de départ", body = [ZoneActivity]),
+        (status = 400, description = "Paramètres invalides", body = String),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
 pub async fn get_zone_activity_data(
     State(pool): State<DbPool>,
+    Query(filters): Query<TripFilters>,
 ) -> Result<Json<Vec<ZoneActivity>>, AppError> {
+    filters::validate(&filters)?;
     let mut conn = pool.get().map_err(|e| AppError(anyhow::Error::new(e)))?;
 
-    let results = tokio::task::spawn_blocking(move || {
-        diesel::sql_query(
-            "SELECT \
-                loc.location_id, \
-                loc.zone, \
-                loc.borough, \
-                COUNT(f.trip_id)::bigint AS trip_count, \
-                COALESCE(AVG(f.total_amount), 0.0)::float8 AS avg_total_amount \
-            FROM fact_trips f \
-            LEFT JOIN dim_location loc ON f.pickup_location_key = loc.location_key \
-            GROUP BY loc.location_id, loc.zone, loc.borough \
-            ORDER BY trip_count DESC"
-        )
-        .load::<ZoneActivity>(&mut conn)
-    })
-    .await??;
+    let results = tokio::task::spawn_blocking(move || queries::zone_activity(&mut conn, &filters))
+        .await??;
 
     Ok(Json(results))
 }
 
 // Handler pour les flux de trajets entre arrondissements
+#[utoipa::path(
+    get,
+    path = "/api/v1/borough_flows",
+    tag = "analytics",
+    params(TripFilters),
+    responses(
+        (status = 200, description = "Flux de trajets entre arrondissements (100 premiers)", body = [BoroughFlowStats]),
+        (status = 400, description = "Paramètres invalides", body = String),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
 pub async fn get_borough_flows_data(
     State(pool): State<DbPool>,
+    Query(filters): Query<TripFilters>,
 ) -> Result<Json<Vec<BoroughFlowStats>>, AppError> {
+    filters::validate(&filters)?;
     let mut conn = pool.get().map_err(|e| {
         tracing::error!("Failed to get DB connection from pool: {}", e);
         AppError(anyhow::Error::new(e))
     })?;
 
-    let results = tokio::task::spawn_blocking(move || {
-        diesel::sql_query(
-            "SELECT \
-                COALESCE(pul.borough, 'Inconnu') AS pickup_borough, \
-                COALESCE(dol.borough, 'Inconnu') AS dropoff_borough, \
-                COUNT(f.trip_id)::bigint AS trip_count, \
-                COALESCE(AVG(f.fare_amount), 0.0)::float8 AS avg_fare_amount \
-            FROM fact_trips f \
-            LEFT JOIN dim_location pul ON f.pickup_location_key = pul.location_key \
-            LEFT JOIN dim_location dol ON f.dropoff_location_key = dol.location_key \
-            WHERE pul.borough IS NOT NULL AND dol.borough IS NOT NULL AND pul.borough != 'Unknown' AND dol.borough != 'Unknown' AND pul.borough != '' AND dol.borough != '' \
-            GROUP BY pul.borough, dol.borough \
-            ORDER BY trip_count DESC \
-            LIMIT 100"
-        )
-        .load::<BoroughFlowStats>(&mut conn)
-    })
-    .await
-    .map_err(|e| {
-        tracing::error!("Spawn blocking task failed for borough flows: {}", e);
-        AppError(anyhow::Error::new(e))
-    })??;
+    let results = tokio::task::spawn_blocking(move || queries::borough_flows(&mut conn, &filters))
+        .await
+        .map_err(|e| {
+            tracing::error!("Spawn blocking task failed for borough flows: {}", e);
+            AppError(anyhow::Error::new(e))
+        })??;
 
     Ok(Json(results))
 }
 
+// Handler pour la liste des arrondissements (filtres côté client)
+#[utoipa::path(
+    get,
+    path = "/api/v1/boroughs",
+    tag = "analytics",
+    responses(
+        (status = 200, description = "Arrondissements présents dans dim_location", body = [String]),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
+pub async fn get_borough_list(
+    State(pool): State<DbPool>,
+) -> Result<Json<Vec<String>>, AppError> {
+    let mut conn = pool.get().map_err(|e| AppError(anyhow::Error::new(e)))?;
+    let results = tokio::task::spawn_blocking(move || queries::boroughs(&mut conn))
+        .await??;
+    Ok(Json(results))
+}
+
+// Handler pour l'endpoint générique d'agrégation (pivot)
+#[utoipa::path(
+    post,
+    path = "/api/v1/query",
+    tag = "analytics",
+    request_body = QuerySpec,
+    responses(
+        (status = 200, description = "Mesures agrégées par les dimensions demandées", body = QueryResponse),
+        (status = 400, description = "Spécification hors liste blanche ou trop coûteuse", body = String),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
+pub async fn run_query(
+    State(pool): State<DbPool>,
+    spec: Result<Json<QuerySpec>, JsonRejection>,
+) -> Result<Json<QueryResponse>, AppError> {
+    let Json(spec) = spec.map_err(|e| AppError::bad_request(e.body_text()))?;
+    let (query, aliases) = CubeQuery::from_spec(&spec)?;
+    query.validate()?;
+
+    let cost = query.cost();
+    let dimensions = query.dimensions.clone();
+    let mut conn = pool.get().map_err(|e| AppError(anyhow::Error::new(e)))?;
+    let rows = tokio::task::spawn_blocking(move || cube::run(&mut conn, &query))
+        .await??;
+
+    Ok(Json(QueryResponse { dimensions, measures: aliases, cost, rows }))
+}
+
 // Centralized error handling for handlers
 #[derive(Debug)]
-pub struct AppError(anyhow::Error);
+pub struct AppError(pub anyhow::Error);
+
+// Requête invalide (paramètres, spécification JSON) : réponse 400
+#[derive(Debug)]
+pub struct BadRequest(pub String);
+
+impl std::fmt::Display for BadRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for BadRequest {}
+
+// Ressource optionnelle absente (ex. géométries non installées) : réponse 503
+#[derive(Debug)]
+pub struct ServiceUnavailable(pub String);
+
+impl std::fmt::Display for ServiceUnavailable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for ServiceUnavailable {}
+
+impl AppError {
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        AppError(anyhow::Error::new(BadRequest(message.into())))
+    }
+
+    pub fn unavailable(message: impl Into<String>) -> Self {
+        AppError(anyhow::Error::new(ServiceUnavailable(message.into())))
+    }
+}
 
 impl IntoResponse for AppError {
     fn into_response(self) -> axum::response::Response {
+        if let Some(bad_request) = self.0.downcast_ref::<BadRequest>() {
+            tracing::debug!("Bad request: {}", bad_request);
+            return (StatusCode::BAD_REQUEST, bad_request.to_string()).into_response();
+        }
+        if let Some(unavailable) = self.0.downcast_ref::<ServiceUnavailable>() {
+            tracing::warn!("Service unavailable: {}", unavailable);
+            return (StatusCode::SERVICE_UNAVAILABLE, unavailable.to_string()).into_response();
+        }
         tracing::error!("Application error: {:#}", self.0);
         (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -478,6 +527,12 @@ impl From<r2d2::Error> for AppError {
     }
 }
 
+impl From<CubeError> for AppError {
+    fn from(err: CubeError) -> Self {
+        AppError::bad_request(err.0)
+    }
+}
+
 impl From<tokio::task::JoinError> for AppError {
     fn from(err: tokio::task::JoinError) -> Self {
         AppError(anyhow::Error::new(err).context("Tokio blocking task failed"))
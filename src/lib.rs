@@ -0,0 +1,189 @@
+//! DataViz : analyse des trajets de taxi sur le schéma en étoile
+//! (`fact_trips` + tables `dim_*`).
+//!
+//! Les types de l'API sont définis dans la crate `api-types` (partagée avec
+//! le frontend Yew) et ré-exportés par `models`.
+
+pub mod airports;
+pub mod anomalies;
+pub mod calendar;
+pub mod compare;
+pub mod concurrency;
+pub mod congestion;
+pub mod cube;
+pub mod db;
+pub mod distribution;
+pub mod durations;
+pub mod filters;
+pub mod forecast;
+pub mod graphql;
+pub mod handlers;
+pub mod location_geo;
+pub mod models;
+pub mod od_matrix;
+pub mod openapi;
+pub mod queries;
+pub mod rankings;
+pub mod revenue;
+pub mod route_efficiency;
+pub mod routing;
+pub mod schema;
+pub mod speed;
+pub mod stats;
+pub mod store_forward;
+pub mod taxi_zones;
+pub mod tiles;
+pub mod tips;
+pub mod weather;
+
+pub use db::DbPool;
+
+use axum::{routing::{get, post}, Router};
+use chrono::NaiveDate;
+use tower_http::services::ServeDir;
+
+use routing::{ApiRouter, Deprecation};
+
+// Date d'annonce de l'espace de noms `/v1` et sunset des anciennes routes
+// non versionnées (`/api/trip_volume`, ...).
+fn legacy_deprecation() -> Deprecation {
+    Deprecation::new(
+        NaiveDate::from_ymd_opt(2026, 10, 18).unwrap(),
+        NaiveDate::from_ymd_opt(2027, 4, 30).unwrap(),
+        "Routes non versionnées remplacées par l'espace de noms /v1",
+    )
+}
+
+// Anciennes routes non versionnées, servies comme alias dépréciés de /v1
+const LEGACY_ROUTES: &[&str] = &[
+    "/trip_volume",
+    "/payment_analysis",
+    "/hourly_activity",
+    "/passenger_analysis",
+    "/financial_breakdown",
+    "/vendor_analysis",
+    "/rate_code_analysis",
+    "/trip_duration_stats",
+    "/fare_efficiency",
+    "/borough_flows",
+    "/kpi_trends",
+    "/zone_activity",
+    "/boroughs",
+];
+
+/// Routes de la version 1 de l'API, relatives à `/v1`.
+pub fn api_v1_router() -> ApiRouter {
+    ApiRouter::new()
+        .route("/trip_volume", get(handlers::get_trip_volume_data))
+        .route("/payment_analysis", get(handlers::get_payment_analysis_data))
+        .route("/hourly_activity", get(handlers::get_hourly_activity_data))
+        .route("/passenger_analysis", get(handlers::get_passenger_analysis_data))
+        .route("/financial_breakdown", get(handlers::get_financial_breakdown_data))
+        .route("/vendor_analysis", get(handlers::get_vendor_analysis_data))
+        .route("/rate_code_analysis", get(handlers::get_rate_code_analysis_data))
+        .route("/trip_duration_stats", get(handlers::get_trip_duration_stats_data))
+        .route("/fare_efficiency", get(handlers::get_fare_efficiency_stats_data))
+        .route("/borough_flows", get(handlers::get_borough_flows_data))
+        .route("/kpi_trends", get(handlers::get_kpi_trend_data))
+        .route("/zone_activity", get(handlers::get_zone_activity_data))
+        .route("/boroughs", get(handlers::get_borough_list))
+        // Quantiles de durée par borough, paire de boroughs, heure, jour ou mois
+        .route("/trip_duration_stats/breakdown", get(durations::get_trip_duration_breakdown))
+        // Vitesses et indice de congestion (par dimension ou heure x jour)
+        .route("/speed", get(speed::get_speed_stats))
+        .route("/speed/heatmap", get(speed::get_speed_heatmap))
+        // Jours anormaux (volume ou recettes) pour le graphique de tendance
+        .route("/anomalies", get(anomalies::get_anomalies))
+        // Prévision de la demande (jour ou heure) avec intervalles et backtest
+        .route("/forecast", get(forecast::get_forecast))
+        // Trajets par condition météo ou tranche de température
+        .route("/weather/breakdown", get(weather::get_weather_breakdown))
+        // Comparaison A / B de deux segments avec tests de significativité
+        .route("/compare", post(compare::compare_segments))
+        // Décomposition de la variation du revenu entre deux périodes (cascade)
+        .route("/revenue/decomposition", get(revenue::get_revenue_decomposition))
+        // Classements top / bottom N d'une dimension avec groupe « Autres »
+        .route("/rankings", get(rankings::get_rankings))
+        // Trajets simultanés (série temporelle, flotte estimée, pics par zone)
+        .route("/concurrency", get(concurrency::get_concurrency))
+        .route("/concurrency/zones", get(concurrency::get_zone_concurrency))
+        // Trajets transmis en différé (store and forward) par vendeur, zone ou heure
+        .route("/store_and_forward", get(store_forward::get_store_forward))
+        // Matrice origine-destination par zone (format creux, paginé)
+        .route("/od_matrix", get(od_matrix::get_od_matrix))
+        // Rapport distance déclarée / vol d'oiseau par paire de zones
+        .route("/route_efficiency", get(route_efficiency::get_route_efficiency))
+        // Surcharge de congestion : recettes et impact d'un changement de politique
+        .route("/congestion_surcharge", get(congestion::get_congestion_surcharge))
+        .route("/congestion_surcharge/policy_impact", get(congestion::get_congestion_policy_impact))
+        // Distribution d'un champ numérique : histogramme, percentiles, moments
+        .route("/distribution/:field", get(distribution::get_distribution))
+        // Pourboires : ventilation, histogramme, pourboires nuls et montants proposés
+        .route("/tips/breakdown", get(tips::get_tip_breakdown))
+        .route("/tips/distribution", get(tips::get_tip_distribution))
+        .route("/tips/summary", get(tips::get_tip_summary))
+        // Trajets vers / depuis JFK, LaGuardia et Newark
+        .route("/airports/summary", get(airports::get_airport_summary))
+        .route("/airports/hourly", get(airports::get_airport_hourly_activity))
+        .route("/airports/fare_types", get(airports::get_airport_fare_types))
+        .route("/airports/durations", get(airports::get_airport_durations))
+        // Zones de taxi (GeoJSON) avec métriques par zone
+        .route("/geo/zones", get(taxi_zones::get_geo_zones))
+        // Tuiles vectorielles des zones : /tiles/{z}/{x}/{y}.mvt
+        .route("/tiles/:z/:x/:tile", get(tiles::get_zone_tile))
+        // Agrégation générique (mesures x dimensions, filtres, tri)
+        .route("/query", post(handlers::run_query))
+}
+
+/// Routes de l'API, sans préfixe : `/v1/...`, anciennes routes dépréciées,
+/// `/deprecations`, `/openapi.json` et `/graphql`.
+///
+/// Permet à un autre service de monter l'API sous son propre préfixe :
+/// `Router::new().nest("/analytics", data_viz::api_router()).with_state(pool)`.
+pub fn api_router() -> Router<DbPool> {
+    let mut api = ApiRouter::new().nest("/v1", api_v1_router());
+    for path in LEGACY_ROUTES {
+        api = api.deprecated_alias(path, &format!("/v1{}", path), legacy_deprecation());
+    }
+
+    // Routes retirées sans préavis avant la mise en place du versionnement
+    let removed_on = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+    api = api
+        .removed(
+            "/tip_analysis",
+            Deprecation::new(removed_on, removed_on, "Analyse des pourboires remplacée par /v1/tips/summary, /v1/tips/breakdown et /v1/tips/distribution")
+                .replaced_by("/v1/tips/summary"),
+        )
+        .removed(
+            "/geojson/taxi_zones",
+            Deprecation::new(removed_on, removed_on, "Géométries des zones de taxi remplacées par /v1/geo/zones (métriques incluses)")
+                .replaced_by("/v1/geo/zones"),
+        )
+        .removed(
+            "/trip_points",
+            Deprecation::new(removed_on, removed_on, "Points de trajet individuels retirés"),
+        );
+
+    api.build()
+        .route("/openapi.json", get(openapi::openapi_json))
+        // Requêtes cube (GraphQL), éditeur GraphiQL en GET
+        .route("/graphql", get(graphql::graphiql).post(graphql::graphql_handler))
+}
+
+/// Application complète du tableau de bord : page d'accueil, API sous `/api`,
+/// GraphQL sous `/graphql`, documentation interactive sous `/docs` et
+/// fichiers statiques sous `/static`.
+pub fn app(db_pool: DbPool) -> Router {
+    Router::new()
+        // Route for the root page
+        .route("/", get(handlers::root_handler))
+        .nest("/api", api_router())
+        // Requêtes cube (GraphQL), éditeur GraphiQL en GET
+        .route("/graphql", get(graphql::graphiql).post(graphql::graphql_handler))
+        // Service to serve static files (like JS, CSS)
+        .nest_service("/static", ServeDir::new("static"))
+        // Documentation interactive de l'API (Swagger UI)
+        .nest_service("/docs", ServeDir::new("static/docs"))
+        // Add the database pool to the application state
+        .with_state(db_pool)
+}
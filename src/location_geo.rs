@@ -0,0 +1,91 @@
+// Enrichissement de `dim_location` : centroïde, superficie et zones voisines
+// de chaque zone de taxi, calculés à partir des géométries TLC et stockés
+// dans `dim_location_geo` (voir `src/bin/enrich_locations.rs`).
+use std::collections::{BTreeSet, HashMap};
+
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use geo::orient::{Direction, Orient};
+use geo::{Centroid, GeodesicArea};
+
+use crate::schema::dim_location_geo;
+use crate::taxi_zones::TaxiZones;
+
+/// Taille des cellules (en degrés, ~10 m) utilisées pour détecter les
+/// frontières communes : deux zones sont voisines si des sommets de l'une et
+/// de l'autre tombent dans la même cellule ou dans des cellules adjacentes.
+pub const NEIGHBOUR_TOLERANCE_DEG: f64 = 0.0001;
+
+#[derive(Debug, Clone, PartialEq, Insertable)]
+#[diesel(table_name = dim_location_geo)]
+pub struct LocationGeo {
+    pub location_id: i32,
+    pub centroid_lon: f64,
+    pub centroid_lat: f64,
+    pub area_km2: f64,
+    pub neighbours: Vec<Option<i32>>,
+}
+
+/// Attributs géographiques de toutes les zones, triés par `location_id`.
+pub fn compute(zones: &TaxiZones) -> Vec<LocationGeo> {
+    let neighbours = neighbours(zones);
+    zones
+        .zones
+        .iter()
+        .filter_map(|zone| {
+            // Géométrie d'origine (niveau le plus détaillé)
+            let geometry = zone.geometry(u8::MAX).orient(Direction::Default);
+            let centroid = geometry.centroid()?;
+            let area_m2: f64 = geometry.0.iter().map(|p| p.geodesic_area_unsigned()).sum();
+            Some(LocationGeo {
+                location_id: zone.location_id,
+                centroid_lon: centroid.x(),
+                centroid_lat: centroid.y(),
+                area_km2: area_m2 / 1e6,
+                neighbours: neighbours
+                    .get(&zone.location_id)
+                    .map(|n| n.iter().copied().map(Some).collect())
+                    .unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+// Contiguïté par sommets proches, via une grille de hachage
+fn neighbours(zones: &TaxiZones) -> HashMap<i32, BTreeSet<i32>> {
+    let cell = |v: f64| (v / NEIGHBOUR_TOLERANCE_DEG).floor() as i64;
+    let mut grid: HashMap<(i64, i64), BTreeSet<i32>> = HashMap::new();
+    for zone in &zones.zones {
+        let geometry = zone.geometry(u8::MAX);
+        for polygon in &geometry.0 {
+            for ring in std::iter::once(polygon.exterior()).chain(polygon.interiors()) {
+                for c in &ring.0 {
+                    grid.entry((cell(c.x), cell(c.y))).or_default().insert(zone.location_id);
+                }
+            }
+        }
+    }
+
+    let mut result: HashMap<i32, BTreeSet<i32>> = HashMap::new();
+    for (&(cx, cy), ids) in &grid {
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                let Some(others) = grid.get(&(cx + dx, cy + dy)) else { continue };
+                for &a in ids {
+                    for &b in others.iter().filter(|&&b| b != a) {
+                        result.entry(a).or_default().insert(b);
+                    }
+                }
+            }
+        }
+    }
+    result
+}
+
+/// Remplace le contenu de `dim_location_geo` (transaction unique).
+pub fn store(conn: &mut PgConnection, rows: &[LocationGeo]) -> QueryResult<usize> {
+    conn.transaction(|conn| {
+        diesel::delete(dim_location_geo::table).execute(conn)?;
+        diesel::insert_into(dim_location_geo::table).values(rows).execute(conn)
+    })
+}
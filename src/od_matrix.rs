@@ -0,0 +1,176 @@
+// Matrice origine-destination au niveau des zones (jusqu'à 265 x 265 paires)
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use diesel::sql_types::{Array, BigInt, Float8, Integer, Nullable, Text};
+
+use crate::cube::{Bind, Join, SqlBuilder};
+use crate::db::DbPool;
+use crate::filters;
+use crate::handlers::AppError;
+use crate::models::{OdMatrix, OdMatrixParams, TripFilters, ZoneRef};
+
+/// Taille de page par défaut (nombre de paires).
+pub const DEFAULT_PAGE_SIZE: i64 = 5_000;
+/// Taille de page maximale : la matrice complète des 265 zones.
+pub const MAX_PAGE_SIZE: i64 = 265 * 265;
+
+#[derive(QueryableByName, Debug)]
+struct OdPairRow {
+    #[diesel(sql_type = Integer)]
+    pickup_location_id: i32,
+    #[diesel(sql_type = Integer)]
+    dropoff_location_id: i32,
+    #[diesel(sql_type = BigInt)]
+    trip_count: i64,
+    #[diesel(sql_type = Float8)]
+    avg_fare: f64,
+    #[diesel(sql_type = Float8)]
+    avg_duration_seconds: f64,
+    #[diesel(sql_type = Float8)]
+    avg_distance: f64,
+    #[diesel(sql_type = Float8)]
+    tip_rate: f64,
+    #[diesel(sql_type = BigInt)]
+    total_pairs: i64,
+}
+
+#[derive(QueryableByName, Debug)]
+struct PairCount {
+    #[diesel(sql_type = BigInt)]
+    total_pairs: i64,
+}
+
+#[derive(QueryableByName, Debug)]
+struct ZoneRow {
+    #[diesel(sql_type = Integer)]
+    location_id: i32,
+    #[diesel(sql_type = Nullable<Text>)]
+    zone: Option<String>,
+    #[diesel(sql_type = Nullable<Text>)]
+    borough: Option<String>,
+}
+
+// FROM ... GROUP BY ... HAVING des paires de zones d'au moins `min_trips` trajets
+fn grouped_pairs(filters: &TripFilters, builder: &mut SqlBuilder, min_trips: i64) -> String {
+    let from_where = filters::from_where(filters, builder, &[Join::PickupLocation, Join::DropoffLocation]);
+    let min_trips_marker = builder.bind(Bind::Text(min_trips.to_string()));
+    format!(
+        "{from_where} AND pul.location_id IS NOT NULL AND dol.location_id IS NOT NULL \
+        GROUP BY pul.location_id, dol.location_id \
+        HAVING COUNT(f.trip_id) >= {min_trips_marker}::bigint"
+    )
+}
+
+/// Une page de la matrice OD, paires triées par nombre de trajets décroissant.
+pub fn od_matrix(
+    conn: &mut PgConnection,
+    filters: &TripFilters,
+    min_trips: i64,
+    page: i64,
+    page_size: i64,
+) -> QueryResult<OdMatrix> {
+    let mut builder = SqlBuilder::default();
+    let grouped = grouped_pairs(filters, &mut builder, min_trips);
+    builder.sql = format!(
+        "SELECT \
+            pul.location_id AS pickup_location_id, \
+            dol.location_id AS dropoff_location_id, \
+            COUNT(f.trip_id)::bigint AS trip_count, \
+            COALESCE(AVG(f.fare_amount), 0.0)::float8 AS avg_fare, \
+            COALESCE(AVG(EXTRACT(EPOCH FROM f.trip_duration)), 0.0)::float8 AS avg_duration_seconds, \
+            COALESCE(AVG(f.trip_distance), 0.0)::float8 AS avg_distance, \
+            COALESCE(SUM(f.tip_amount) / NULLIF(SUM(f.fare_amount), 0), 0.0)::float8 AS tip_rate, \
+            COUNT(*) OVER ()::bigint AS total_pairs \
+        {} \
+        ORDER BY trip_count DESC, pickup_location_id, dropoff_location_id \
+        LIMIT {} OFFSET {}",
+        grouped,
+        page_size,
+        page.saturating_mul(page_size)
+    );
+    let pairs = builder.into_query().load::<OdPairRow>(conn)?;
+    // Au-delà de la dernière page, le total est compté à part
+    let total_pairs = match pairs.first() {
+        Some(pair) => pair.total_pairs,
+        None if page > 0 => {
+            let mut builder = SqlBuilder::default();
+            let grouped = grouped_pairs(filters, &mut builder, min_trips);
+            builder.sql = format!("SELECT COUNT(*)::bigint AS total_pairs FROM (SELECT 1 {grouped}) pairs");
+            builder.into_query().get_result::<PairCount>(conn)?.total_pairs
+        }
+        None => 0,
+    };
+
+    let mut ids: Vec<i32> = pairs
+        .iter()
+        .flat_map(|p| [p.pickup_location_id, p.dropoff_location_id])
+        .collect();
+    ids.sort_unstable();
+    ids.dedup();
+    let zones = diesel::sql_query(
+        "SELECT DISTINCT ON (location_id) location_id, zone, borough \
+        FROM dim_location \
+        WHERE location_id = ANY($1) \
+        ORDER BY location_id",
+    )
+    .bind::<Array<Integer>, _>(ids)
+    .load::<ZoneRow>(conn)?;
+
+    Ok(OdMatrix {
+        zones: zones
+            .into_iter()
+            .map(|z| ZoneRef { location_id: z.location_id, zone: z.zone, borough: z.borough })
+            .collect(),
+        origins: pairs.iter().map(|p| p.pickup_location_id).collect(),
+        destinations: pairs.iter().map(|p| p.dropoff_location_id).collect(),
+        trip_counts: pairs.iter().map(|p| p.trip_count).collect(),
+        avg_fare: pairs.iter().map(|p| p.avg_fare).collect(),
+        avg_duration_seconds: pairs.iter().map(|p| p.avg_duration_seconds).collect(),
+        avg_distance: pairs.iter().map(|p| p.avg_distance).collect(),
+        tip_rate: pairs.iter().map(|p| p.tip_rate).collect(),
+        total_pairs,
+        page,
+        page_size,
+        min_trips,
+    })
+}
+
+// Handler pour la matrice origine-destination par zone
+#[utoipa::path(
+    get,
+    path = "/api/v1/od_matrix",
+    tag = "analytics",
+    params(TripFilters, OdMatrixParams),
+    responses(
+        (status = 200, description = "Matrice OD creuse (une entrée par paire de zones)", body = OdMatrix),
+        (status = 400, description = "Paramètres invalides", body = String),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
+pub async fn get_od_matrix(
+    State(pool): State<DbPool>,
+    Query(filters): Query<TripFilters>,
+    Query(params): Query<OdMatrixParams>,
+) -> Result<Json<OdMatrix>, AppError> {
+    filters::validate(&filters)?;
+    let min_trips = params.min_trips.unwrap_or(1);
+    let page = params.page.unwrap_or(0);
+    let page_size = params.page_size.unwrap_or(DEFAULT_PAGE_SIZE);
+    if min_trips < 1 || page < 0 || !(1..=MAX_PAGE_SIZE).contains(&page_size) {
+        return Err(AppError::bad_request(format!(
+            "min_trips >= 1, page >= 0 et page_size entre 1 et {MAX_PAGE_SIZE} requis"
+        )));
+    }
+    if page.checked_mul(page_size).is_none() {
+        return Err(AppError::bad_request("page trop grande pour page_size"));
+    }
+
+    let mut conn = pool.get().map_err(|e| AppError(anyhow::Error::new(e)))?;
+    let matrix = tokio::task::spawn_blocking(move || od_matrix(&mut conn, &filters, min_trips, page, page_size))
+        .await??;
+    Ok(Json(matrix))
+}
@@ -0,0 +1,186 @@
+// Spécification OpenAPI 3 générée à partir des annotations `#[utoipa::path]`
+// des handlers et des schémas de `api-types`.
+use axum::Json;
+use utoipa::OpenApi;
+
+use crate::airports;
+use crate::anomalies;
+use crate::compare;
+use crate::concurrency;
+use crate::congestion;
+use crate::distribution;
+use crate::durations;
+use crate::forecast;
+use crate::handlers;
+use crate::od_matrix;
+use crate::rankings;
+use crate::revenue;
+use crate::route_efficiency;
+use crate::speed;
+use crate::store_forward;
+use crate::taxi_zones;
+use crate::tiles;
+use crate::tips;
+use crate::weather;
+use crate::models::{AggregatedTripStats, PaymentTypeAnalysis, HourlyWeekdayActivity, PassengerAnalysis, FinancialBreakdown, VendorAnalysis, KpiTrendData, TrendValue, ZoneActivity, RateCodeAnalysis, TripDurationStats, FareEfficiencyStats, BoroughFlowStats, RouteNotice, RouteStatus, QuerySpec, QueryMeasure, QueryFilter, QuerySort, QueryResponse, Aggregate, MeasureColumn, FilterField, FilterOp, SortDirection, CubeDimension, CubeRow, TripFilters, OdMatrix, ZoneRef, ZoneMetrics, RouteEfficiency, RouteEfficiencySort, AirportCode, AirportDirection, AirportSummary, AirportHourlyActivity, AirportFareTypeStats, AirportDurationDistribution, CongestionGrouping, CongestionSurchargeStats, CongestionPeriodStats, CongestionPolicyImpact, TipDimension, TipBreakdown, TipDistribution, TipPresetShare, TipSummary, DistributionField, BinningMode, HistogramBin, PercentileValue, OutlierCounts, Distribution, DurationDimension, TripDurationBreakdown, SpeedDimension, SpeedStats, SpeedHeatmapCell, AnomalyMetric, AnomalyMethod, Anomaly, AnomalyReport, ForecastGranularity, ForecastMethod, ForecastSplit, ForecastPoint, ForecastBacktest, ForecastSeries, ForecastReport, DayType, WeatherDimension, WeatherBreakdown, CompareMetric, CompareRequest, SegmentSummary, MetricStats, WelchTest, MannWhitneyTest, MetricComparison, CompareResponse, RevenueMixDimension, RevenueComponent, WaterfallStep, RevenuePeriod, RevenueMixContribution, RevenueDecomposition, CubeMeasure, RankingItem, RankingOther, RankingReport, ConcurrencyGranularity, ConcurrencyPoint, ConcurrencySeries, ZoneConcurrency, StoreForwardDimension, TelemetryProfile, StoreForwardGroup, StoreForwardReport };
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "DataViz API",
+        description = "Analyses des trajets de taxi sur le schéma en étoile `fact_trips` / `dim_*`."
+    ),
+    paths(
+        handlers::get_trip_volume_data,
+        handlers::get_payment_analysis_data,
+        handlers::get_hourly_activity_data,
+        handlers::get_passenger_analysis_data,
+        handlers::get_financial_breakdown_data,
+        handlers::get_vendor_analysis_data,
+        handlers::get_rate_code_analysis_data,
+        handlers::get_trip_duration_stats_data,
+        durations::get_trip_duration_breakdown,
+        speed::get_speed_stats,
+        speed::get_speed_heatmap,
+        anomalies::get_anomalies,
+        forecast::get_forecast,
+        weather::get_weather_breakdown,
+        compare::compare_segments,
+        revenue::get_revenue_decomposition,
+        rankings::get_rankings,
+        concurrency::get_concurrency,
+        concurrency::get_zone_concurrency,
+        store_forward::get_store_forward,
+        handlers::get_fare_efficiency_stats_data,
+        handlers::get_kpi_trend_data,
+        handlers::get_zone_activity_data,
+        handlers::get_borough_flows_data,
+        handlers::get_borough_list,
+        handlers::run_query,
+        od_matrix::get_od_matrix,
+        route_efficiency::get_route_efficiency,
+        taxi_zones::get_geo_zones,
+        tiles::get_zone_tile,
+        congestion::get_congestion_surcharge,
+        congestion::get_congestion_policy_impact,
+        airports::get_airport_summary,
+        airports::get_airport_hourly_activity,
+        airports::get_airport_fare_types,
+        airports::get_airport_durations,
+        tips::get_tip_breakdown,
+        tips::get_tip_distribution,
+        tips::get_tip_summary,
+        distribution::get_distribution,
+    ),
+    components(schemas(
+        AggregatedTripStats,
+        PaymentTypeAnalysis,
+        HourlyWeekdayActivity,
+        PassengerAnalysis,
+        FinancialBreakdown,
+        VendorAnalysis,
+        RateCodeAnalysis,
+        TripDurationStats,
+        FareEfficiencyStats,
+        KpiTrendData,
+        TrendValue,
+        ZoneActivity,
+        BoroughFlowStats,
+        RouteNotice,
+        RouteStatus,
+        QuerySpec,
+        QueryMeasure,
+        QueryFilter,
+        QuerySort,
+        QueryResponse,
+        Aggregate,
+        MeasureColumn,
+        FilterField,
+        FilterOp,
+        SortDirection,
+        CubeDimension,
+        CubeRow,
+        TripFilters,
+        OdMatrix,
+        ZoneRef,
+        ZoneMetrics,
+        RouteEfficiency,
+        RouteEfficiencySort,
+        AirportCode,
+        AirportDirection,
+        AirportSummary,
+        AirportHourlyActivity,
+        AirportFareTypeStats,
+        AirportDurationDistribution,
+        CongestionGrouping,
+        CongestionSurchargeStats,
+        CongestionPeriodStats,
+        CongestionPolicyImpact,
+        TipDimension,
+        TipBreakdown,
+        TipDistribution,
+        TipPresetShare,
+        TipSummary,
+        DistributionField,
+        BinningMode,
+        HistogramBin,
+        PercentileValue,
+        OutlierCounts,
+        Distribution,
+        DurationDimension,
+        TripDurationBreakdown,
+        SpeedDimension,
+        SpeedStats,
+        SpeedHeatmapCell,
+        AnomalyMetric,
+        AnomalyMethod,
+        Anomaly,
+        AnomalyReport,
+        ForecastGranularity,
+        ForecastMethod,
+        ForecastSplit,
+        ForecastPoint,
+        ForecastBacktest,
+        ForecastSeries,
+        ForecastReport,
+        DayType,
+        WeatherDimension,
+        WeatherBreakdown,
+        CompareMetric,
+        CompareRequest,
+        SegmentSummary,
+        MetricStats,
+        WelchTest,
+        MannWhitneyTest,
+        MetricComparison,
+        CompareResponse,
+        RevenueMixDimension,
+        RevenueComponent,
+        WaterfallStep,
+        RevenuePeriod,
+        RevenueMixContribution,
+        RevenueDecomposition,
+        CubeMeasure,
+        RankingItem,
+        RankingOther,
+        RankingReport,
+        ConcurrencyGranularity,
+        ConcurrencyPoint,
+        ConcurrencySeries,
+        ZoneConcurrency,
+        StoreForwardDimension,
+        TelemetryProfile,
+        StoreForwardGroup,
+        StoreForwardReport,
+    )),
+    tags(
+        (name = "analytics", description = "Agrégats sur les trajets"),
+        (name = "airports", description = "Trajets desservant JFK, LaGuardia et Newark")
+    )
+)]
+pub struct ApiDoc;
+
+// Handler servant le document OpenAPI (consommé par l'UI sous /docs)
+pub async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
@@ -0,0 +1,292 @@
+// Requêtes SQL d'analyse, indépendantes d'Axum.
+// Chaque fonction prend une connexion synchrone : les handlers les exécutent
+// dans `spawn_blocking`, mais d'autres services peuvent les appeler directement.
+use chrono::{Datelike, NaiveDate};
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use crate::cube::{Join, SqlBuilder};
+use crate::filters;
+use crate::models::{AggregatedTripStats, PaymentTypeAnalysis, HourlyWeekdayActivity, PassengerAnalysis, FinancialBreakdown, VendorAnalysis, ZoneActivity, RateCodeAnalysis, TripDurationStats, FareEfficiencyStats, BoroughFlowStats, TripFilters };
+
+// Volume de trajets et moyennes par jour
+pub fn trip_volume(conn: &mut PgConnection, filters: &TripFilters) -> QueryResult<Vec<AggregatedTripStats>> {
+    let mut builder = SqlBuilder::default();
+    let from_where = filters::from_where(filters, &mut builder, &[Join::Date]);
+    builder.sql = format!(
+        "SELECT \
+            d.full_date::date as date, \
+            COUNT(f.trip_id)::bigint AS trip_count, \
+            COALESCE(AVG(f.total_amount), 0.0)::float8 AS avg_total_amount, \
+            COALESCE(AVG(f.tip_amount), 0.0)::float8 AS avg_tip_amount, \
+            COALESCE(AVG(f.trip_distance), 0.0)::float8 AS avg_trip_distance, \
+            COALESCE(AVG(EXTRACT(EPOCH FROM f.trip_duration)), 0.0)::float8 AS avg_trip_duration_seconds \
+        {from_where} \
+        GROUP BY d.full_date \
+        ORDER BY d.full_date"
+    );
+    builder.into_query().load::<AggregatedTripStats>(conn)
+}
+
+// Analyse par type de paiement
+pub fn payment_analysis(conn: &mut PgConnection, filters: &TripFilters) -> QueryResult<Vec<PaymentTypeAnalysis>> {
+    let mut builder = SqlBuilder::default();
+    let from_where = filters::from_where(filters, &mut builder, &[Join::PaymentType]);
+    builder.sql = format!(
+        "SELECT \
+            COALESCE(pt.payment_type_name, 'Inconnu') as payment_type_name, \
+            COUNT(f.trip_id)::bigint AS trip_count, \
+            COALESCE(AVG(f.tip_amount), 0.0)::float8 AS avg_tip_amount \
+        {from_where} \
+        GROUP BY pt.payment_type_name \
+        ORDER BY trip_count DESC" // Trier par nombre de trajets
+    );
+    builder.into_query().load::<PaymentTypeAnalysis>(conn)
+}
+
+// Activité par heure et jour de la semaine
+pub fn hourly_activity(conn: &mut PgConnection, filters: &TripFilters) -> QueryResult<Vec<HourlyWeekdayActivity>> {
+    let mut builder = SqlBuilder::default();
+    let from_where = filters::from_where(filters, &mut builder, &[]);
+    builder.sql = format!(
+        "SELECT \
+            EXTRACT(ISODOW FROM f.tpep_pickup_datetime)::INTEGER AS day_of_week, \
+            EXTRACT(HOUR FROM f.tpep_pickup_datetime)::INTEGER AS hour_of_day, \
+            COUNT(f.trip_id)::bigint AS trip_count \
+        {from_where} \
+        GROUP BY 1, 2 \
+        ORDER BY 1, 2"
+    );
+    builder.into_query().load::<HourlyWeekdayActivity>(conn)
+}
+
+// Analyse par nombre de passagers
+pub fn passenger_analysis(conn: &mut PgConnection, filters: &TripFilters) -> QueryResult<Vec<PassengerAnalysis>> {
+    let mut builder = SqlBuilder::default();
+    let from_where = filters::from_where(filters, &mut builder, &[]);
+    builder.sql = format!(
+        "SELECT \
+            f.passenger_count, \
+            COUNT(f.trip_id)::bigint AS trip_count \
+        {from_where} \
+        GROUP BY f.passenger_count \
+        ORDER BY f.passenger_count ASC"
+    );
+    builder.into_query().load::<PassengerAnalysis>(conn)
+}
+
+// Décomposition financière (par mois). Les montants absents comptent pour 0 :
+// la somme des composantes moyennes retombe sur `avg_total_amount`.
+pub fn financial_breakdown(conn: &mut PgConnection, filters: &TripFilters) -> QueryResult<Vec<FinancialBreakdown>> {
+    let mut builder = SqlBuilder::default();
+    let from_where = filters::from_where(filters, &mut builder, &[Join::Date]);
+    builder.sql = format!(
+        "SELECT \
+            DATE_TRUNC('month', d.full_date)::date as date, \
+            COALESCE(AVG(COALESCE(f.fare_amount, 0)), 0.0)::float8 AS avg_fare_amount, \
+            COALESCE(AVG(COALESCE(f.tip_amount, 0)), 0.0)::float8 AS avg_tip_amount, \
+            COALESCE(AVG(COALESCE(f.tolls_amount, 0)), 0.0)::float8 AS avg_tolls_amount, \
+            COALESCE(AVG(COALESCE(f.mta_tax, 0)), 0.0)::float8 AS avg_mta_tax, \
+            COALESCE(AVG(COALESCE(f.improvement_surcharge, 0)), 0.0)::float8 AS avg_improvement_surcharge, \
+            COALESCE(AVG(COALESCE(f.extra, 0)), 0.0)::float8 AS avg_extra, \
+            COALESCE(AVG(COALESCE(f.congestion_surcharge, 0)), 0.0)::float8 AS avg_congestion_surcharge, \
+            COALESCE(AVG(COALESCE(f.airport_fee, 0)), 0.0)::float8 AS avg_airport_fee, \
+            COALESCE(AVG(COALESCE(f.total_amount, 0)), 0.0)::float8 AS avg_total_amount \
+        {from_where} \
+        GROUP BY DATE_TRUNC('month', d.full_date) \
+        ORDER BY date ASC"
+    );
+    builder.into_query().load::<FinancialBreakdown>(conn)
+}
+
+// Analyse par vendeur
+pub fn vendor_analysis(conn: &mut PgConnection, filters: &TripFilters) -> QueryResult<Vec<VendorAnalysis>> {
+    let mut builder = SqlBuilder::default();
+    let from_where = filters::from_where(filters, &mut builder, &[Join::Vendor]);
+    builder.sql = format!(
+        "SELECT \
+            COALESCE(NULLIF(TRIM(v.vendor_name), ''), 'Vendor ' || v.vendor_key::text, 'Inconnu') as vendor_name, \
+            COUNT(f.trip_id)::bigint AS trip_count, \
+            COALESCE(AVG(f.total_amount), 0.0)::float8 AS avg_total_amount, \
+            COALESCE(AVG(f.trip_distance), 0.0)::float8 AS avg_trip_distance \
+        {from_where} \
+        GROUP BY v.vendor_key, v.vendor_name \
+        ORDER BY trip_count DESC"
+    );
+    builder.into_query().load::<VendorAnalysis>(conn)
+}
+
+// Analyse par code tarifaire
+pub fn rate_code_analysis(conn: &mut PgConnection, filters: &TripFilters) -> QueryResult<Vec<RateCodeAnalysis>> {
+    let mut builder = SqlBuilder::default();
+    let from_where = filters::from_where(filters, &mut builder, &[Join::RateCode]);
+    builder.sql = format!(
+        "SELECT \
+            COALESCE(rc.rate_code_name, 'Inconnu') as rate_code_name, \
+            COUNT(f.trip_id)::bigint AS trip_count, \
+            COALESCE(AVG(f.total_amount), 0.0)::float8 AS avg_total_amount, \
+            COALESCE(AVG(f.trip_distance), 0.0)::float8 AS avg_trip_distance, \
+            COALESCE(AVG(f.tip_amount), 0.0)::float8 AS avg_tip_amount \
+        {from_where} \
+        GROUP BY rc.rate_code_name \
+        ORDER BY trip_count DESC"
+    );
+    builder.into_query().load::<RateCodeAnalysis>(conn)
+}
+
+// Statistiques globales sur la durée des trajets
+pub fn trip_duration_stats(conn: &mut PgConnection, filters: &TripFilters) -> QueryResult<TripDurationStats> {
+    let mut builder = SqlBuilder::default();
+    let from_where = filters::from_where(filters, &mut builder, &[]);
+    builder.sql = format!(
+        "SELECT \
+            COALESCE(AVG(EXTRACT(EPOCH FROM f.trip_duration)), 0.0)::float8 AS avg_duration_seconds, \
+            COALESCE(MIN(EXTRACT(EPOCH FROM f.trip_duration)), 0.0)::float8 AS min_duration_seconds, \
+            COALESCE(MAX(EXTRACT(EPOCH FROM f.trip_duration)), 0.0)::float8 AS max_duration_seconds, \
+            COALESCE(PERCENTILE_CONT(0.25) WITHIN GROUP (ORDER BY EXTRACT(EPOCH FROM f.trip_duration)), 0.0)::float8 AS p25_duration_seconds, \
+            COALESCE(PERCENTILE_CONT(0.50) WITHIN GROUP (ORDER BY EXTRACT(EPOCH FROM f.trip_duration)), 0.0)::float8 AS p50_duration_seconds, \
+            COALESCE(PERCENTILE_CONT(0.75) WITHIN GROUP (ORDER BY EXTRACT(EPOCH FROM f.trip_duration)), 0.0)::float8 AS p75_duration_seconds \
+        {from_where} AND f.trip_duration IS NOT NULL AND EXTRACT(EPOCH FROM f.trip_duration) > 0"
+    );
+    builder.into_query().get_result::<TripDurationStats>(conn)
+}
+
+// Statistiques d'efficacité tarifaire
+pub fn fare_efficiency_stats(conn: &mut PgConnection, filters: &TripFilters) -> QueryResult<FareEfficiencyStats> {
+    let mut builder = SqlBuilder::default();
+    let from_where = filters::from_where(filters, &mut builder, &[]);
+    builder.sql = format!(
+        "SELECT \
+            COALESCE(AVG(CASE WHEN f.trip_distance > 0 THEN f.fare_amount / f.trip_distance ELSE NULL END), 0.0)::float8 AS avg_fare_per_km, \
+            COALESCE(AVG(CASE WHEN EXTRACT(EPOCH FROM f.trip_duration) > 0 THEN f.fare_amount / (EXTRACT(EPOCH FROM f.trip_duration) / 60.0) ELSE NULL END), 0.0)::float8 AS avg_fare_per_minute \
+        {from_where}"
+    );
+    builder.into_query().get_result::<FareEfficiencyStats>(conn)
+}
+
+// Période des tendances KPI : dates des filtres, T4 2024 à défaut
+pub fn kpi_period(filters: &TripFilters) -> (NaiveDate, NaiveDate) {
+    let start = filters.start_date.or(NaiveDate::from_ymd_opt(2024, 10, 1)).expect("date valide");
+    let end = filters.end_date.or(NaiveDate::from_ymd_opt(2024, 12, 31)).expect("date valide");
+    (start, end)
+}
+
+// Nombre de mois calendaires touchés par la période KPI
+pub fn kpi_period_months(filters: &TripFilters) -> i64 {
+    let (start, end) = kpi_period(filters);
+    let months = (end.year() - start.year()) as i64 * 12 + end.month() as i64 - start.month() as i64 + 1;
+    months.max(1)
+}
+
+fn kpi_filters(filters: &TripFilters) -> TripFilters {
+    let (start, end) = kpi_period(filters);
+    TripFilters { start_date: Some(start), end_date: Some(end), ..filters.clone() }
+}
+
+// Agrégat sur la période KPI utilisé par les tendances KPI
+pub fn kpi_current_period(conn: &mut PgConnection, filters: &TripFilters) -> QueryResult<AggregatedTripStats> {
+    let mut builder = SqlBuilder::default();
+    let from_where = filters::from_where(&kpi_filters(filters), &mut builder, &[Join::Date]);
+    builder.sql = format!(
+        "SELECT \
+            COALESCE(MAX(d.full_date), CURRENT_DATE)::date as date, \
+            COALESCE(COUNT(f.trip_id), 0)::bigint AS trip_count, \
+            COALESCE(AVG(f.total_amount), 0.0)::float8 AS avg_total_amount, \
+            0.0::float8 AS avg_tip_amount, \
+            0.0::float8 AS avg_trip_distance, \
+            0.0::float8 AS avg_trip_duration_seconds \
+        {from_where}"
+    );
+    builder.into_query().get_result::<AggregatedTripStats>(conn)
+}
+
+// Mois le plus chargé de la période KPI (None si aucune donnée)
+pub fn kpi_busiest_month(conn: &mut PgConnection, filters: &TripFilters) -> QueryResult<Option<AggregatedTripStats>> {
+    let mut builder = SqlBuilder::default();
+    let from_where = filters::from_where(&kpi_filters(filters), &mut builder, &[Join::Date]);
+    builder.sql = format!(
+        "WITH monthly_data AS (
+            SELECT \
+                DATE_TRUNC('month', d.full_date)::date as date, \
+                COUNT(f.trip_id) AS trip_count, \
+                AVG(f.total_amount) AS avg_total_amount, \
+                AVG(f.tip_amount) AS avg_tip_amount, \
+                AVG(f.trip_distance) AS avg_trip_distance, \
+                AVG(EXTRACT(EPOCH FROM f.trip_duration)) AS avg_trip_duration_seconds \
+            {from_where} \
+            GROUP BY DATE_TRUNC('month', d.full_date) \
+            ORDER BY trip_count DESC \
+            LIMIT 1
+        )
+        SELECT \
+            COALESCE(date, DATE_TRUNC('month', CURRENT_DATE)::date) as date, \
+            COALESCE(trip_count, 0)::bigint AS trip_count, \
+            COALESCE(avg_total_amount, 0.0)::float8 AS avg_total_amount, \
+            COALESCE(avg_tip_amount, 0.0)::float8 AS avg_tip_amount, \
+            COALESCE(avg_trip_distance, 0.0)::float8 AS avg_trip_distance, \
+            COALESCE(avg_trip_duration_seconds, 0.0)::float8 AS avg_trip_duration_seconds \
+        FROM monthly_data \
+        UNION ALL \
+        SELECT \
+            DATE_TRUNC('month', CURRENT_DATE)::date as date, \
+            0::bigint AS trip_count, \
+            0.0::float8 AS avg_total_amount, \
+            0.0::float8 AS avg_tip_amount, \
+            0.0::float8 AS avg_trip_distance, \
+            0.0::float8 AS avg_trip_duration_seconds \
+        WHERE NOT EXISTS (SELECT 1 FROM monthly_data) \
+        LIMIT 1"
+    );
+    builder.into_query().get_result::<AggregatedTripStats>(conn)
+    .optional()
+}
+
+// Activité par zone de départ
+pub fn zone_activity(conn: &mut PgConnection, filters: &TripFilters) -> QueryResult<Vec<ZoneActivity>> {
+    let mut builder = SqlBuilder::default();
+    let from_where = filters::from_where(filters, &mut builder, &[Join::PickupLocation]);
+    builder.sql = format!(
+        "SELECT \
+            pul.location_id, \
+            pul.zone, \
+            pul.borough, \
+            COUNT(f.trip_id)::bigint AS trip_count, \
+            COALESCE(AVG(f.total_amount), 0.0)::float8 AS avg_total_amount \
+        {from_where} \
+        GROUP BY pul.location_id, pul.zone, pul.borough \
+        ORDER BY trip_count DESC"
+    );
+    builder.into_query().load::<ZoneActivity>(conn)
+}
+
+// Flux de trajets entre arrondissements
+pub fn borough_flows(conn: &mut PgConnection, filters: &TripFilters) -> QueryResult<Vec<BoroughFlowStats>> {
+    let mut builder = SqlBuilder::default();
+    let from_where =
+        filters::from_where(filters, &mut builder, &[Join::PickupLocation, Join::DropoffLocation]);
+    builder.sql = format!(
+        "SELECT \
+            COALESCE(pul.borough, 'Inconnu') AS pickup_borough, \
+            COALESCE(dol.borough, 'Inconnu') AS dropoff_borough, \
+            COUNT(f.trip_id)::bigint AS trip_count, \
+            COALESCE(AVG(f.fare_amount), 0.0)::float8 AS avg_fare_amount \
+        {from_where} \
+        AND pul.borough IS NOT NULL AND dol.borough IS NOT NULL AND pul.borough != 'Unknown' AND dol.borough != 'Unknown' AND pul.borough != '' AND dol.borough != '' \
+        GROUP BY pul.borough, dol.borough \
+        ORDER BY trip_count DESC \
+        LIMIT 100"
+    );
+    builder.into_query().load::<BoroughFlowStats>(conn)
+}
+
+// Liste des arrondissements connus
+pub fn boroughs(conn: &mut PgConnection) -> QueryResult<Vec<String>> {
+    use crate::schema::dim_location::dsl::*;
+
+    dim_location
+        .select(borough)
+        .filter(borough.is_not_null().and(borough.ne("")).and(borough.ne("Unknown")))
+        .distinct()
+        .order(borough.asc())
+        .load::<Option<String>>(conn)
+        .map(|rows| rows.into_iter().flatten().collect())
+}
@@ -0,0 +1,239 @@
+// Classements top N / bottom N : une dimension du cube classée selon une
+// mesure prédéfinie, avec parts du total (Pareto), rangs de la période
+// précédente et groupe « Autres » pour la longue traîne.
+//
+// Les rangs suivent le classement olympique (RANK) : les ex aequo partagent
+// un rang et sont tous retenus, si bien que le top N peut compter plus de N
+// groupes. La période précédente a la durée de `start_date`..`end_date` et
+// se termine la veille de `start_date`.
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use chrono::{Duration, NaiveDate};
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Float8, Nullable, Text};
+
+use crate::cube::{self, Bind, Measure, SqlBuilder};
+use crate::db::DbPool;
+use crate::filters;
+use crate::handlers::AppError;
+use crate::models::{
+    CubeDimension, CubeMeasure, RankingItem, RankingOther, RankingParams, RankingReport, TripFilters,
+};
+
+const DEFAULT_TOP: i64 = 10;
+const MAX_RANKED: i64 = 100;
+// Part du total couverte par les premiers groupes pour `pareto_80_groups`
+const PARETO_SHARE: f64 = 0.8;
+
+#[derive(QueryableByName)]
+struct RankedGroup {
+    #[diesel(sql_type = Text)]
+    key: String,
+    #[diesel(sql_type = Nullable<Float8>)]
+    value: Option<f64>,
+    #[diesel(sql_type = BigInt)]
+    rank: i64,
+    // Rang par valeur croissante, pour le bottom N
+    #[diesel(sql_type = BigInt)]
+    bottom_rank: i64,
+}
+
+#[derive(QueryableByName)]
+struct MeasureValue {
+    #[diesel(sql_type = Nullable<Float8>)]
+    value: Option<f64>,
+}
+
+// Seules les sommes se partagent entre groupes
+fn is_additive(measure: CubeMeasure) -> bool {
+    matches!(measure, CubeMeasure::TripCount | CubeMeasure::TotalRevenue)
+}
+
+fn key_sql(dimension: CubeDimension) -> String {
+    let (sql, _, _) = cube::dimension_sql(dimension);
+    format!("COALESCE(({sql})::text, 'Inconnu')")
+}
+
+fn ranked_groups(
+    conn: &mut PgConnection,
+    filters: &TripFilters,
+    dimension: CubeDimension,
+    measure: CubeMeasure,
+) -> QueryResult<Vec<RankedGroup>> {
+    let (_, _, join) = cube::dimension_sql(dimension);
+    let mut builder = SqlBuilder::default();
+    let from_where = filters::from_where(filters, &mut builder, join.as_slice());
+    let value = Measure::from(measure).to_sql(&mut builder);
+    builder.sql = format!(
+        "WITH groups AS ( \
+            SELECT {key} AS key, ({value})::float8 AS value \
+            {from_where} \
+            GROUP BY 1 \
+        ) \
+        SELECT \
+            key, \
+            value, \
+            RANK() OVER (ORDER BY value DESC NULLS LAST)::bigint AS rank, \
+            RANK() OVER (ORDER BY value ASC NULLS LAST)::bigint AS bottom_rank \
+        FROM groups \
+        ORDER BY rank, key",
+        key = key_sql(dimension),
+    );
+    builder.into_query().load::<RankedGroup>(conn)
+}
+
+// Mesure sur les trajets filtrés, restreinte aux groupes hors de `excluded`
+// si la liste est fournie
+fn measure_value(
+    conn: &mut PgConnection,
+    filters: &TripFilters,
+    dimension: CubeDimension,
+    measure: CubeMeasure,
+    excluded: Option<Vec<String>>,
+) -> QueryResult<Option<f64>> {
+    let (_, _, join) = cube::dimension_sql(dimension);
+    let mut builder = SqlBuilder::default();
+    let mut from_where = filters::from_where(filters, &mut builder, join.as_slice());
+    if let Some(excluded) = excluded {
+        let marker = builder.bind(Bind::TextArray(excluded));
+        from_where.push_str(&format!(" AND {} <> ALL({marker}::text[])", key_sql(dimension)));
+    }
+    let value = Measure::from(measure).to_sql(&mut builder);
+    builder.sql = format!("SELECT ({value})::float8 AS value {from_where}");
+    Ok(builder.into_query().get_result::<MeasureValue>(conn)?.value)
+}
+
+// Période de même durée se terminant la veille de `start_date`
+fn previous_period(filters: &TripFilters) -> Option<(NaiveDate, NaiveDate)> {
+    let (start, end) = (filters.start_date?, filters.end_date?);
+    let previous_end = start - Duration::days(1);
+    Some((previous_end - (end - start), previous_end))
+}
+
+/// Top et bottom `dimension` selon `measure`, avec rangs de la période
+/// précédente et groupe « Autres ».
+pub fn rankings(
+    conn: &mut PgConnection,
+    filters: &TripFilters,
+    dimension: CubeDimension,
+    measure: CubeMeasure,
+    params: &RankingParams,
+) -> QueryResult<RankingReport> {
+    let top = params.top.unwrap_or(DEFAULT_TOP);
+    let bottom = params.bottom.unwrap_or(0);
+    let groups = ranked_groups(conn, filters, dimension, measure)?;
+    let total = measure_value(conn, filters, dimension, measure, None)?;
+
+    let previous = previous_period(filters);
+    let previous_ranks: HashMap<String, (Option<f64>, i64)> = match previous {
+        Some((start_date, end_date)) => {
+            let previous_filters =
+                TripFilters { start_date: Some(start_date), end_date: Some(end_date), ..filters.clone() };
+            ranked_groups(conn, &previous_filters, dimension, measure)?
+                .into_iter()
+                .map(|g| (g.key, (g.value, g.rank)))
+                .collect()
+        }
+        None => HashMap::new(),
+    };
+
+    // Parts sur le total des groupes, cumulées dans l'ordre du classement
+    let additive_total = total.filter(|t| is_additive(measure) && *t != 0.0);
+    let mut cumulative = 0.0;
+    let mut pareto_80_groups = None;
+    let mut shares = Vec::with_capacity(groups.len());
+    for (i, group) in groups.iter().enumerate() {
+        let share = additive_total.map(|t| group.value.unwrap_or(0.0) / t);
+        cumulative += share.unwrap_or(0.0);
+        if additive_total.is_some() && pareto_80_groups.is_none() && cumulative >= PARETO_SHARE - 1e-9 {
+            pareto_80_groups = Some(i as i64 + 1);
+        }
+        shares.push((share, additive_total.map(|_| cumulative)));
+    }
+
+    let item = |i: usize| {
+        let group = &groups[i];
+        let (share, cumulative_share) = shares[i];
+        let previous = previous_ranks.get(&group.key);
+        RankingItem {
+            key: group.key.clone(),
+            value: group.value,
+            rank: group.rank,
+            share,
+            cumulative_share,
+            previous_value: previous.and_then(|p| p.0),
+            previous_rank: previous.map(|p| p.1),
+            rank_change: previous.map(|p| p.1 - group.rank),
+        }
+    };
+    let top_items: Vec<RankingItem> = (0..groups.len()).filter(|&i| groups[i].rank <= top).map(item).collect();
+    let mut bottom_indices: Vec<usize> = (0..groups.len()).filter(|&i| groups[i].bottom_rank <= bottom).collect();
+    bottom_indices.sort_by_key(|&i| (groups[i].bottom_rank, groups[i].key.clone()));
+    let bottom_items: Vec<RankingItem> = bottom_indices.into_iter().map(item).collect();
+
+    let shown: Vec<String> = groups
+        .iter()
+        .filter(|g| g.rank <= top || g.bottom_rank <= bottom)
+        .map(|g| g.key.clone())
+        .collect();
+    let other_count = (groups.len() - shown.len()) as i64;
+    let other = if params.other.unwrap_or(true) && other_count > 0 {
+        let value = measure_value(conn, filters, dimension, measure, Some(shown))?;
+        Some(RankingOther {
+            group_count: other_count,
+            value,
+            share: additive_total.map(|t| value.unwrap_or(0.0) / t),
+        })
+    } else {
+        None
+    };
+
+    Ok(RankingReport {
+        dimension,
+        measure,
+        total,
+        group_count: groups.len() as i64,
+        previous_start: previous.map(|p| p.0),
+        previous_end: previous.map(|p| p.1),
+        pareto_80_groups,
+        top: top_items,
+        bottom: bottom_items,
+        other,
+    })
+}
+
+// Handler pour les classements d'une dimension
+#[utoipa::path(
+    get,
+    path = "/api/v1/rankings",
+    tag = "analytics",
+    params(TripFilters, RankingParams),
+    responses(
+        (status = 200, description = "Premiers et derniers groupes, parts du total, évolution des rangs et groupe « Autres »", body = RankingReport),
+        (status = 400, description = "Paramètres invalides", body = String),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
+pub async fn get_rankings(
+    State(pool): State<DbPool>,
+    Query(filters): Query<TripFilters>,
+    Query(params): Query<RankingParams>,
+) -> Result<Json<RankingReport>, AppError> {
+    filters::validate(&filters)?;
+    for (name, value) in [("top", params.top), ("bottom", params.bottom)] {
+        if value.is_some_and(|n| !(0..=MAX_RANKED).contains(&n)) {
+            return Err(AppError::bad_request(format!("{name} entre 0 et {MAX_RANKED} requis")));
+        }
+    }
+    let dimension = params.dimension.unwrap_or(CubeDimension::PickupZone);
+    let measure = params.measure.unwrap_or(CubeMeasure::TripCount);
+    let mut conn = pool.get().map_err(|e| AppError(anyhow::Error::new(e)))?;
+    let report =
+        tokio::task::spawn_blocking(move || rankings(&mut conn, &filters, dimension, measure, &params)).await??;
+    Ok(Json(report))
+}
@@ -0,0 +1,242 @@
+// Décomposition de la variation du revenu entre une période de référence (0)
+// et une période comparée (1), pour un graphique en cascade.
+//
+// Le revenu s'écrit R = N x Σ_g s_g x (f_g + t_g + o_g) : N trajets, part s_g
+// des trajets du groupe g (borough ou code tarifaire), tarif f_g, pourboire
+// t_g et surcharges / péages o_g moyens par trajet du groupe (o_g est le reste
+// du montant total). Chaque produit est décomposé par la méthode des moyennes
+// (Bennet), ΔXY = ΔX x (Y0 + Y1) / 2 + ΔY x (X0 + X1) / 2, ce qui rend la
+// somme des effets exactement égale à R1 - R0, sans dépendre d'un ordre.
+use std::collections::BTreeMap;
+
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use chrono::NaiveDate;
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Float8, Text};
+
+use crate::cube::{self, Bind, Join, SqlBuilder};
+use crate::db::DbPool;
+use crate::filters;
+use crate::handlers::AppError;
+use crate::models::{
+    CubeDimension, RevenueComponent, RevenueDecomposition, RevenueDecompositionParams, RevenueMixContribution,
+    RevenueMixDimension, RevenuePeriod, TripFilters, WaterfallStep,
+};
+
+#[derive(QueryableByName)]
+struct GroupTotals {
+    #[diesel(sql_type = Text)]
+    key: String,
+    #[diesel(sql_type = BigInt)]
+    trip_count: i64,
+    #[diesel(sql_type = Float8)]
+    fare: f64,
+    #[diesel(sql_type = Float8)]
+    tip: f64,
+    #[diesel(sql_type = Float8)]
+    total: f64,
+}
+
+impl GroupTotals {
+    // (tarif, pourboire, surcharges et péages) moyens par trajet
+    fn per_trip(&self) -> Option<[f64; 3]> {
+        let n = self.trip_count as f64;
+        (self.trip_count > 0).then(|| [self.fare / n, self.tip / n, (self.total - self.fare - self.tip) / n])
+    }
+}
+
+fn group_totals(
+    conn: &mut PgConnection,
+    filters: &TripFilters,
+    mix: RevenueMixDimension,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> QueryResult<Vec<GroupTotals>> {
+    let dimension = match mix {
+        RevenueMixDimension::PickupBorough => CubeDimension::PickupBorough,
+        RevenueMixDimension::RateCode => CubeDimension::RateCode,
+    };
+    let (key, _, join) = cube::dimension_sql(dimension);
+    let mut joins = vec![Join::Date];
+    joins.extend(join);
+
+    let mut builder = SqlBuilder::default();
+    let mut from_where = filters::from_where(filters, &mut builder, &joins);
+    from_where.push_str(&format!(
+        " AND d.full_date BETWEEN {}::date AND {}::date",
+        builder.bind(Bind::Text(start.to_string())),
+        builder.bind(Bind::Text(end.to_string()))
+    ));
+    builder.sql = format!(
+        "SELECT \
+            COALESCE(({key})::text, 'Inconnu') AS key, \
+            COUNT(*)::bigint AS trip_count, \
+            COALESCE(SUM(f.fare_amount), 0)::float8 AS fare, \
+            COALESCE(SUM(f.tip_amount), 0)::float8 AS tip, \
+            COALESCE(SUM(f.total_amount), 0)::float8 AS total \
+        {from_where} \
+        GROUP BY 1"
+    );
+    builder.into_query().load::<GroupTotals>(conn)
+}
+
+fn period(groups: &[GroupTotals], start_date: NaiveDate, end_date: NaiveDate) -> RevenuePeriod {
+    let trip_count: i64 = groups.iter().map(|g| g.trip_count).sum();
+    let [fare, tip, revenue] =
+        groups.iter().fold([0.0; 3], |[fare, tip, total], g| [fare + g.fare, tip + g.tip, total + g.total]);
+    let per_trip = |value: f64| (trip_count > 0).then(|| value / trip_count as f64);
+    RevenuePeriod {
+        start_date,
+        end_date,
+        trip_count,
+        revenue,
+        revenue_per_trip: per_trip(revenue),
+        fare_per_trip: per_trip(fare),
+        tip_per_trip: per_trip(tip),
+        surcharges_tolls_per_trip: per_trip(revenue - fare - tip),
+    }
+}
+
+/// Décompose R1 - R0 en effets volume, mix, tarif, pourboire et surcharges /
+/// péages, avec la contribution de chaque groupe de `mix`.
+pub fn revenue_decomposition(
+    conn: &mut PgConnection,
+    filters: &TripFilters,
+    mix: RevenueMixDimension,
+    base_range: (NaiveDate, NaiveDate),
+    compare_range: (NaiveDate, NaiveDate),
+) -> QueryResult<RevenueDecomposition> {
+    let base_groups = group_totals(conn, filters, mix, base_range.0, base_range.1)?;
+    let compare_groups = group_totals(conn, filters, mix, compare_range.0, compare_range.1)?;
+    let base = period(&base_groups, base_range.0, base_range.1);
+    let compare = period(&compare_groups, compare_range.0, compare_range.1);
+
+    let mut by_key: BTreeMap<&str, [Option<&GroupTotals>; 2]> = BTreeMap::new();
+    for g in &base_groups {
+        by_key.entry(&g.key).or_default()[0] = Some(g);
+    }
+    for g in &compare_groups {
+        by_key.entry(&g.key).or_default()[1] = Some(g);
+    }
+
+    let (n0, n1) = (base.trip_count as f64, compare.trip_count as f64);
+    // Sans trajet dans l'une des périodes, toute la variation est un effet volume
+    let both = n0 > 0.0 && n1 > 0.0;
+    let mean_n = (n0 + n1) / 2.0;
+    let mut groups: Vec<RevenueMixContribution> = by_key
+        .into_iter()
+        .map(|(key, [g0, g1])| {
+            let trips = |g: Option<&GroupTotals>| g.map_or(0, |g| g.trip_count);
+            let (t0, t1) = (trips(g0), trips(g1));
+            let share = |t: i64, n: f64| if n > 0.0 { t as f64 / n } else { 0.0 };
+            let (s0, s1) = (share(t0, n0), share(t1, n1));
+            let (p0, p1) = (g0.and_then(GroupTotals::per_trip), g1.and_then(GroupTotals::per_trip));
+            // Un groupe absent d'une période y reprend les montants de l'autre :
+            // sa variation relève alors du seul effet mix
+            let (c0, c1) = (p0.or(p1).unwrap_or_default(), p1.or(p0).unwrap_or_default());
+            let revenue_per_trip = |c: [f64; 3]| c.iter().sum::<f64>();
+            let (mut mix_effect, mut effects) = (0.0, [0.0; 3]);
+            if both {
+                mix_effect = mean_n * (s1 - s0) * (revenue_per_trip(c0) + revenue_per_trip(c1)) / 2.0;
+                for (effect, (a, b)) in effects.iter_mut().zip(c0.iter().zip(c1.iter())) {
+                    *effect = mean_n * (s0 + s1) / 2.0 * (b - a);
+                }
+            }
+            RevenueMixContribution {
+                key: key.to_string(),
+                base_trips: t0,
+                compare_trips: t1,
+                base_share: s0,
+                compare_share: s1,
+                base_revenue_per_trip: p0.map(revenue_per_trip),
+                compare_revenue_per_trip: p1.map(revenue_per_trip),
+                mix_effect,
+                fare_effect: effects[0],
+                tip_effect: effects[1],
+                surcharges_tolls_effect: effects[2],
+            }
+        })
+        .collect();
+    groups.sort_by(|a, b| {
+        (b.base_trips + b.compare_trips)
+            .cmp(&(a.base_trips + a.compare_trips))
+            .then_with(|| a.key.cmp(&b.key))
+    });
+
+    let change = compare.revenue - base.revenue;
+    let sum = |effect: fn(&RevenueMixContribution) -> f64| groups.iter().fold(0.0, |acc, g| acc + effect(g));
+    let effects = [
+        (RevenueComponent::Mix, sum(|g| g.mix_effect)),
+        (RevenueComponent::Fare, sum(|g| g.fare_effect)),
+        (RevenueComponent::Tip, sum(|g| g.tip_effect)),
+        (RevenueComponent::SurchargesTolls, sum(|g| g.surcharges_tolls_effect)),
+    ];
+    // L'effet volume est le reste, égal à ΔN x (R0 / N0 + R1 / N1) / 2 aux
+    // arrondis près
+    let volume = change - effects.iter().map(|(_, v)| v).sum::<f64>();
+
+    let mut steps = vec![WaterfallStep {
+        component: RevenueComponent::Base,
+        value: base.revenue,
+        start: 0.0,
+        end: base.revenue,
+    }];
+    let mut cumulative = base.revenue;
+    for (component, value) in std::iter::once((RevenueComponent::Volume, volume)).chain(effects) {
+        steps.push(WaterfallStep { component, value, start: cumulative, end: cumulative + value });
+        cumulative += value;
+    }
+    steps.push(WaterfallStep {
+        component: RevenueComponent::Compare,
+        value: compare.revenue,
+        start: 0.0,
+        end: compare.revenue,
+    });
+
+    Ok(RevenueDecomposition { mix, base, compare, change, steps, groups })
+}
+
+fn required_range(
+    start: Option<NaiveDate>,
+    end: Option<NaiveDate>,
+    name: &str,
+) -> Result<(NaiveDate, NaiveDate), AppError> {
+    match (start, end) {
+        (Some(start), Some(end)) if start <= end => Ok((start, end)),
+        (Some(_), Some(_)) => Err(AppError::bad_request(format!("{name}_start est postérieure à {name}_end"))),
+        _ => Err(AppError::bad_request(format!("{name}_start et {name}_end requis"))),
+    }
+}
+
+// Handler pour la décomposition du revenu entre deux périodes
+#[utoipa::path(
+    get,
+    path = "/api/v1/revenue/decomposition",
+    tag = "analytics",
+    params(TripFilters, RevenueDecompositionParams),
+    responses(
+        (status = 200, description = "Effets volume, mix, tarif, pourboire et surcharges sur la variation du revenu", body = RevenueDecomposition),
+        (status = 400, description = "Paramètres invalides", body = String),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
+pub async fn get_revenue_decomposition(
+    State(pool): State<DbPool>,
+    Query(filters): Query<TripFilters>,
+    Query(params): Query<RevenueDecompositionParams>,
+) -> Result<Json<RevenueDecomposition>, AppError> {
+    filters::validate(&filters)?;
+    let base_range = required_range(params.base_start, params.base_end, "base")?;
+    let compare_range = required_range(params.compare_start, params.compare_end, "compare")?;
+    let mix = params.mix.unwrap_or_default();
+    let mut conn = pool.get().map_err(|e| AppError(anyhow::Error::new(e)))?;
+    let decomposition = tokio::task::spawn_blocking(move || {
+        revenue_decomposition(&mut conn, &filters, mix, base_range, compare_range)
+    })
+    .await??;
+    Ok(Json(decomposition))
+}
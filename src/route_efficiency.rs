@@ -0,0 +1,142 @@
+// Efficacité des itinéraires par paire de zones : distance déclarée
+// (`trip_distance`) rapportée à la distance à vol d'oiseau entre centroïdes
+// (`dim_location_geo`, alimentée par `enrich_locations`).
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use diesel::sql_types::Bool;
+
+use crate::cube::{Bind, Join, SqlBuilder, MAX_ROWS};
+use crate::db::DbPool;
+use crate::filters;
+use crate::handlers::AppError;
+use crate::models::{RouteEfficiency, RouteEfficiencyParams, RouteEfficiencySort, SortDirection, TripFilters};
+
+/// Rayon terrestre moyen, en miles (unité de `trip_distance`).
+pub const EARTH_RADIUS_MILES: f64 = 3958.8;
+
+const DEFAULT_MIN_TRIPS: i64 = 10;
+const DEFAULT_LIMIT: i64 = 100;
+
+// Distance orthodromique (haversine) entre les centroïdes g1 et g2
+fn straight_line_sql() -> String {
+    format!(
+        "{} * 2 * ASIN(SQRT( \
+            POWER(SIN(RADIANS(g2.centroid_lat - g1.centroid_lat) / 2), 2) \
+            + COS(RADIANS(g1.centroid_lat)) * COS(RADIANS(g2.centroid_lat)) \
+            * POWER(SIN(RADIANS(g2.centroid_lon - g1.centroid_lon) / 2), 2)))",
+        EARTH_RADIUS_MILES
+    )
+}
+
+#[derive(QueryableByName)]
+struct Enriched {
+    #[diesel(sql_type = Bool)]
+    enriched: bool,
+}
+
+/// Vrai si `dim_location_geo` a été alimentée.
+pub fn is_enriched(conn: &mut PgConnection) -> QueryResult<bool> {
+    diesel::sql_query("SELECT EXISTS (SELECT 1 FROM dim_location_geo) AS enriched")
+        .get_result::<Enriched>(conn)
+        .map(|r| r.enriched)
+}
+
+/// Paires de zones distinctes avec leur rapport de détour.
+pub fn route_efficiency(
+    conn: &mut PgConnection,
+    filters: &TripFilters,
+    min_trips: i64,
+    sort_by: RouteEfficiencySort,
+    direction: SortDirection,
+    limit: i64,
+) -> QueryResult<Vec<RouteEfficiency>> {
+    let mut builder = SqlBuilder::default();
+    let from_where = filters::from_where(filters, &mut builder, &[Join::PickupLocation, Join::DropoffLocation]);
+    let min_trips_marker = builder.bind(Bind::Text(min_trips.to_string()));
+    let order = match sort_by {
+        RouteEfficiencySort::TripCount => "trip_count",
+        RouteEfficiencySort::DetourRatio => "detour_ratio",
+        RouteEfficiencySort::StraightLineMiles => "straight_line_miles",
+    };
+    let direction = match direction {
+        SortDirection::Asc => "ASC",
+        SortDirection::Desc => "DESC",
+    };
+    builder.sql = format!(
+        "WITH trips AS ( \
+            SELECT pul.location_id AS pickup_id, dol.location_id AS dropoff_id, \
+                pul.zone AS pickup_zone, dol.zone AS dropoff_zone, f.trip_distance \
+            {from_where} \
+                AND pul.location_id <> dol.location_id \
+                AND f.trip_distance > 0 \
+        ), pairs AS ( \
+            SELECT t.*, {straight} AS straight \
+            FROM trips t \
+            JOIN dim_location_geo g1 ON g1.location_id = t.pickup_id \
+            JOIN dim_location_geo g2 ON g2.location_id = t.dropoff_id \
+        ) \
+        SELECT \
+            pickup_id AS pickup_location_id, \
+            MIN(pickup_zone)::text AS pickup_zone, \
+            dropoff_id AS dropoff_location_id, \
+            MIN(dropoff_zone)::text AS dropoff_zone, \
+            COUNT(*)::bigint AS trip_count, \
+            MIN(straight)::float8 AS straight_line_miles, \
+            AVG(trip_distance)::float8 AS avg_trip_distance, \
+            (AVG(trip_distance) / MIN(straight))::float8 AS detour_ratio, \
+            percentile_cont(0.5) WITHIN GROUP (ORDER BY trip_distance / straight)::float8 AS median_detour_ratio, \
+            AVG((trip_distance < straight)::int)::float8 AS share_below_straight_line \
+        FROM pairs \
+        WHERE straight > 0 \
+        GROUP BY pickup_id, dropoff_id \
+        HAVING COUNT(*) >= {min_trips_marker}::bigint \
+        ORDER BY {order} {direction}, pickup_location_id, dropoff_location_id \
+        LIMIT {limit}",
+        straight = straight_line_sql(),
+    );
+    builder.into_query().load::<RouteEfficiency>(conn)
+}
+
+// Handler pour l'efficacité des itinéraires par paire origine-destination
+#[utoipa::path(
+    get,
+    path = "/api/v1/route_efficiency",
+    tag = "analytics",
+    params(TripFilters, RouteEfficiencyParams),
+    responses(
+        (status = 200, description = "Rapport de détour par paire de zones", body = Vec<RouteEfficiency>),
+        (status = 400, description = "Paramètres invalides", body = String),
+        (status = 503, description = "dim_location_geo non alimentée (cargo run --bin enrich_locations)", body = String),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
+pub async fn get_route_efficiency(
+    State(pool): State<DbPool>,
+    Query(filters): Query<TripFilters>,
+    Query(params): Query<RouteEfficiencyParams>,
+) -> Result<Json<Vec<RouteEfficiency>>, AppError> {
+    filters::validate(&filters)?;
+    let min_trips = params.min_trips.unwrap_or(DEFAULT_MIN_TRIPS);
+    let limit = params.limit.unwrap_or(DEFAULT_LIMIT);
+    if min_trips < 1 || !(1..=MAX_ROWS).contains(&limit) {
+        return Err(AppError::bad_request(format!("min_trips >= 1 et limit entre 1 et {} requis", MAX_ROWS)));
+    }
+    let sort_by = params.sort_by.unwrap_or_default();
+    let direction = params.direction.unwrap_or(SortDirection::Desc);
+
+    let mut conn = pool.get().map_err(|e| AppError(anyhow::Error::new(e)))?;
+    let results = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        if !is_enriched(&mut conn)? {
+            return Err(AppError::unavailable(
+                "dim_location_geo est vide : exécuter `cargo run --bin enrich_locations`",
+            ));
+        }
+        Ok(route_efficiency(&mut conn, &filters, min_trips, sort_by, direction, limit)?)
+    })
+    .await??;
+    Ok(Json(results))
+}
@@ -0,0 +1,264 @@
+// Construction des routes de l'API avec gestion de leur cycle de vie.
+//
+// Une route ne disparaît jamais silencieusement : elle est d'abord dépréciée
+// avec un préavis minimal (en-têtes `Deprecation` / `Sunset`, journalisation
+// des appelants), puis répond automatiquement 410 Gone après sa date de sunset.
+use std::sync::Arc;
+
+use axum::{
+    extract::{ConnectInfo, OriginalUri, Request, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{any, get, MethodRouter},
+    Json, Router,
+};
+use chrono::{NaiveDate, Utc};
+use std::net::SocketAddr;
+
+use crate::db::DbPool;
+use crate::models::{RouteNotice, RouteStatus};
+
+/// Préavis minimal, en jours, entre l'annonce d'une dépréciation et son sunset.
+pub const MIN_SUNSET_NOTICE_DAYS: i64 = 90;
+
+/// Dépréciation d'une route encore servie.
+#[derive(Debug, Clone)]
+pub struct Deprecation {
+    pub since: NaiveDate,
+    pub sunset: NaiveDate,
+    pub replacement: Option<String>,
+    pub reason: String,
+}
+
+impl Deprecation {
+    pub fn new(since: NaiveDate, sunset: NaiveDate, reason: impl Into<String>) -> Self {
+        Deprecation { since, sunset, replacement: None, reason: reason.into() }
+    }
+
+    /// Chemin (relatif à la racine de l'API) de la route qui remplace celle-ci.
+    pub fn replaced_by(mut self, path: impl Into<String>) -> Self {
+        self.replacement = Some(path.into());
+        self
+    }
+}
+
+// Route annoncée : état partagé par le middleware et la liste /deprecations
+#[derive(Debug)]
+struct Lifecycle {
+    path: String,
+    deprecation: Deprecation,
+    // Retirée avant la mise en place de la politique : plus aucun handler
+    removed: bool,
+}
+
+impl Lifecycle {
+    fn status(&self, today: NaiveDate) -> RouteStatus {
+        if self.removed || today >= self.deprecation.sunset {
+            RouteStatus::Removed
+        } else {
+            RouteStatus::Deprecated
+        }
+    }
+
+    fn notice(&self, prefix: &str, today: NaiveDate) -> RouteNotice {
+        RouteNotice {
+            path: format!("{}{}", prefix, self.path),
+            status: self.status(today),
+            deprecated_since: self.deprecation.since,
+            sunset: self.deprecation.sunset,
+            replacement: self.deprecation.replacement.as_ref().map(|r| format!("{}{}", prefix, r)),
+            reason: self.deprecation.reason.clone(),
+        }
+    }
+}
+
+struct RouteEntry {
+    path: String,
+    method_router: MethodRouter<DbPool>,
+    lifecycle: Option<Arc<Lifecycle>>,
+}
+
+/// Constructeur de routeur qui impose la politique de dépréciation.
+///
+/// Les chemins sont relatifs à la racine de l'API ; le préfixe réel (`/api`
+/// ou celui choisi par un service qui embarque les routes) est retrouvé à
+/// partir de l'URI de la requête pour les en-têtes `Link` et les annonces.
+#[derive(Default)]
+pub struct ApiRouter {
+    entries: Vec<RouteEntry>,
+}
+
+impl ApiRouter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Route active.
+    pub fn route(mut self, path: &str, method_router: MethodRouter<DbPool>) -> Self {
+        self.entries.push(RouteEntry { path: path.to_string(), method_router, lifecycle: None });
+        self
+    }
+
+    /// Route dépréciée : servie normalement jusqu'au sunset, puis 410 Gone.
+    ///
+    /// Panique si le préavis est inférieur à [`MIN_SUNSET_NOTICE_DAYS`].
+    pub fn deprecated(mut self, path: &str, method_router: MethodRouter<DbPool>, deprecation: Deprecation) -> Self {
+        let notice_days = (deprecation.sunset - deprecation.since).num_days();
+        assert!(
+            notice_days >= MIN_SUNSET_NOTICE_DAYS,
+            "route {path}: préavis de dépréciation de {notice_days} jours, minimum {MIN_SUNSET_NOTICE_DAYS}"
+        );
+        let lifecycle = Arc::new(Lifecycle { path: path.to_string(), deprecation, removed: false });
+        self.entries.push(RouteEntry { path: path.to_string(), method_router, lifecycle: Some(lifecycle) });
+        self
+    }
+
+    /// Ancien chemin servant le même handler qu'une route existante, déprécié
+    /// au profit de celle-ci.
+    pub fn deprecated_alias(self, path: &str, target: &str, deprecation: Deprecation) -> Self {
+        let method_router = self
+            .entries
+            .iter()
+            .find(|e| e.path == target)
+            .unwrap_or_else(|| panic!("alias {path}: route cible {target} inconnue"))
+            .method_router
+            .clone();
+        self.deprecated(path, method_router, deprecation.replaced_by(target))
+    }
+
+    /// Route déjà retirée : répond 410 Gone avec son annonce, pour que les
+    /// anciens clients obtiennent une explication plutôt qu'un 404.
+    pub fn removed(mut self, path: &str, deprecation: Deprecation) -> Self {
+        let lifecycle = Arc::new(Lifecycle { path: path.to_string(), deprecation, removed: true });
+        let method_router = any(gone_handler).with_state(lifecycle.clone());
+        self.entries.push(RouteEntry { path: path.to_string(), method_router, lifecycle: Some(lifecycle) });
+        self
+    }
+
+    /// Monte un autre `ApiRouter` sous `prefix` (ex. `/v1`).
+    pub fn nest(mut self, prefix: &str, other: ApiRouter) -> Self {
+        for entry in other.entries {
+            let path = format!("{}{}", prefix, entry.path);
+            let lifecycle = entry.lifecycle.map(|l| {
+                let mut deprecation = l.deprecation.clone();
+                deprecation.replacement = deprecation.replacement.map(|r| format!("{}{}", prefix, r));
+                Arc::new(Lifecycle { path: path.clone(), deprecation, removed: l.removed })
+            });
+            self.entries.push(RouteEntry { path, method_router: entry.method_router, lifecycle });
+        }
+        self
+    }
+
+    /// Construit le routeur Axum, avec la liste des annonces sur `/deprecations`.
+    ///
+    /// Panique si une route de remplacement n'est pas servie par ce routeur.
+    pub fn build(self) -> Router<DbPool> {
+        let lifecycles: Vec<Arc<Lifecycle>> = self.entries.iter().filter_map(|e| e.lifecycle.clone()).collect();
+        for lifecycle in &lifecycles {
+            if let Some(replacement) = &lifecycle.deprecation.replacement {
+                let served = self.entries.iter().any(|e| &e.path == replacement && e.lifecycle.is_none());
+                assert!(served, "route {}: remplacement {} absent ou lui-même déprécié", lifecycle.path, replacement);
+            }
+        }
+
+        let mut router = Router::new();
+        for entry in self.entries {
+            let method_router = match &entry.lifecycle {
+                Some(lifecycle) if !lifecycle.removed => entry
+                    .method_router
+                    .layer(middleware::from_fn_with_state(lifecycle.clone(), deprecation_layer)),
+                _ => entry.method_router,
+            };
+            router = router.route(&entry.path, method_router);
+        }
+
+        router.route(
+            "/deprecations",
+            get(list_deprecations).with_state(Arc::new(lifecycles)),
+        )
+    }
+}
+
+// Préfixe sous lequel l'API est montée (`/api`, `/analytics`, ...)
+fn mount_prefix(original: &OriginalUri, req_path: &str) -> String {
+    original.path().strip_suffix(req_path).unwrap_or("").to_string()
+}
+
+fn log_caller(lifecycle: &Lifecycle, headers: &HeaderMap, peer: Option<SocketAddr>) {
+    let header_str = |name: &str| headers.get(name).and_then(|v| v.to_str().ok()).unwrap_or("-").to_string();
+    tracing::warn!(
+        route = %lifecycle.path,
+        sunset = %lifecycle.deprecation.sunset,
+        peer = %peer.map(|p| p.to_string()).unwrap_or_else(|| "-".into()),
+        forwarded_for = %header_str("x-forwarded-for"),
+        user_agent = %header_str("user-agent"),
+        "Appel d'une route dépréciée"
+    );
+}
+
+// En-têtes RFC 9745 (Deprecation), RFC 8594 (Sunset) et lien vers le remplacement
+fn set_lifecycle_headers(headers: &mut HeaderMap, lifecycle: &Lifecycle, prefix: &str) {
+    let deprecation = &lifecycle.deprecation;
+    let since = deprecation.since.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp();
+    let sunset = deprecation.sunset.format("%a, %d %b %Y 00:00:00 GMT").to_string();
+    if let Ok(value) = HeaderValue::from_str(&format!("@{}", since)) {
+        headers.insert("deprecation", value);
+    }
+    if let Ok(value) = HeaderValue::from_str(&sunset) {
+        headers.insert("sunset", value);
+    }
+    if let Some(replacement) = &deprecation.replacement {
+        if let Ok(value) = HeaderValue::from_str(&format!("<{}{}>; rel=\"successor-version\"", prefix, replacement)) {
+            headers.insert(header::LINK, value);
+        }
+    }
+}
+
+fn gone_response(lifecycle: &Lifecycle, prefix: &str) -> Response {
+    let notice = lifecycle.notice(prefix, Utc::now().date_naive());
+    let mut response = (StatusCode::GONE, Json(notice)).into_response();
+    set_lifecycle_headers(response.headers_mut(), lifecycle, prefix);
+    response
+}
+
+async fn deprecation_layer(
+    State(lifecycle): State<Arc<Lifecycle>>,
+    original: OriginalUri,
+    req: Request,
+    next: Next,
+) -> Response {
+    let prefix = mount_prefix(&original, req.uri().path());
+    let peer = req.extensions().get::<ConnectInfo<SocketAddr>>().map(|c| c.0);
+    log_caller(&lifecycle, req.headers(), peer);
+
+    if lifecycle.status(Utc::now().date_naive()) == RouteStatus::Removed {
+        return gone_response(&lifecycle, &prefix);
+    }
+
+    let mut response = next.run(req).await;
+    set_lifecycle_headers(response.headers_mut(), &lifecycle, &prefix);
+    response
+}
+
+async fn gone_handler(
+    State(lifecycle): State<Arc<Lifecycle>>,
+    original: OriginalUri,
+    req: Request,
+) -> Response {
+    let prefix = mount_prefix(&original, req.uri().path());
+    let peer = req.extensions().get::<ConnectInfo<SocketAddr>>().map(|c| c.0);
+    log_caller(&lifecycle, req.headers(), peer);
+    gone_response(&lifecycle, &prefix)
+}
+
+// Liste publique des routes dépréciées ou retirées
+async fn list_deprecations(
+    State(lifecycles): State<Arc<Vec<Arc<Lifecycle>>>>,
+    original: OriginalUri,
+    req: Request,
+) -> Json<Vec<RouteNotice>> {
+    let prefix = mount_prefix(&original, req.uri().path());
+    let today = Utc::now().date_naive();
+    Json(lifecycles.iter().map(|l| l.notice(&prefix, today)).collect())
+}
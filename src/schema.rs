@@ -14,6 +14,13 @@ diesel::table! {
         month_name -> Varchar,
         quarter -> Int4,
         is_weekend -> Bool,
+        is_holiday -> Bool,
+        #[max_length = 64]
+        holiday_name -> Nullable<Varchar>,
+        is_school_day -> Bool,
+        is_dst_transition -> Bool,
+        #[max_length = 8]
+        day_type -> Varchar,
     }
 }
 
@@ -30,6 +37,17 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    dim_location_geo (location_id) {
+        location_id -> Int4,
+        centroid_lon -> Float8,
+        centroid_lat -> Float8,
+        area_km2 -> Float8,
+        neighbours -> Array<Nullable<Int4>>,
+        updated_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     dim_payment_type (payment_type_key) {
         payment_type_key -> Int4,
@@ -57,6 +75,23 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    dim_weather (weather_date, hour) {
+        weather_date -> Date,
+        hour -> Int4,
+        #[max_length = 32]
+        station -> Varchar,
+        temperature_c -> Nullable<Float8>,
+        precipitation_mm -> Nullable<Float8>,
+        snowfall_mm -> Nullable<Float8>,
+        wind_speed_ms -> Nullable<Float8>,
+        #[max_length = 16]
+        condition -> Varchar,
+        #[max_length = 8]
+        source -> Varchar,
+    }
+}
+
 diesel::table! {
     fact_trips (trip_id) {
         trip_id -> Int8,
@@ -92,8 +127,10 @@ diesel::joinable!(fact_trips -> dim_vendor (vendor_key));
 diesel::allow_tables_to_appear_in_same_query!(
     dim_date,
     dim_location,
+    dim_location_geo,
     dim_payment_type,
     dim_rate_code,
     dim_vendor,
+    dim_weather,
     fact_trips,
 );
@@ -0,0 +1,205 @@
+// Vitesse des trajets (mph) par heure de la semaine, zone de départ ou paire
+// de boroughs, et indice de congestion.
+//
+// L'indice de congestion d'un groupe est le rapport entre la durée réelle de
+// ses trajets et la durée qu'ils auraient prise à la vitesse fluide de leur
+// zone de départ, mesurée la nuit (0 h - 5 h) sur les mêmes filtres hors
+// plage horaire. Les zones avec trop peu de trajets de nuit utilisent la
+// vitesse fluide de l'ensemble des zones.
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+
+use crate::cube::{Join, SqlBuilder};
+use crate::db::DbPool;
+use crate::filters;
+use crate::handlers::AppError;
+use crate::models::{SpeedDimension, SpeedHeatmapCell, SpeedParams, SpeedStats, TripFilters};
+
+/// Heure de fin (exclue) de la période de référence nocturne.
+pub const NIGHT_END_HOUR: i32 = 5;
+/// Trajets de nuit requis pour retenir la vitesse fluide propre à une zone.
+pub const MIN_FREE_FLOW_TRIPS: i64 = 20;
+const DEFAULT_MIN_TRIPS: i64 = 10;
+
+// Trajets exploitables : distance positive, au moins une minute, vitesse
+// plausible (80 mph au plus)
+const VALID_TRIP_SQL: &str = "f.trip_distance > 0 \
+    AND EXTRACT(EPOCH FROM f.trip_duration) >= 60 \
+    AND f.trip_distance / (EXTRACT(EPOCH FROM f.trip_duration) / 3600.0) <= 80";
+
+// CTE `trips` (miles, heures, zone de départ et colonnes `columns`),
+// `free_flow` (vitesse de nuit par zone) et `city` (vitesse de nuit globale)
+fn speed_ctes(filters: &TripFilters, builder: &mut SqlBuilder, joins: &[Join], columns: &str) -> String {
+    let from_where = filters::from_where(filters, builder, joins);
+    let reference_filters = TripFilters { hour_from: None, hour_to: None, ..filters.clone() };
+    let reference_from_where = filters::from_where(&reference_filters, builder, &[]);
+    format!(
+        "WITH trips AS ( \
+            SELECT \
+                f.pickup_location_key AS zone_key, \
+                f.trip_distance AS miles, \
+                EXTRACT(EPOCH FROM f.trip_duration) / 3600.0 AS hours, \
+                {columns} \
+            {from_where} AND {VALID_TRIP_SQL} \
+        ), \
+        free_flow AS ( \
+            SELECT \
+                f.pickup_location_key AS zone_key, \
+                COUNT(*) AS trip_count, \
+                SUM(f.trip_distance) AS miles, \
+                SUM(EXTRACT(EPOCH FROM f.trip_duration) / 3600.0) AS hours \
+            {reference_from_where} AND {VALID_TRIP_SQL} \
+                AND EXTRACT(HOUR FROM f.tpep_pickup_datetime) < {NIGHT_END_HOUR} \
+            GROUP BY 1 \
+        ), \
+        city AS (SELECT SUM(miles) / NULLIF(SUM(hours), 0) AS speed FROM free_flow) "
+    )
+}
+
+// Durée réelle / durée à vitesse fluide, sur `trips t` joint à `free_flow ff` et `city`
+fn congestion_index_sql() -> String {
+    format!(
+        "SUM(t.hours) / NULLIF(SUM(t.miles / COALESCE( \
+            CASE WHEN ff.trip_count >= {MIN_FREE_FLOW_TRIPS} THEN ff.miles / NULLIF(ff.hours, 0) END, \
+            city.speed)), 0)"
+    )
+}
+
+/// Vitesses (distance totale / durée totale, p10 / p50 / p90) et indice de
+/// congestion par groupe de `dimension`, pour les groupes d'au moins
+/// `min_trips` trajets.
+pub fn speed_stats(
+    conn: &mut PgConnection,
+    filters: &TripFilters,
+    dimension: SpeedDimension,
+    min_trips: i64,
+) -> QueryResult<Vec<SpeedStats>> {
+    let (joins, key, label, order): (&[Join], &str, &str, &str) = match dimension {
+        SpeedDimension::HourOfWeek => (
+            &[],
+            "COALESCE(EXTRACT(ISODOW FROM f.tpep_pickup_datetime)::int::text || '-' \
+                || LPAD(EXTRACT(HOUR FROM f.tpep_pickup_datetime)::int::text, 2, '0'), 'Inconnu')",
+            "NULL::text",
+            "key",
+        ),
+        SpeedDimension::PickupZone => (
+            &[Join::PickupLocation],
+            "COALESCE(pul.location_id::text, 'Inconnu')",
+            "pul.zone || ', ' || pul.borough",
+            "trip_count DESC, key",
+        ),
+        SpeedDimension::OdBoroughPair => (
+            &[Join::PickupLocation, Join::DropoffLocation],
+            "COALESCE(pul.borough, 'Inconnu') || ' -> ' || COALESCE(dol.borough, 'Inconnu')",
+            "NULL::text",
+            "trip_count DESC, key",
+        ),
+    };
+    let mut builder = SqlBuilder::default();
+    let ctes = speed_ctes(filters, &mut builder, joins, &format!("{key} AS key, {label} AS label"));
+    builder.sql = format!(
+        "{ctes} \
+        SELECT \
+            t.key, \
+            MIN(t.label) AS label, \
+            COUNT(*)::bigint AS trip_count, \
+            (SUM(t.miles) / SUM(t.hours))::float8 AS avg_speed_mph, \
+            PERCENTILE_CONT(0.10) WITHIN GROUP (ORDER BY t.miles / t.hours)::float8 AS p10_speed_mph, \
+            PERCENTILE_CONT(0.50) WITHIN GROUP (ORDER BY t.miles / t.hours)::float8 AS p50_speed_mph, \
+            PERCENTILE_CONT(0.90) WITHIN GROUP (ORDER BY t.miles / t.hours)::float8 AS p90_speed_mph, \
+            ({index})::float8 AS congestion_index \
+        FROM trips t \
+        LEFT JOIN free_flow ff ON ff.zone_key = t.zone_key \
+        CROSS JOIN city \
+        GROUP BY t.key \
+        HAVING COUNT(*) >= {min_trips} \
+        ORDER BY {order}",
+        index = congestion_index_sql()
+    );
+    builder.into_query().load::<SpeedStats>(conn)
+}
+
+/// Vitesse moyenne et indice de congestion par jour de la semaine et heure.
+pub fn speed_heatmap(conn: &mut PgConnection, filters: &TripFilters) -> QueryResult<Vec<SpeedHeatmapCell>> {
+    let mut builder = SqlBuilder::default();
+    let ctes = speed_ctes(
+        filters,
+        &mut builder,
+        &[],
+        "EXTRACT(ISODOW FROM f.tpep_pickup_datetime)::int AS day_of_week, \
+            EXTRACT(HOUR FROM f.tpep_pickup_datetime)::int AS hour_of_day",
+    );
+    // Trajets sans heure de prise en charge exclus : la cellule serait NULL
+    builder.sql = format!(
+        "{ctes} \
+        SELECT \
+            t.day_of_week, \
+            t.hour_of_day, \
+            COUNT(*)::bigint AS trip_count, \
+            (SUM(t.miles) / SUM(t.hours))::float8 AS avg_speed_mph, \
+            ({index})::float8 AS congestion_index \
+        FROM trips t \
+        LEFT JOIN free_flow ff ON ff.zone_key = t.zone_key \
+        CROSS JOIN city \
+        WHERE t.day_of_week IS NOT NULL AND t.hour_of_day IS NOT NULL \
+        GROUP BY t.day_of_week, t.hour_of_day \
+        ORDER BY t.day_of_week, t.hour_of_day",
+        index = congestion_index_sql()
+    );
+    builder.into_query().load::<SpeedHeatmapCell>(conn)
+}
+
+// Handler pour les vitesses par dimension
+#[utoipa::path(
+    get,
+    path = "/api/v1/speed",
+    tag = "analytics",
+    params(TripFilters, SpeedParams),
+    responses(
+        (status = 200, description = "Vitesses et indice de congestion par heure de la semaine, zone de départ ou paire de boroughs", body = Vec<SpeedStats>),
+        (status = 400, description = "Paramètres invalides", body = String),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
+pub async fn get_speed_stats(
+    State(pool): State<DbPool>,
+    Query(filters): Query<TripFilters>,
+    Query(params): Query<SpeedParams>,
+) -> Result<Json<Vec<SpeedStats>>, AppError> {
+    filters::validate(&filters)?;
+    let min_trips = params.min_trips.unwrap_or(DEFAULT_MIN_TRIPS);
+    if min_trips < 1 {
+        return Err(AppError::bad_request("min_trips doit être au moins 1"));
+    }
+    let dimension = params.by.unwrap_or_default();
+    let mut conn = pool.get().map_err(|e| AppError(anyhow::Error::new(e)))?;
+    let results =
+        tokio::task::spawn_blocking(move || speed_stats(&mut conn, &filters, dimension, min_trips)).await??;
+    Ok(Json(results))
+}
+
+// Handler pour la carte de chaleur des vitesses
+#[utoipa::path(
+    get,
+    path = "/api/v1/speed/heatmap",
+    tag = "analytics",
+    params(TripFilters),
+    responses(
+        (status = 200, description = "Vitesse moyenne et indice de congestion par jour de la semaine et heure", body = Vec<SpeedHeatmapCell>),
+        (status = 400, description = "Paramètres invalides", body = String),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
+pub async fn get_speed_heatmap(
+    State(pool): State<DbPool>,
+    Query(filters): Query<TripFilters>,
+) -> Result<Json<Vec<SpeedHeatmapCell>>, AppError> {
+    filters::validate(&filters)?;
+    let mut conn = pool.get().map_err(|e| AppError(anyhow::Error::new(e)))?;
+    let results = tokio::task::spawn_blocking(move || speed_heatmap(&mut conn, &filters)).await??;
+    Ok(Json(results))
+}
@@ -0,0 +1,249 @@
+// Tests statistiques calculés côté Rust : t de Welch et Mann-Whitney, avec
+// les fonctions de répartition nécessaires (loi normale, loi de Student).
+
+/// Résumé d'un échantillon : effectif, moyenne et variance (n - 1).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Moments {
+    pub count: f64,
+    pub mean: f64,
+    pub variance: f64,
+}
+
+// ln Γ(x) pour x > 0 (approximation de Lanczos, g = 7)
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        // Formule des compléments
+        return (std::f64::consts::PI / (std::f64::consts::PI * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let series = COEFFICIENTS[1..]
+        .iter()
+        .enumerate()
+        .fold(COEFFICIENTS[0], |sum, (i, c)| sum + c / (x + i as f64 + 1.0));
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + series.ln()
+}
+
+// Fraction continue de la fonction bêta incomplète (méthode de Lentz)
+fn beta_continued_fraction(a: f64, b: f64, x: f64) -> f64 {
+    const TINY: f64 = 1e-300;
+    let mut c = 1.0;
+    let mut d = 1.0 - (a + b) * x / (a + 1.0);
+    if d.abs() < TINY {
+        d = TINY;
+    }
+    d = 1.0 / d;
+    let mut result = d;
+    for m in 1..300 {
+        let m = m as f64;
+        for numerator in [
+            m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m)),
+            -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0)),
+        ] {
+            d = 1.0 + numerator * d;
+            if d.abs() < TINY {
+                d = TINY;
+            }
+            c = 1.0 + numerator / c;
+            if c.abs() < TINY {
+                c = TINY;
+            }
+            d = 1.0 / d;
+            result *= d * c;
+        }
+        if (d * c - 1.0).abs() < 1e-14 {
+            break;
+        }
+    }
+    result
+}
+
+/// Fonction bêta incomplète régularisée I_x(a, b).
+pub fn regularized_incomplete_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let front = (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+    // Convergence rapide de la fraction continue sous (a + 1) / (a + b + 2)
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_continued_fraction(a, b, x) / a
+    } else {
+        1.0 - front * beta_continued_fraction(b, a, 1.0 - x) / b
+    }
+}
+
+/// P(|T| >= |t|) pour une loi de Student à `df` degrés de liberté.
+pub fn student_t_two_sided_p(t: f64, df: f64) -> f64 {
+    regularized_incomplete_beta(df / 2.0, 0.5, df / (df + t * t))
+}
+
+// Fonction d'erreur complémentaire (Numerical Recipes, erreur < 1,2e-7)
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let poly = -z * z - 1.265_512_23
+        + t * (1.000_023_68
+            + t * (0.374_091_96
+                + t * (0.096_784_18
+                    + t * (-0.186_288_06
+                        + t * (0.278_868_07
+                            + t * (-1.135_203_98 + t * (1.488_515_87 + t * (-0.822_152_23 + t * 0.170_872_77))))))));
+    let value = t * poly.exp();
+    if x >= 0.0 {
+        value
+    } else {
+        2.0 - value
+    }
+}
+
+/// P(|Z| >= |z|) pour une loi normale centrée réduite.
+pub fn normal_two_sided_p(z: f64) -> f64 {
+    erfc(z.abs() / std::f64::consts::SQRT_2)
+}
+
+/// Test t de Welch de B contre A : (t, degrés de liberté, p bilatéral), ou
+/// `None` si un échantillon compte moins de deux valeurs ou si les deux
+/// variances sont nulles.
+pub fn welch_t_test(a: Moments, b: Moments) -> Option<(f64, f64, f64)> {
+    if a.count < 2.0 || b.count < 2.0 {
+        return None;
+    }
+    let (va, vb) = (a.variance / a.count, b.variance / b.count);
+    if va + vb <= 0.0 {
+        return None;
+    }
+    let t = (b.mean - a.mean) / (va + vb).sqrt();
+    let df = (va + vb).powi(2) / (va * va / (a.count - 1.0) + vb * vb / (b.count - 1.0));
+    Some((t, df, student_t_two_sided_p(t, df)))
+}
+
+/// Résultat du test de Mann-Whitney.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MannWhitney {
+    /// U de l'échantillon B
+    pub u: f64,
+    pub z: f64,
+    pub p_value: f64,
+    /// U / (n_a x n_b) : probabilité qu'une valeur de B dépasse une valeur de A
+    pub prob_b_greater: f64,
+}
+
+/// Test de Mann-Whitney de B contre A (approximation normale avec correction
+/// de continuité et des ex aequo), ou `None` si un échantillon est vide ou si
+/// toutes les valeurs sont égales.
+pub fn mann_whitney_u(a: &[f64], b: &[f64]) -> Option<MannWhitney> {
+    if a.is_empty() || b.is_empty() {
+        return None;
+    }
+    let (na, nb) = (a.len() as f64, b.len() as f64);
+    let mut values: Vec<(f64, bool)> = a.iter().map(|&v| (v, false)).chain(b.iter().map(|&v| (v, true))).collect();
+    values.sort_by(|x, y| x.0.total_cmp(&y.0));
+
+    // Rangs moyens des ex aequo
+    let (mut rank_sum_b, mut ties) = (0.0, 0.0);
+    let mut start = 0;
+    while start < values.len() {
+        let mut end = start;
+        while end + 1 < values.len() && values[end + 1].0 == values[start].0 {
+            end += 1;
+        }
+        let rank = (start + end) as f64 / 2.0 + 1.0;
+        let group = (end - start + 1) as f64;
+        ties += group * group * group - group;
+        rank_sum_b += rank * values[start..=end].iter().filter(|v| v.1).count() as f64;
+        start = end + 1;
+    }
+
+    let n = na + nb;
+    let u = rank_sum_b - nb * (nb + 1.0) / 2.0;
+    let mean = na * nb / 2.0;
+    let variance = na * nb / 12.0 * ((n + 1.0) - ties / (n * (n - 1.0)));
+    if variance <= 0.0 {
+        return None;
+    }
+    let deviation = u - mean;
+    let z = (deviation.abs() - 0.5).max(0.0).copysign(deviation) / variance.sqrt();
+    Some(MannWhitney { u, z, p_value: normal_two_sided_p(z), prob_b_greater: u / (na * nb) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(actual: f64, expected: f64, tolerance: f64) {
+        assert!((actual - expected).abs() < tolerance, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn distributions() {
+        close(student_t_two_sided_p(2.0, 10.0), 0.0734, 1e-4);
+        close(student_t_two_sided_p(-2.0, 10.0), 0.0734, 1e-4);
+        close(student_t_two_sided_p(0.0, 5.0), 1.0, 1e-9);
+        // Grand nombre de degrés de liberté : loi normale
+        close(student_t_two_sided_p(1.96, 1e6), 0.05, 1e-4);
+        close(normal_two_sided_p(1.96), 0.05, 1e-4);
+        close(normal_two_sided_p(0.0), 1.0, 1e-6);
+        close(normal_two_sided_p(-3.0), 0.0027, 1e-4);
+        close(regularized_incomplete_beta(2.0, 3.0, 0.0), 0.0, 1e-12);
+        close(regularized_incomplete_beta(2.0, 3.0, 1.0), 1.0, 1e-12);
+        // I_0,5(a, a) = 0,5
+        close(regularized_incomplete_beta(4.5, 4.5, 0.5), 0.5, 1e-9);
+    }
+
+    #[test]
+    fn welch() {
+        let a = Moments { count: 5.0, mean: 3.0, variance: 2.5 };
+        let b = Moments { count: 5.0, mean: 8.0, variance: 2.5 };
+        let (t, df, p) = welch_t_test(a, b).unwrap();
+        close(t, 5.0, 1e-12);
+        close(df, 8.0, 1e-12);
+        close(p, 0.001053, 1e-6);
+
+        let constant = Moments { count: 10.0, mean: 1.0, variance: 0.0 };
+        assert_eq!(welch_t_test(constant, constant), None);
+        assert_eq!(welch_t_test(Moments { count: 1.0, ..a }, b), None);
+    }
+
+    #[test]
+    fn mann_whitney_with_ties() {
+        let result = mann_whitney_u(&[1.0, 2.0, 2.0, 3.0], &[2.0, 3.0, 4.0, 5.0]).unwrap();
+        // Rangs de B : 3 + 5,5 + 7 + 8 = 23,5 ; U = 23,5 - 4 x 5 / 2
+        close(result.u, 13.5, 1e-12);
+        close(result.prob_b_greater, 13.5 / 16.0, 1e-12);
+        close(result.z, 1.488351, 1e-6);
+        close(result.p_value, 0.136658, 1e-5);
+    }
+
+    #[test]
+    fn mann_whitney_separated_samples() {
+        let a = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let b = [6.0, 7.0, 8.0, 9.0, 10.0];
+        let result = mann_whitney_u(&a, &b).unwrap();
+        close(result.u, 25.0, 1e-12);
+        close(result.p_value, 0.012186, 1e-5);
+        let reversed = mann_whitney_u(&b, &a).unwrap();
+        close(reversed.u, 0.0, 1e-12);
+        close(reversed.z, -result.z, 1e-12);
+    }
+
+    #[test]
+    fn mann_whitney_degenerate() {
+        assert_eq!(mann_whitney_u(&[], &[1.0, 2.0]), None);
+        assert_eq!(mann_whitney_u(&[1.0], &[]), None);
+        assert_eq!(mann_whitney_u(&[4.0, 4.0], &[4.0, 4.0, 4.0]), None);
+    }
+}
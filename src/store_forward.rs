@@ -0,0 +1,220 @@
+// Trajets « store and forward » : part des trajets transmis en différé
+// (`store_and_fwd_flag` = Y) par vendeur, zone de départ ou heure, et écarts
+// de durée, de tarif et de cohérence avec les trajets transmis directement,
+// pour juger de la fiabilité de la télémétrie des vendeurs selon les secteurs.
+//
+// L'écart de la part d'un groupe au taux global est exprimé en écarts-types
+// binomiaux (z), avec le p bilatéral de l'approximation normale.
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Float8, Nullable, Text};
+
+use crate::cube::{self, Join, SqlBuilder};
+use crate::db::DbPool;
+use crate::filters;
+use crate::handlers::AppError;
+use crate::models::{
+    CubeDimension, StoreForwardDimension, StoreForwardGroup, StoreForwardParams, StoreForwardReport, TelemetryProfile,
+    TripFilters,
+};
+use crate::stats;
+
+const DEFAULT_MIN_TRIPS: i64 = 30;
+
+const STORED_SQL: &str = "f.store_and_fwd_flag = 'Y'";
+const DIRECT_SQL: &str = "f.store_and_fwd_flag IS DISTINCT FROM 'Y'";
+const DURATION_SQL: &str = "EXTRACT(EPOCH FROM f.trip_duration)";
+
+// Trajets incohérents : horodatages absents ou inversés, distance nulle,
+// montant négatif, plus de 6 heures ou vitesse supérieure à 80 mph
+const INVALID_TRIP_SQL: &str = "COALESCE( \
+        f.tpep_pickup_datetime IS NULL \
+        OR f.tpep_dropoff_datetime IS NULL \
+        OR f.tpep_dropoff_datetime <= f.tpep_pickup_datetime \
+        OR COALESCE(f.trip_distance, 0) <= 0 \
+        OR f.fare_amount < 0 \
+        OR f.total_amount < 0 \
+        OR f.trip_duration > INTERVAL '6 hours' \
+        OR f.trip_distance / NULLIF(EXTRACT(EPOCH FROM f.trip_duration) / 3600.0, 0) > 80, \
+    FALSE)";
+
+#[derive(QueryableByName)]
+struct ProfileRow {
+    #[diesel(sql_type = Text)]
+    key: String,
+    #[diesel(sql_type = Nullable<Text>)]
+    label: Option<String>,
+    #[diesel(sql_type = BigInt)]
+    trip_count: i64,
+    #[diesel(sql_type = BigInt)]
+    stored_count: i64,
+    #[diesel(sql_type = Nullable<Float8>)]
+    stored_avg_duration: Option<f64>,
+    #[diesel(sql_type = Nullable<Float8>)]
+    stored_median_duration: Option<f64>,
+    #[diesel(sql_type = Nullable<Float8>)]
+    stored_avg_fare: Option<f64>,
+    #[diesel(sql_type = Nullable<Float8>)]
+    stored_invalid_share: Option<f64>,
+    #[diesel(sql_type = Nullable<Float8>)]
+    direct_avg_duration: Option<f64>,
+    #[diesel(sql_type = Nullable<Float8>)]
+    direct_median_duration: Option<f64>,
+    #[diesel(sql_type = Nullable<Float8>)]
+    direct_avg_fare: Option<f64>,
+    #[diesel(sql_type = Nullable<Float8>)]
+    direct_invalid_share: Option<f64>,
+}
+
+// Colonnes de profil des trajets vérifiant `condition`, préfixées par `prefix`
+fn profile_columns(prefix: &str, condition: &str) -> String {
+    format!(
+        "(AVG({DURATION_SQL}) FILTER (WHERE {condition}))::float8 AS {prefix}_avg_duration, \
+        (PERCENTILE_CONT(0.5) WITHIN GROUP (ORDER BY {DURATION_SQL}) FILTER (WHERE {condition}))::float8 AS {prefix}_median_duration, \
+        (AVG(f.fare_amount) FILTER (WHERE {condition}))::float8 AS {prefix}_avg_fare, \
+        (AVG(({INVALID_TRIP_SQL})::int) FILTER (WHERE {condition}))::float8 AS {prefix}_invalid_share"
+    )
+}
+
+// Profils par groupe de `dimension`, ou de l'ensemble des trajets (`None`)
+fn profile_rows(
+    conn: &mut PgConnection,
+    filters: &TripFilters,
+    dimension: Option<StoreForwardDimension>,
+    min_trips: i64,
+) -> QueryResult<Vec<ProfileRow>> {
+    let (joins, key, label, group_by): (Vec<Join>, String, &str, &str) = match dimension {
+        None => (vec![], "'all'".to_string(), "NULL::text", ""),
+        Some(StoreForwardDimension::Vendor) => {
+            let (sql, _, join) = cube::dimension_sql(CubeDimension::Vendor);
+            (join.into_iter().collect(), format!("COALESCE(({sql})::text, 'Inconnu')"), "NULL::text", "GROUP BY 1")
+        }
+        Some(StoreForwardDimension::PickupZone) => (
+            vec![Join::PickupLocation],
+            "COALESCE(pul.location_id::text, 'Inconnu')".to_string(),
+            "MIN(pul.zone || ', ' || pul.borough)",
+            "GROUP BY 1",
+        ),
+        Some(StoreForwardDimension::Hour) => (vec![], cube::PICKUP_HOUR_KEY.to_string(), "NULL::text", "GROUP BY 1"),
+    };
+    let mut builder = SqlBuilder::default();
+    let from_where = filters::from_where(filters, &mut builder, &joins);
+    let having = if dimension.is_some() { format!("HAVING COUNT(*) >= {min_trips}") } else { String::new() };
+    builder.sql = format!(
+        "SELECT \
+            {key} AS key, \
+            {label} AS label, \
+            COUNT(*)::bigint AS trip_count, \
+            COUNT(*) FILTER (WHERE {STORED_SQL})::bigint AS stored_count, \
+            {stored}, \
+            {direct} \
+        {from_where} \
+        {group_by} \
+        {having} \
+        ORDER BY 1",
+        stored = profile_columns("stored", STORED_SQL),
+        direct = profile_columns("direct", DIRECT_SQL),
+    );
+    builder.into_query().load::<ProfileRow>(conn)
+}
+
+fn difference(stored: Option<f64>, direct: Option<f64>) -> Option<f64> {
+    stored.zip(direct).map(|(s, d)| s - d)
+}
+
+// `overall_share` : taux global de trajets différés, pour le z de la part
+fn group(row: ProfileRow, overall_share: Option<f64>) -> StoreForwardGroup {
+    let stored_share = if row.trip_count > 0 { row.stored_count as f64 / row.trip_count as f64 } else { 0.0 };
+    let share_z = overall_share
+        .filter(|p| *p > 0.0 && *p < 1.0 && row.trip_count > 0)
+        .map(|p| (stored_share - p) / (p * (1.0 - p) / row.trip_count as f64).sqrt());
+    let stored = TelemetryProfile {
+        trip_count: row.stored_count,
+        avg_duration_seconds: row.stored_avg_duration,
+        median_duration_seconds: row.stored_median_duration,
+        avg_fare: row.stored_avg_fare,
+        invalid_share: row.stored_invalid_share,
+    };
+    let direct = TelemetryProfile {
+        trip_count: row.trip_count - row.stored_count,
+        avg_duration_seconds: row.direct_avg_duration,
+        median_duration_seconds: row.direct_median_duration,
+        avg_fare: row.direct_avg_fare,
+        invalid_share: row.direct_invalid_share,
+    };
+    StoreForwardGroup {
+        key: row.key,
+        label: row.label,
+        trip_count: row.trip_count,
+        stored_share,
+        share_z,
+        share_p_value: share_z.map(stats::normal_two_sided_p),
+        duration_diff_seconds: difference(stored.avg_duration_seconds, direct.avg_duration_seconds),
+        fare_diff: difference(stored.avg_fare, direct.avg_fare),
+        invalid_share_diff: difference(stored.invalid_share, direct.invalid_share),
+        stored,
+        direct,
+    }
+}
+
+/// Part des trajets différés et écarts différé / direct, pour l'ensemble des
+/// trajets filtrés et par groupe de `dimension` (au moins `min_trips` trajets).
+pub fn store_forward_report(
+    conn: &mut PgConnection,
+    filters: &TripFilters,
+    dimension: StoreForwardDimension,
+    min_trips: i64,
+) -> QueryResult<StoreForwardReport> {
+    // Sans GROUP BY, la requête renvoie toujours une ligne
+    let overall_row = profile_rows(conn, filters, None, min_trips)?.pop().ok_or(diesel::result::Error::NotFound)?;
+    let overall_share =
+        (overall_row.trip_count > 0).then(|| overall_row.stored_count as f64 / overall_row.trip_count as f64);
+    let overall = group(overall_row, None);
+
+    let mut groups: Vec<StoreForwardGroup> = profile_rows(conn, filters, Some(dimension), min_trips)?
+        .into_iter()
+        .map(|row| group(row, overall_share))
+        .collect();
+    if dimension != StoreForwardDimension::Hour {
+        groups.sort_by(|a, b| {
+            b.share_z
+                .unwrap_or(f64::NEG_INFINITY)
+                .total_cmp(&a.share_z.unwrap_or(f64::NEG_INFINITY))
+                .then_with(|| a.key.cmp(&b.key))
+        });
+    }
+    Ok(StoreForwardReport { by: dimension, overall, groups })
+}
+
+// Handler pour l'analyse des trajets « store and forward »
+#[utoipa::path(
+    get,
+    path = "/api/v1/store_and_forward",
+    tag = "analytics",
+    params(TripFilters, StoreForwardParams),
+    responses(
+        (status = 200, description = "Part des trajets transmis en différé et écarts de durée, tarif et cohérence par vendeur, zone ou heure", body = StoreForwardReport),
+        (status = 400, description = "Paramètres invalides", body = String),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
+pub async fn get_store_forward(
+    State(pool): State<DbPool>,
+    Query(filters): Query<TripFilters>,
+    Query(params): Query<StoreForwardParams>,
+) -> Result<Json<StoreForwardReport>, AppError> {
+    filters::validate(&filters)?;
+    let min_trips = params.min_trips.unwrap_or(DEFAULT_MIN_TRIPS);
+    if min_trips < 1 {
+        return Err(AppError::bad_request("min_trips >= 1 requis"));
+    }
+    let dimension = params.by.unwrap_or_default();
+    let mut conn = pool.get().map_err(|e| AppError(anyhow::Error::new(e)))?;
+    let report =
+        tokio::task::spawn_blocking(move || store_forward_report(&mut conn, &filters, dimension, min_trips)).await??;
+    Ok(Json(report))
+}
@@ -0,0 +1,280 @@
+// Géométries des zones de taxi TLC et carte choroplèthe des métriques par zone.
+//
+// Les géométries ne sont pas livrées avec le dépôt : elles sont lues au premier
+// appel depuis `TAXI_ZONES_PATH` ou `DEFAULT_PATHS` (GeoJSON ou shapefile TLC
+// converti à la volée), puis simplifiées une fois pour toutes à chaque niveau
+// de `SIMPLIFICATION_LEVELS`. Sans fichier installé, les routes répondent 503.
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use anyhow::{bail, Context};
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use geo::{MapCoords, MultiPolygon, Simplify};
+use geojson::{feature::Id, Feature, FeatureCollection, GeoJson, JsonObject};
+
+use crate::cube::{Join, SqlBuilder};
+use crate::db::DbPool;
+use crate::filters;
+use crate::handlers::AppError;
+use crate::models::{GeoZonesParams, TripFilters, ZoneMetrics};
+
+mod shapefile;
+
+/// Emplacements essayés, dans l'ordre, quand `TAXI_ZONES_PATH` n'est pas défini.
+pub const DEFAULT_PATHS: &[&str] = &["data/taxi_zones.geojson", "data/taxi_zones/taxi_zones.shp"];
+
+/// Tolérances de simplification (en degrés) par zoom minimal, du plus détaillé
+/// au plus grossier. Tolérance nulle : géométrie d'origine.
+pub const SIMPLIFICATION_LEVELS: &[(u8, f64)] = &[(14, 0.0), (12, 0.000_05), (10, 0.000_2), (0, 0.001)];
+
+// Zoom utilisé quand le client n'en précise pas (vue de la ville entière)
+const DEFAULT_ZOOM: u8 = 11;
+
+// Précision des coordonnées (6 décimales, ~10 cm)
+const COORD_PRECISION: f64 = 1e6;
+
+pub struct TaxiZone {
+    pub location_id: i32,
+    pub zone: String,
+    pub borough: String,
+    // Une géométrie par niveau de `SIMPLIFICATION_LEVELS`
+    geometries: Vec<MultiPolygon<f64>>,
+}
+
+impl TaxiZone {
+    /// Géométrie simplifiée adaptée au niveau de zoom.
+    pub fn geometry(&self, zoom: u8) -> &MultiPolygon<f64> {
+        let level = SIMPLIFICATION_LEVELS
+            .iter()
+            .position(|(min_zoom, _)| zoom >= *min_zoom)
+            .unwrap_or(SIMPLIFICATION_LEVELS.len() - 1);
+        &self.geometries[level]
+    }
+}
+
+pub struct TaxiZones {
+    pub zones: Vec<TaxiZone>,
+}
+
+static INSTALLED: OnceLock<TaxiZones> = OnceLock::new();
+// Sérialise les chargements : un échec n'est pas mis en cache, le fichier est
+// relu à l'appel suivant
+static LOADING: Mutex<()> = Mutex::new(());
+
+/// Chemin des géométries : `TAXI_ZONES_PATH`, sinon le premier de `DEFAULT_PATHS` présent.
+pub fn configured_path() -> Option<PathBuf> {
+    if let Ok(path) = std::env::var("TAXI_ZONES_PATH") {
+        return Some(PathBuf::from(path));
+    }
+    DEFAULT_PATHS.iter().map(PathBuf::from).find(|p| p.exists())
+}
+
+/// Géométries installées localement (voir `data/README.md`), chargées au
+/// premier appel réussi (appel bloquant). `AppError::unavailable` (503) tant
+/// qu'aucun fichier n'est présent.
+pub fn installed() -> Result<&'static TaxiZones, AppError> {
+    if let Some(zones) = INSTALLED.get() {
+        return Ok(zones);
+    }
+    let _guard = LOADING.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(zones) = INSTALLED.get() {
+        return Ok(zones);
+    }
+    let unavailable = |e: String| AppError::unavailable(format!("Géométries des zones indisponibles : {}", e));
+    let path = configured_path()
+        .ok_or_else(|| unavailable(format!("aucun fichier de zones ({} ou TAXI_ZONES_PATH)", DEFAULT_PATHS.join(", "))))?;
+    let zones = load(&path).map_err(|e| unavailable(format!("{:#}", e)))?;
+    tracing::info!("{} zones de taxi chargées depuis {}", zones.zones.len(), path.display());
+    Ok(INSTALLED.get_or_init(|| zones))
+}
+
+/// Charge un fichier GeoJSON ou un shapefile TLC (`.shp` + `.dbf`).
+pub fn load(path: &Path) -> anyhow::Result<TaxiZones> {
+    let is_shapefile = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("shp"));
+    let raw = if is_shapefile { read_shapefile(path)? } else { read_geojson(path)? };
+
+    // Certaines zones (ex. 56, 103) sont découpées en plusieurs enregistrements
+    let mut merged: BTreeMap<i32, (String, String, MultiPolygon<f64>)> = BTreeMap::new();
+    for (location_id, zone, borough, geometry) in raw {
+        let (_, _, parts) = merged.entry(location_id).or_insert_with(|| (zone, borough, MultiPolygon(Vec::new())));
+        parts.0.extend(geometry);
+    }
+    if merged.is_empty() {
+        bail!("{} ne contient aucune zone", path.display());
+    }
+
+    Ok(TaxiZones {
+        zones: merged
+            .into_iter()
+            .map(|(location_id, (zone, borough, geometry))| TaxiZone {
+                location_id,
+                zone,
+                borough,
+                geometries: SIMPLIFICATION_LEVELS.iter().map(|(_, tolerance)| simplify(&geometry, *tolerance)).collect(),
+            })
+            .collect(),
+    })
+}
+
+type RawZone = (i32, String, String, MultiPolygon<f64>);
+
+fn read_shapefile(path: &Path) -> anyhow::Result<Vec<RawZone>> {
+    shapefile::read(path)?
+        .into_iter()
+        .filter_map(|record| {
+            let location_id = record.attribute("LocationID")?.parse().ok()?;
+            let zone = record.attribute("zone").unwrap_or_default().to_string();
+            let borough = record.attribute("borough").unwrap_or_default().to_string();
+            Some(Ok((location_id, zone, borough, record.geometry)))
+        })
+        .collect()
+}
+
+fn read_geojson(path: &Path) -> anyhow::Result<Vec<RawZone>> {
+    let text = std::fs::read_to_string(path).with_context(|| format!("lecture de {}", path.display()))?;
+    let GeoJson::FeatureCollection(collection) = text.parse::<GeoJson>()? else {
+        bail!("{} n'est pas une FeatureCollection", path.display());
+    };
+
+    let mut zones = Vec::new();
+    for feature in collection.features {
+        let property = |names: &[&str]| {
+            names.iter().find_map(|name| match feature.property(name)? {
+                serde_json::Value::String(s) => Some(s.clone()),
+                serde_json::Value::Number(n) => Some(n.to_string()),
+                _ => None,
+            })
+        };
+        // Export TLC (`LocationID`) ou NYC Open Data (`location_id`, en texte)
+        let Some(location_id) = property(&["LocationID", "location_id", "locationid"]).and_then(|id| id.parse().ok()) else {
+            continue;
+        };
+        let zone = property(&["zone"]).unwrap_or_default();
+        let borough = property(&["borough"]).unwrap_or_default();
+        let Some(geometry) = feature.geometry else { continue };
+        let geometry = match geo::Geometry::<f64>::try_from(geometry)? {
+            geo::Geometry::Polygon(polygon) => MultiPolygon(vec![polygon]),
+            geo::Geometry::MultiPolygon(multi) => multi,
+            _ => bail!("zone {} : géométrie non polygonale", location_id),
+        };
+        zones.push((location_id, zone, borough, geometry));
+    }
+    Ok(zones)
+}
+
+// Douglas-Peucker puis arrondi ; les anneaux dégénérés sont écartés
+fn simplify(geometry: &MultiPolygon<f64>, tolerance: f64) -> MultiPolygon<f64> {
+    let simplified = if tolerance > 0.0 { geometry.simplify(&tolerance) } else { geometry.clone() };
+    let rounded = simplified.map_coords(|c| geo::Coord {
+        x: (c.x * COORD_PRECISION).round() / COORD_PRECISION,
+        y: (c.y * COORD_PRECISION).round() / COORD_PRECISION,
+    });
+    let polygons: Vec<_> = rounded.0.into_iter().filter(|p| p.exterior().0.len() >= 4).collect();
+    // Une zone minuscule garde sa géométrie d'origine plutôt que de disparaître
+    if polygons.is_empty() {
+        return geometry.clone();
+    }
+    MultiPolygon(polygons)
+}
+
+/// Départs, arrivées et moyennes par zone pour les trajets filtrés.
+pub fn zone_metrics(conn: &mut PgConnection, filters: &TripFilters) -> QueryResult<Vec<ZoneMetrics>> {
+    let mut builder = SqlBuilder::default();
+    let from_where = filters::from_where(filters, &mut builder, &[Join::PickupLocation, Join::DropoffLocation]);
+    builder.sql = format!(
+        "WITH trips AS ( \
+            SELECT pul.location_id AS pickup_id, dol.location_id AS dropoff_id, \
+                f.fare_amount, f.total_amount, f.tip_amount \
+            {} \
+        ), pickups AS ( \
+            SELECT pickup_id AS location_id, COUNT(*) AS pickups, \
+                AVG(fare_amount) AS avg_fare, \
+                AVG(total_amount) AS avg_total_amount, \
+                SUM(tip_amount) / NULLIF(SUM(fare_amount), 0) AS tip_rate \
+            FROM trips WHERE pickup_id IS NOT NULL GROUP BY pickup_id \
+        ), dropoffs AS ( \
+            SELECT dropoff_id AS location_id, COUNT(*) AS dropoffs \
+            FROM trips WHERE dropoff_id IS NOT NULL GROUP BY dropoff_id \
+        ) \
+        SELECT \
+            location_id, \
+            COALESCE(p.pickups, 0)::bigint AS pickups, \
+            COALESCE(d.dropoffs, 0)::bigint AS dropoffs, \
+            p.avg_fare::float8 AS avg_fare, \
+            p.avg_total_amount::float8 AS avg_total_amount, \
+            p.tip_rate::float8 AS tip_rate \
+        FROM pickups p FULL OUTER JOIN dropoffs d USING (location_id) \
+        ORDER BY location_id",
+        from_where
+    );
+    builder.into_query().load::<ZoneMetrics>(conn)
+}
+
+/// FeatureCollection des zones, avec leurs métriques dans les propriétés.
+pub fn choropleth(zones: &TaxiZones, metrics: &[ZoneMetrics], zoom: u8) -> FeatureCollection {
+    let by_zone: BTreeMap<i32, &ZoneMetrics> = metrics.iter().map(|m| (m.location_id, m)).collect();
+    let features = zones
+        .zones
+        .iter()
+        .map(|zone| {
+            let metrics = by_zone.get(&zone.location_id);
+            let mut properties = JsonObject::new();
+            properties.insert("location_id".into(), zone.location_id.into());
+            properties.insert("zone".into(), zone.zone.clone().into());
+            properties.insert("borough".into(), zone.borough.clone().into());
+            properties.insert("pickups".into(), metrics.map_or(0, |m| m.pickups).into());
+            properties.insert("dropoffs".into(), metrics.map_or(0, |m| m.dropoffs).into());
+            properties.insert("avg_fare".into(), metrics.and_then(|m| m.avg_fare).into());
+            properties.insert("avg_total_amount".into(), metrics.and_then(|m| m.avg_total_amount).into());
+            properties.insert("tip_rate".into(), metrics.and_then(|m| m.tip_rate).into());
+            Feature {
+                bbox: None,
+                geometry: Some(geojson::Geometry::from(zone.geometry(zoom))),
+                id: Some(Id::Number(zone.location_id.into())),
+                properties: Some(properties),
+                foreign_members: None,
+            }
+        })
+        .collect();
+    FeatureCollection { bbox: None, features, foreign_members: None }
+}
+
+// Handler pour les zones de taxi avec métriques (carte choroplèthe)
+#[utoipa::path(
+    get,
+    path = "/api/v1/geo/zones",
+    tag = "analytics",
+    params(TripFilters, GeoZonesParams),
+    responses(
+        (status = 200, description = "FeatureCollection GeoJSON des zones ; propriétés location_id, zone, borough, pickups, dropoffs, avg_fare, avg_total_amount, tip_rate", body = Object),
+        (status = 400, description = "Paramètres invalides", body = String),
+        (status = 503, description = "Géométries des zones non installées", body = String),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
+pub async fn get_geo_zones(
+    State(pool): State<DbPool>,
+    Query(filters): Query<TripFilters>,
+    Query(params): Query<GeoZonesParams>,
+) -> Result<Json<FeatureCollection>, AppError> {
+    filters::validate(&filters)?;
+    let zoom = params.zoom.unwrap_or(DEFAULT_ZOOM);
+    if zoom > 22 {
+        return Err(AppError::bad_request("zoom entre 0 et 22 requis"));
+    }
+
+    let mut conn = pool.get().map_err(|e| AppError(anyhow::Error::new(e)))?;
+    let collection = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let zones = installed()?;
+        let metrics = zone_metrics(&mut conn, &filters)?;
+        Ok(choropleth(zones, &metrics, zoom))
+    })
+    .await??;
+    Ok(Json(collection))
+}
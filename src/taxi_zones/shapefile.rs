@@ -0,0 +1,367 @@
+// Lecture du shapefile des zones de taxi publié par la TLC (`taxi_zones.shp`
+// + `taxi_zones.dbf`) et reprojection en WGS84.
+//
+// Seul le sous-ensemble du format utilisé par la TLC est pris en charge :
+// géométries polygonales (types 5, 15, 25) et attributs dBase texte/numériques.
+use std::fs;
+use std::path::Path;
+
+use anyhow::{bail, Context};
+use geo::{Contains, Coord, LineString, MapCoords, MultiPolygon, Point, Polygon};
+
+// Enregistrement lu : attributs dBase (nom de colonne -> valeur) et géométrie
+pub(crate) struct ShapeRecord {
+    pub attributes: Vec<(String, String)>,
+    pub geometry: MultiPolygon<f64>,
+}
+
+impl ShapeRecord {
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Lit `path` (.shp) et le fichier .dbf voisin, en reprojetant si besoin les
+/// coordonnées EPSG:2263 (State Plane New York Long Island, pieds US) en WGS84.
+pub(crate) fn read(path: &Path) -> anyhow::Result<Vec<ShapeRecord>> {
+    let shp = fs::read(path).with_context(|| format!("lecture de {}", path.display()))?;
+    let dbf_path = path.with_extension("dbf");
+    let dbf = fs::read(&dbf_path).with_context(|| format!("lecture de {}", dbf_path.display()))?;
+
+    let geometries = read_shp(&shp)?;
+    let attributes = read_dbf(&dbf)?;
+    if geometries.len() != attributes.len() {
+        bail!("{} géométries pour {} enregistrements dBase", geometries.len(), attributes.len());
+    }
+
+    // Le shapefile TLC est en pieds US ; une version déjà en degrés est acceptée telle quelle
+    let projected = geometries
+        .iter()
+        .flat_map(|g| g.0.iter())
+        .flat_map(|p| p.exterior().0.iter())
+        .any(|c| c.x.abs() > 180.0 || c.y.abs() > 90.0);
+    Ok(geometries
+        .into_iter()
+        .zip(attributes)
+        .map(|(geometry, attributes)| ShapeRecord {
+            geometry: if projected { unproject(geometry) } else { geometry },
+            attributes,
+        })
+        .collect())
+}
+
+fn le_i32(bytes: &[u8], offset: usize) -> anyhow::Result<i32> {
+    let slice = bytes.get(offset..offset + 4).context("shapefile tronqué")?;
+    Ok(i32::from_le_bytes(slice.try_into()?))
+}
+
+fn be_i32(bytes: &[u8], offset: usize) -> anyhow::Result<i32> {
+    let slice = bytes.get(offset..offset + 4).context("shapefile tronqué")?;
+    Ok(i32::from_be_bytes(slice.try_into()?))
+}
+
+fn le_f64(bytes: &[u8], offset: usize) -> anyhow::Result<f64> {
+    let slice = bytes.get(offset..offset + 8).context("shapefile tronqué")?;
+    Ok(f64::from_le_bytes(slice.try_into()?))
+}
+
+fn read_shp(bytes: &[u8]) -> anyhow::Result<Vec<MultiPolygon<f64>>> {
+    if be_i32(bytes, 0)? != 9994 {
+        bail!("fichier .shp invalide (code {})", be_i32(bytes, 0)?);
+    }
+    let mut geometries = Vec::new();
+    let mut offset = 100;
+    while offset + 8 <= bytes.len() {
+        // Longueur du contenu en mots de 16 bits
+        let content_len = be_i32(bytes, offset + 4)? as usize * 2;
+        let content = bytes
+            .get(offset + 8..offset + 8 + content_len)
+            .context("enregistrement .shp tronqué")?;
+        geometries.push(read_polygon(content)?);
+        offset += 8 + content_len;
+    }
+    Ok(geometries)
+}
+
+fn read_polygon(content: &[u8]) -> anyhow::Result<MultiPolygon<f64>> {
+    match le_i32(content, 0)? {
+        0 => return Ok(MultiPolygon(Vec::new())),
+        5 | 15 | 25 => {}
+        other => bail!("type de forme {} non pris en charge (polygones uniquement)", other),
+    }
+    let num_parts = le_i32(content, 36)? as usize;
+    let num_points = le_i32(content, 40)? as usize;
+    let points_at = 44 + 4 * num_parts;
+    let mut starts = (0..num_parts)
+        .map(|i| le_i32(content, 44 + 4 * i).map(|s| s as usize))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    starts.push(num_points);
+
+    let mut rings = Vec::with_capacity(num_parts);
+    for bounds in starts.windows(2) {
+        let coords = (bounds[0]..bounds[1])
+            .map(|i| {
+                Ok(Coord {
+                    x: le_f64(content, points_at + 16 * i)?,
+                    y: le_f64(content, points_at + 16 * i + 8)?,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        rings.push(LineString(coords));
+    }
+    Ok(assemble(rings))
+}
+
+// Aire signée (formule du lacet) : négative pour un anneau horaire
+fn signed_area(ring: &LineString<f64>) -> f64 {
+    ring.0.windows(2).map(|w| w[0].x * w[1].y - w[1].x * w[0].y).sum::<f64>() / 2.0
+}
+
+// Anneaux horaires = contours extérieurs, anti-horaires = trous du contour qui les contient
+fn assemble(rings: Vec<LineString<f64>>) -> MultiPolygon<f64> {
+    let (exteriors, holes): (Vec<_>, Vec<_>) = rings.into_iter().partition(|r| signed_area(r) <= 0.0);
+    let mut polygons: Vec<(LineString<f64>, Vec<LineString<f64>>)> =
+        exteriors.into_iter().map(|e| (e, Vec::new())).collect();
+    for hole in holes {
+        let Some(first) = hole.0.first().copied() else { continue };
+        let owner = polygons
+            .iter()
+            .position(|(exterior, _)| Polygon::new(exterior.clone(), Vec::new()).contains(&Point::from(first)));
+        match owner {
+            Some(i) => polygons[i].1.push(hole),
+            // Anneau mal orienté : traité comme un contour
+            None => polygons.push((hole, Vec::new())),
+        }
+    }
+    MultiPolygon(polygons.into_iter().map(|(exterior, holes)| Polygon::new(exterior, holes)).collect())
+}
+
+fn read_dbf(bytes: &[u8]) -> anyhow::Result<Vec<Vec<(String, String)>>> {
+    if bytes.len() < 32 {
+        bail!("fichier .dbf tronqué");
+    }
+    let record_count = u32::from_le_bytes(bytes[4..8].try_into()?) as usize;
+    let header_len = u16::from_le_bytes(bytes[8..10].try_into()?) as usize;
+    let record_len = u16::from_le_bytes(bytes[10..12].try_into()?) as usize;
+
+    // Descripteurs de champs (32 octets chacun) jusqu'au terminateur 0x0D
+    let mut fields = Vec::new();
+    let mut offset = 32;
+    while offset + 32 <= header_len && bytes[offset] != 0x0D {
+        let name = &bytes[offset..offset + 11];
+        let name = String::from_utf8_lossy(&name[..name.iter().position(|&b| b == 0).unwrap_or(11)]).into_owned();
+        fields.push((name, bytes[offset + 16] as usize));
+        offset += 32;
+    }
+
+    let mut records = Vec::with_capacity(record_count);
+    for i in 0..record_count {
+        let start = header_len + i * record_len;
+        let record = bytes.get(start..start + record_len).context("enregistrement .dbf tronqué")?;
+        // Premier octet : indicateur de suppression ('*'). L'enregistrement est
+        // conservé sans attributs pour rester aligné avec le .shp
+        if record[0] == b'*' {
+            records.push(Vec::new());
+            continue;
+        }
+        let mut position = 1;
+        let mut attributes = Vec::with_capacity(fields.len());
+        for (name, len) in &fields {
+            let raw = record.get(position..position + len).context("champ .dbf tronqué")?;
+            attributes.push((name.clone(), String::from_utf8_lossy(raw).trim().to_string()));
+            position += len;
+        }
+        records.push(attributes);
+    }
+    Ok(records)
+}
+
+// Conique conforme de Lambert à deux parallèles (EPSG:2263, ellipsoïde GRS80)
+const SEMI_MAJOR_AXIS: f64 = 6_378_137.0;
+const INVERSE_FLATTENING: f64 = 298.257_222_101;
+const US_SURVEY_FOOT: f64 = 1200.0 / 3937.0;
+const FALSE_EASTING_M: f64 = 300_000.0;
+const STANDARD_PARALLEL_1: f64 = 41.0 + 2.0 / 60.0;
+const STANDARD_PARALLEL_2: f64 = 40.0 + 40.0 / 60.0;
+const LATITUDE_OF_ORIGIN: f64 = 40.0 + 10.0 / 60.0;
+const CENTRAL_MERIDIAN: f64 = -74.0;
+
+struct Lambert {
+    e: f64,
+    n: f64,
+    af: f64,
+    rho0: f64,
+}
+
+impl Lambert {
+    fn epsg_2263() -> Self {
+        let f = 1.0 / INVERSE_FLATTENING;
+        let e = (2.0 * f - f * f).sqrt();
+        let m = |phi: f64| phi.cos() / (1.0 - e * e * phi.sin().powi(2)).sqrt();
+        let t = |phi: f64| {
+            (std::f64::consts::FRAC_PI_4 - phi / 2.0).tan()
+                / ((1.0 - e * phi.sin()) / (1.0 + e * phi.sin())).powf(e / 2.0)
+        };
+        let (phi1, phi2, phi0) = (
+            STANDARD_PARALLEL_1.to_radians(),
+            STANDARD_PARALLEL_2.to_radians(),
+            LATITUDE_OF_ORIGIN.to_radians(),
+        );
+        let n = (m(phi1).ln() - m(phi2).ln()) / (t(phi1).ln() - t(phi2).ln());
+        let af = SEMI_MAJOR_AXIS * m(phi1) / (n * t(phi1).powf(n));
+        Lambert { e, n, af, rho0: af * t(phi0).powf(n) }
+    }
+
+    // (x, y) en pieds US -> (longitude, latitude) en degrés
+    fn inverse(&self, c: Coord<f64>) -> Coord<f64> {
+        let x = c.x * US_SURVEY_FOOT - FALSE_EASTING_M;
+        let y = self.rho0 - c.y * US_SURVEY_FOOT;
+        let rho = (x * x + y * y).sqrt().copysign(self.n);
+        let t = (rho / self.af).powf(1.0 / self.n);
+        let lambda = x.atan2(y) / self.n + CENTRAL_MERIDIAN.to_radians();
+
+        let mut phi = std::f64::consts::FRAC_PI_2 - 2.0 * t.atan();
+        for _ in 0..8 {
+            let es = self.e * phi.sin();
+            phi = std::f64::consts::FRAC_PI_2 - 2.0 * (t * ((1.0 - es) / (1.0 + es)).powf(self.e / 2.0)).atan();
+        }
+        Coord { x: lambda.to_degrees(), y: phi.to_degrees() }
+    }
+}
+
+fn unproject(geometry: MultiPolygon<f64>) -> MultiPolygon<f64> {
+    let lambert = Lambert::epsg_2263();
+    geometry.map_coords(|c| lambert.inverse(c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Contenu d'un enregistrement polygone (type 5), boîte englobante à zéro
+    fn polygon_content(rings: &[&[(f64, f64)]]) -> Vec<u8> {
+        let mut bytes = 5i32.to_le_bytes().to_vec();
+        bytes.extend([0u8; 32]);
+        bytes.extend((rings.len() as i32).to_le_bytes());
+        bytes.extend((rings.iter().map(|r| r.len()).sum::<usize>() as i32).to_le_bytes());
+        let mut start = 0;
+        for ring in rings {
+            bytes.extend((start as i32).to_le_bytes());
+            start += ring.len();
+        }
+        for (x, y) in rings.iter().flat_map(|r| r.iter()) {
+            bytes.extend(x.to_le_bytes());
+            bytes.extend(y.to_le_bytes());
+        }
+        bytes
+    }
+
+    fn dbf(fields: &[(&str, u8)], records: &[(u8, &[&str])]) -> Vec<u8> {
+        let header_len = 32 + 32 * fields.len() + 1;
+        let record_len = 1 + fields.iter().map(|(_, len)| *len as usize).sum::<usize>();
+        let mut bytes = vec![3u8, 124, 1, 1];
+        bytes.extend((records.len() as u32).to_le_bytes());
+        bytes.extend((header_len as u16).to_le_bytes());
+        bytes.extend((record_len as u16).to_le_bytes());
+        bytes.extend([0u8; 20]);
+        for (name, len) in fields {
+            let mut descriptor = [0u8; 32];
+            descriptor[..name.len()].copy_from_slice(name.as_bytes());
+            descriptor[11] = b'C';
+            descriptor[16] = *len;
+            bytes.extend(descriptor);
+        }
+        bytes.push(0x0D);
+        for (flag, values) in records {
+            bytes.push(*flag);
+            for ((_, len), value) in fields.iter().zip(values.iter()) {
+                bytes.extend(format!("{:<width$}", value, width = *len as usize).into_bytes());
+            }
+        }
+        bytes
+    }
+
+    #[test]
+    fn polygon_with_hole() {
+        // Contour horaire, trou anti-horaire
+        let exterior: &[(f64, f64)] = &[(0.0, 0.0), (0.0, 10.0), (10.0, 10.0), (10.0, 0.0), (0.0, 0.0)];
+        let hole: &[(f64, f64)] = &[(2.0, 2.0), (8.0, 2.0), (8.0, 8.0), (2.0, 8.0), (2.0, 2.0)];
+        let geometry = read_polygon(&polygon_content(&[exterior, hole])).unwrap();
+        assert_eq!(geometry.0.len(), 1);
+        let polygon = &geometry.0[0];
+        assert_eq!(polygon.exterior().0.len(), 5);
+        assert_eq!(polygon.interiors().len(), 1);
+        assert_eq!(polygon.interiors()[0].0[1], Coord { x: 8.0, y: 2.0 });
+    }
+
+    #[test]
+    fn separate_parts_are_separate_polygons() {
+        let a: &[(f64, f64)] = &[(0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (1.0, 0.0), (0.0, 0.0)];
+        let b: &[(f64, f64)] = &[(5.0, 5.0), (5.0, 6.0), (6.0, 6.0), (6.0, 5.0), (5.0, 5.0)];
+        let geometry = read_polygon(&polygon_content(&[a, b])).unwrap();
+        assert_eq!(geometry.0.len(), 2);
+        assert!(geometry.0.iter().all(|p| p.interiors().is_empty()));
+    }
+
+    #[test]
+    fn null_and_unsupported_shapes() {
+        assert!(read_polygon(&0i32.to_le_bytes()).unwrap().0.is_empty());
+        let mut point = 1i32.to_le_bytes().to_vec();
+        point.extend([0u8; 16]);
+        assert!(read_polygon(&point).is_err());
+        assert!(read_polygon(&polygon_content(&[])[..30]).is_err());
+    }
+
+    #[test]
+    fn dbf_records() {
+        let bytes = dbf(
+            &[("LocationID", 4), ("zone", 12)],
+            &[(b' ', &["1", "Newark"]), (b'*', &["2", "Supprimée"]), (b' ', &["132", "JFK Airport"])],
+        );
+        let records = read_dbf(&bytes).unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0], vec![("LocationID".into(), "1".into()), ("zone".into(), "Newark".into())]);
+        // Enregistrement supprimé : conservé vide pour rester aligné avec le .shp
+        assert!(records[1].is_empty());
+        assert_eq!(records[2][1].1, "JFK Airport");
+        assert!(read_dbf(&bytes[..bytes.len() - 3]).is_err());
+    }
+
+    // Projection directe EPSG:2263, pour vérifier l'aller-retour
+    fn forward(lambert: &Lambert, lon: f64, lat: f64) -> Coord<f64> {
+        let phi = lat.to_radians();
+        let es = lambert.e * phi.sin();
+        let t = (std::f64::consts::FRAC_PI_4 - phi / 2.0).tan() / ((1.0 - es) / (1.0 + es)).powf(lambert.e / 2.0);
+        let rho = lambert.af * t.powf(lambert.n);
+        let theta = lambert.n * (lon - CENTRAL_MERIDIAN).to_radians();
+        Coord {
+            x: (FALSE_EASTING_M + rho * theta.sin()) / US_SURVEY_FOOT,
+            y: (lambert.rho0 - rho * theta.cos()) / US_SURVEY_FOOT,
+        }
+    }
+
+    #[test]
+    fn lambert_origin() {
+        // Origine de la projection : faux est sur le méridien central, y = 0 à 40°10' N
+        let c = Lambert::epsg_2263().inverse(Coord { x: 984_250.0, y: 0.0 });
+        assert!((c.x - CENTRAL_MERIDIAN).abs() < 1e-9);
+        assert!((c.y - LATITUDE_OF_ORIGIN).abs() < 1e-9);
+    }
+
+    #[test]
+    fn lambert_round_trip() {
+        let lambert = Lambert::epsg_2263();
+        // Central Park, JFK, Staten Island
+        for (lon, lat) in [(-73.9654, 40.7829), (-73.7781, 40.6413), (-74.1502, 40.5795)] {
+            let projected = forward(&lambert, lon, lat);
+            // Ordre de grandeur des coordonnées TLC (pieds)
+            assert!((900_000.0..1_100_000.0).contains(&projected.x), "{projected:?}");
+            assert!((100_000.0..300_000.0).contains(&projected.y), "{projected:?}");
+            let back = lambert.inverse(projected);
+            assert!((back.x - lon).abs() < 1e-8 && (back.y - lat).abs() < 1e-8, "{back:?}");
+        }
+    }
+}
@@ -0,0 +1,311 @@
+// Tuiles vectorielles (Mapbox Vector Tiles) des zones de taxi avec leurs
+// métriques, pour les cartes choroplèthes : seules les tuiles visibles sont
+// transférées à chaque changement de filtre.
+//
+// Deux caches LRU : métriques par jeu de filtres (partagées par toutes les
+// tuiles d'une vue) et tuiles encodées par (z, x, y, filtres).
+use std::f64::consts::PI;
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+};
+use geo::{BoundingRect, MultiPolygon};
+use lru::LruCache;
+
+use crate::db::DbPool;
+use crate::filters;
+use crate::handlers::AppError;
+use crate::models::{TripFilters, ZoneMetrics};
+use crate::taxi_zones::{self, TaxiZones};
+
+pub mod mvt;
+
+/// Nom de la couche des zones dans les tuiles.
+pub const LAYER_NAME: &str = "zones";
+/// Zoom maximal servi.
+pub const MAX_ZOOM: u8 = 22;
+/// Durée de validité des entrées en cache (et `Cache-Control` des réponses).
+pub const CACHE_TTL: Duration = Duration::from_secs(600);
+
+const TILE_CACHE_SIZE: usize = 4096;
+const METRICS_CACHE_SIZE: usize = 64;
+// Marge autour de la tuile (unités de grille) pour éviter les coutures au rendu
+const BUFFER: f64 = 64.0;
+const MAX_LATITUDE: f64 = 85.051_128_78;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct TileKey {
+    z: u8,
+    x: u32,
+    y: u32,
+    filters: TripFilters,
+}
+
+type Cache<K, V> = Mutex<LruCache<K, (Instant, Arc<V>)>>;
+
+static TILE_CACHE: OnceLock<Cache<TileKey, Vec<u8>>> = OnceLock::new();
+static METRICS_CACHE: OnceLock<Cache<TripFilters, Vec<ZoneMetrics>>> = OnceLock::new();
+
+fn cache<K: std::hash::Hash + Eq, V>(cell: &'static OnceLock<Cache<K, V>>, size: usize) -> &'static Cache<K, V> {
+    cell.get_or_init(|| Mutex::new(LruCache::new(NonZeroUsize::new(size).unwrap())))
+}
+
+fn cached<K: std::hash::Hash + Eq, V>(cache: &Cache<K, V>, key: &K) -> Option<Arc<V>> {
+    let mut cache = cache.lock().unwrap_or_else(|e| e.into_inner());
+    match cache.get(key) {
+        Some((inserted, value)) if inserted.elapsed() < CACHE_TTL => Some(value.clone()),
+        _ => None,
+    }
+}
+
+fn store<K: std::hash::Hash + Eq, V>(cache: &Cache<K, V>, key: K, value: Arc<V>) {
+    cache.lock().unwrap_or_else(|e| e.into_inner()).put(key, (Instant::now(), value));
+}
+
+/// Vide les caches de tuiles et de métriques (ex. après un chargement de données).
+pub fn clear_cache() {
+    cache(&TILE_CACHE, TILE_CACHE_SIZE).lock().unwrap_or_else(|e| e.into_inner()).clear();
+    cache(&METRICS_CACHE, METRICS_CACHE_SIZE).lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+// Longitude/latitude -> coordonnées Web Mercator en tuiles (zoom z)
+fn to_tile_space(lon: f64, lat: f64, z: u8) -> (f64, f64) {
+    let n = f64::from(1u32 << z);
+    let lat = lat.clamp(-MAX_LATITUDE, MAX_LATITUDE).to_radians();
+    let x = (lon + 180.0) / 360.0 * n;
+    let y = (1.0 - (lat.tan() + 1.0 / lat.cos()).ln() / PI) / 2.0 * n;
+    (x, y)
+}
+
+// Sutherland-Hodgman contre la tuile élargie de BUFFER (fenêtre convexe)
+fn clip_ring(points: Vec<(f64, f64)>) -> Vec<(f64, f64)> {
+    let (min, max) = (-BUFFER, f64::from(mvt::EXTENT) + BUFFER);
+    type Edge = (fn(&(f64, f64), f64) -> bool, fn(&(f64, f64), &(f64, f64), f64) -> (f64, f64), f64);
+    let edges: [Edge; 4] = [
+        (|p, v| p.0 >= v, |a, b, v| (v, a.1 + (b.1 - a.1) * (v - a.0) / (b.0 - a.0)), min),
+        (|p, v| p.0 <= v, |a, b, v| (v, a.1 + (b.1 - a.1) * (v - a.0) / (b.0 - a.0)), max),
+        (|p, v| p.1 >= v, |a, b, v| (a.0 + (b.0 - a.0) * (v - a.1) / (b.1 - a.1), v), min),
+        (|p, v| p.1 <= v, |a, b, v| (a.0 + (b.0 - a.0) * (v - a.1) / (b.1 - a.1), v), max),
+    ];
+    let mut output = points;
+    for (inside, intersect, bound) in edges {
+        let input = std::mem::take(&mut output);
+        let Some(mut previous) = input.last().copied() else { break };
+        for current in input {
+            match (inside(&current, bound), inside(&previous, bound)) {
+                (true, true) => output.push(current),
+                (true, false) => {
+                    output.push(intersect(&previous, &current, bound));
+                    output.push(current);
+                }
+                (false, true) => output.push(intersect(&previous, &current, bound)),
+                (false, false) => {}
+            }
+            previous = current;
+        }
+    }
+    output
+}
+
+// Multipolygone WGS84 -> polygones en coordonnées de la tuile (z, x, y)
+fn project(geometry: &MultiPolygon<f64>, z: u8, x: u32, y: u32) -> Vec<mvt::Polygon> {
+    let extent = f64::from(mvt::EXTENT);
+    let to_grid = |lon: f64, lat: f64| {
+        let (tx, ty) = to_tile_space(lon, lat, z);
+        ((tx - f64::from(x)) * extent, (ty - f64::from(y)) * extent)
+    };
+    geometry
+        .0
+        .iter()
+        .map(|polygon| {
+            std::iter::once(polygon.exterior())
+                .chain(polygon.interiors())
+                .map(|ring| {
+                    let points = ring.0.iter().map(|c| to_grid(c.x, c.y)).collect();
+                    clip_ring(points)
+                        .into_iter()
+                        .map(|(px, py)| (px.round() as i32, py.round() as i32))
+                        .collect()
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Tuile encodée des zones intersectant (z, x, y), avec leurs métriques.
+pub fn render_tile(zones: &TaxiZones, metrics: &[ZoneMetrics], z: u8, x: u32, y: u32) -> Vec<u8> {
+    let n = f64::from(1u32 << z);
+    // Emprise de la tuile en degrés (avec marge) pour écarter les zones hors champ
+    let margin = BUFFER / f64::from(mvt::EXTENT);
+    let lon = |tx: f64| tx / n * 360.0 - 180.0;
+    let lat = |ty: f64| (PI * (1.0 - 2.0 * ty / n)).sinh().atan().to_degrees();
+    let (west, east) = (lon(f64::from(x) - margin), lon(f64::from(x) + 1.0 + margin));
+    let (north, south) = (lat(f64::from(y) - margin), lat(f64::from(y) + 1.0 + margin));
+
+    let mut layer = mvt::Layer::new(LAYER_NAME);
+    for zone in &zones.zones {
+        let geometry = zone.geometry(z);
+        let Some(bounds) = geometry.bounding_rect() else { continue };
+        if bounds.max().x < west || bounds.min().x > east || bounds.max().y < south || bounds.min().y > north {
+            continue;
+        }
+        let polygons = project(geometry, z, x, y);
+
+        let metric = metrics.iter().find(|m| m.location_id == zone.location_id);
+        let mut attributes = vec![
+            ("location_id", mvt::Value::Int(zone.location_id.into())),
+            ("zone", mvt::Value::String(zone.zone.clone())),
+            ("borough", mvt::Value::String(zone.borough.clone())),
+            ("pickups", mvt::Value::Int(metric.map_or(0, |m| m.pickups))),
+            ("dropoffs", mvt::Value::Int(metric.map_or(0, |m| m.dropoffs))),
+        ];
+        // Attributs absents plutôt que nuls (pas de valeur nulle en MVT)
+        let averages = [
+            ("avg_fare", metric.and_then(|m| m.avg_fare)),
+            ("avg_total_amount", metric.and_then(|m| m.avg_total_amount)),
+            ("tip_rate", metric.and_then(|m| m.tip_rate)),
+        ];
+        for (key, value) in averages {
+            if let Some(value) = value {
+                attributes.push((key, mvt::Value::Double(value)));
+            }
+        }
+        layer.add_polygons(zone.location_id as u64, &polygons, attributes);
+    }
+    mvt::encode_tile(&[layer])
+}
+
+// Handler pour les tuiles vectorielles des zones
+#[utoipa::path(
+    get,
+    path = "/api/v1/tiles/{z}/{x}/{y}.mvt",
+    tag = "analytics",
+    params(
+        ("z" = u8, Path, description = "Niveau de zoom (0-22)"),
+        ("x" = u32, Path, description = "Colonne de la tuile"),
+        ("y" = u32, Path, description = "Ligne de la tuile"),
+        TripFilters
+    ),
+    responses(
+        (status = 200, description = "Tuile Mapbox Vector Tile, couche `zones` (location_id, zone, borough, pickups, dropoffs, avg_fare, avg_total_amount, tip_rate)", content_type = "application/vnd.mapbox-vector-tile", body = Vec<u8>),
+        (status = 204, description = "Aucune zone dans la tuile"),
+        (status = 400, description = "Coordonnées de tuile ou filtres invalides", body = String),
+        (status = 503, description = "Géométries des zones non installées", body = String),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
+pub async fn get_zone_tile(
+    State(pool): State<DbPool>,
+    Path((z, x, tile)): Path<(u8, u32, String)>,
+    Query(filters): Query<TripFilters>,
+) -> Result<Response, AppError> {
+    filters::validate(&filters)?;
+    let y: u32 = tile
+        .strip_suffix(".mvt")
+        .and_then(|y| y.parse().ok())
+        .ok_or_else(|| AppError::bad_request(format!("tuile {} invalide, attendu {{y}}.mvt", tile)))?;
+    if z > MAX_ZOOM || x >= 1u32 << z || y >= 1u32 << z {
+        return Err(AppError::bad_request(format!("tuile {}/{}/{} hors de la grille", z, x, y)));
+    }
+
+    let key = TileKey { z, x, y, filters };
+    let tiles = cache(&TILE_CACHE, TILE_CACHE_SIZE);
+    let body = match cached(tiles, &key) {
+        Some(body) => body,
+        None => {
+            let zones = tokio::task::spawn_blocking(taxi_zones::installed).await??;
+            let metrics_cache = cache(&METRICS_CACHE, METRICS_CACHE_SIZE);
+            let metrics = match cached(metrics_cache, &key.filters) {
+                Some(metrics) => metrics,
+                None => {
+                    let mut conn = pool.get().map_err(|e| AppError(anyhow::Error::new(e)))?;
+                    let filters = key.filters.clone();
+                    let metrics = Arc::new(
+                        tokio::task::spawn_blocking(move || taxi_zones::zone_metrics(&mut conn, &filters)).await??,
+                    );
+                    store(metrics_cache, key.filters.clone(), metrics.clone());
+                    metrics
+                }
+            };
+            let body = Arc::new(tokio::task::spawn_blocking(move || render_tile(zones, &metrics, z, x, y)).await?);
+            store(tiles, key, body.clone());
+            body
+        }
+    };
+
+    let cache_control = format!("public, max-age={}", CACHE_TTL.as_secs());
+    if body.is_empty() {
+        return Ok((StatusCode::NO_CONTENT, [(header::CACHE_CONTROL, cache_control)]).into_response());
+    }
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/vnd.mapbox-vector-tile".to_string()),
+            (header::CACHE_CONTROL, cache_control),
+        ],
+        body.as_ref().clone(),
+    )
+        .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMIT: f64 = mvt::EXTENT as f64 + BUFFER;
+
+    fn area(ring: &[(f64, f64)]) -> f64 {
+        let n = ring.len();
+        (0..n).map(|i| ring[i].0 * ring[(i + 1) % n].1 - ring[(i + 1) % n].0 * ring[i].1).sum::<f64>().abs() / 2.0
+    }
+
+    fn within_buffer(ring: &[(f64, f64)]) -> bool {
+        ring.iter().all(|&(x, y)| (-BUFFER..=LIMIT).contains(&x) && (-BUFFER..=LIMIT).contains(&y))
+    }
+
+    #[test]
+    fn inside_ring_is_unchanged() {
+        let ring = vec![(10.0, 10.0), (100.0, 10.0), (100.0, 100.0), (10.0, 100.0), (10.0, 10.0)];
+        let clipped = clip_ring(ring.clone());
+        assert_eq!(area(&clipped), area(&ring));
+        assert!(ring.iter().all(|p| clipped.contains(p)));
+    }
+
+    #[test]
+    fn ring_straddling_right_edge() {
+        let ring = vec![(4000.0, 100.0), (4400.0, 100.0), (4400.0, 300.0), (4000.0, 300.0), (4000.0, 100.0)];
+        let clipped = clip_ring(ring);
+        assert!(within_buffer(&clipped));
+        assert!(clipped.contains(&(LIMIT, 100.0)) && clipped.contains(&(LIMIT, 300.0)));
+        assert!((area(&clipped) - (LIMIT - 4000.0) * 200.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn ring_straddling_corner() {
+        // Carré centré sur le coin supérieur gauche de la tuile
+        let ring = vec![(-500.0, -500.0), (500.0, -500.0), (500.0, 500.0), (-500.0, 500.0), (-500.0, -500.0)];
+        let clipped = clip_ring(ring);
+        assert!(within_buffer(&clipped));
+        assert!((area(&clipped) - (500.0 + BUFFER).powi(2)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn diagonal_edge_is_interpolated() {
+        let ring = vec![(4000.0, 0.0), (4400.0, 400.0), (4000.0, 400.0), (4000.0, 0.0)];
+        let clipped = clip_ring(ring);
+        assert!(within_buffer(&clipped));
+        // Intersection de la diagonale avec x = LIMIT
+        assert!(clipped.iter().any(|&(x, y)| x == LIMIT && (y - (LIMIT - 4000.0)).abs() < 1e-9));
+    }
+
+    #[test]
+    fn outside_ring_is_dropped() {
+        let ring = vec![(5000.0, 5000.0), (6000.0, 5000.0), (6000.0, 6000.0), (5000.0, 5000.0)];
+        assert!(clip_ring(ring).is_empty());
+        assert!(clip_ring(Vec::new()).is_empty());
+    }
+}
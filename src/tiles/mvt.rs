@@ -0,0 +1,338 @@
+// Encodage Mapbox Vector Tile 2.1 (protobuf écrit à la main, polygones uniquement).
+//
+// Référence : https://github.com/mapbox/vector-tile-spec/tree/master/2.1
+use std::collections::HashMap;
+
+/// Résolution de la grille d'une tuile.
+pub const EXTENT: u32 = 4096;
+
+// Types de fil protobuf
+const VARINT: u32 = 0;
+const LENGTH_DELIMITED: u32 = 2;
+const FIXED64: u32 = 1;
+
+// Commandes de géométrie
+const MOVE_TO: u32 = 1;
+const LINE_TO: u32 = 2;
+const CLOSE_PATH: u32 = 7;
+
+const GEOM_POLYGON: u64 = 3;
+
+/// Valeur d'attribut d'une feature.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    String(String),
+    Double(f64),
+    Int(i64),
+}
+
+impl Value {
+    // Clé de déduplication dans la table des valeurs de la couche
+    fn key(&self) -> String {
+        match self {
+            Value::String(s) => format!("s:{}", s),
+            Value::Double(d) => format!("d:{}", d.to_bits()),
+            Value::Int(i) => format!("i:{}", i),
+        }
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        match self {
+            Value::String(s) => write_bytes(&mut buf, 1, s.as_bytes()),
+            Value::Double(d) => {
+                write_tag(&mut buf, 3, FIXED64);
+                buf.extend_from_slice(&d.to_le_bytes());
+            }
+            // sint_value (zigzag)
+            Value::Int(i) => write_varint_field(&mut buf, 6, zigzag(*i)),
+        }
+        buf
+    }
+}
+
+/// Anneau en coordonnées de tuile (0..EXTENT, y vers le bas), non fermé.
+pub type Ring = Vec<(i32, i32)>;
+
+/// Polygone : premier anneau extérieur, suivants intérieurs.
+pub type Polygon = Vec<Ring>;
+
+/// Couche de polygones en cours de construction.
+pub struct Layer {
+    name: String,
+    keys: Vec<String>,
+    key_index: HashMap<String, u32>,
+    values: Vec<Value>,
+    value_index: HashMap<String, u32>,
+    features: Vec<Vec<u8>>,
+}
+
+impl Layer {
+    pub fn new(name: impl Into<String>) -> Self {
+        Layer {
+            name: name.into(),
+            keys: Vec::new(),
+            key_index: HashMap::new(),
+            values: Vec::new(),
+            value_index: HashMap::new(),
+            features: Vec::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.features.is_empty()
+    }
+
+    fn key(&mut self, key: &str) -> u32 {
+        if let Some(&i) = self.key_index.get(key) {
+            return i;
+        }
+        let i = self.keys.len() as u32;
+        self.keys.push(key.to_string());
+        self.key_index.insert(key.to_string(), i);
+        i
+    }
+
+    fn value(&mut self, value: Value) -> u32 {
+        let key = value.key();
+        if let Some(&i) = self.value_index.get(&key) {
+            return i;
+        }
+        let i = self.values.len() as u32;
+        self.values.push(value);
+        self.value_index.insert(key, i);
+        i
+    }
+
+    /// Ajoute une feature multipolygone. Les anneaux sont réorientés selon la
+    /// spécification (extérieur horaire à l'écran) ; ceux de moins de trois
+    /// points distincts sont ignorés.
+    pub fn add_polygons(&mut self, id: u64, polygons: &[Polygon], attributes: Vec<(&str, Value)>) {
+        let geometry = encode_geometry(polygons);
+        if geometry.is_empty() {
+            return;
+        }
+        let mut tags = Vec::with_capacity(attributes.len() * 2);
+        for (key, value) in attributes {
+            tags.push(self.key(key));
+            tags.push(self.value(value));
+        }
+
+        let mut feature = Vec::new();
+        write_varint_field(&mut feature, 1, id);
+        write_packed(&mut feature, 2, &tags);
+        write_varint_field(&mut feature, 3, GEOM_POLYGON);
+        write_packed(&mut feature, 4, &geometry);
+        self.features.push(feature);
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        write_varint_field(&mut buf, 15, 2);
+        write_bytes(&mut buf, 1, self.name.as_bytes());
+        for feature in &self.features {
+            write_bytes(&mut buf, 2, feature);
+        }
+        for key in &self.keys {
+            write_bytes(&mut buf, 3, key.as_bytes());
+        }
+        for value in &self.values {
+            write_bytes(&mut buf, 4, &value.encode());
+        }
+        write_varint_field(&mut buf, 5, EXTENT as u64);
+        buf
+    }
+}
+
+/// Tuile complète (message `Tile`).
+pub fn encode_tile(layers: &[Layer]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    for layer in layers.iter().filter(|l| !l.is_empty()) {
+        write_bytes(&mut buf, 3, &layer.encode());
+    }
+    buf
+}
+
+// Aire signée en coordonnées écran : positive pour un anneau horaire
+fn signed_area(ring: &Ring) -> i64 {
+    let n = ring.len();
+    (0..n)
+        .map(|i| {
+            let (x0, y0) = ring[i];
+            let (x1, y1) = ring[(i + 1) % n];
+            x0 as i64 * y1 as i64 - x1 as i64 * y0 as i64
+        })
+        .sum()
+}
+
+fn encode_geometry(polygons: &[Polygon]) -> Vec<u32> {
+    let mut commands = Vec::new();
+    let (mut cx, mut cy) = (0i32, 0i32);
+    for polygon in polygons {
+        for (index, ring) in polygon.iter().enumerate() {
+            let mut ring: Ring = ring.clone();
+            ring.dedup();
+            while ring.len() > 1 && ring.first() == ring.last() {
+                ring.pop();
+            }
+            let area = signed_area(&ring);
+            if ring.len() < 3 || area == 0 {
+                // Un extérieur dégénéré invalide tout le polygone
+                if index == 0 {
+                    break;
+                }
+                continue;
+            }
+            let exterior = index == 0;
+            if (area > 0) != exterior {
+                ring.reverse();
+            }
+
+            commands.push(command(MOVE_TO, 1));
+            let (x, y) = ring[0];
+            commands.push(zigzag32(x - cx));
+            commands.push(zigzag32(y - cy));
+            (cx, cy) = (x, y);
+            commands.push(command(LINE_TO, ring.len() as u32 - 1));
+            for &(x, y) in &ring[1..] {
+                commands.push(zigzag32(x - cx));
+                commands.push(zigzag32(y - cy));
+                (cx, cy) = (x, y);
+            }
+            commands.push(command(CLOSE_PATH, 1));
+        }
+    }
+    commands
+}
+
+fn command(id: u32, count: u32) -> u32 {
+    (id & 0x7) | (count << 3)
+}
+
+fn zigzag(n: i64) -> u64 {
+    ((n << 1) ^ (n >> 63)) as u64
+}
+
+fn zigzag32(n: i32) -> u32 {
+    ((n << 1) ^ (n >> 31)) as u32
+}
+
+fn write_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn write_tag(buf: &mut Vec<u8>, field: u32, wire_type: u32) {
+    write_varint(buf, ((field << 3) | wire_type) as u64);
+}
+
+fn write_varint_field(buf: &mut Vec<u8>, field: u32, value: u64) {
+    write_tag(buf, field, VARINT);
+    write_varint(buf, value);
+}
+
+fn write_bytes(buf: &mut Vec<u8>, field: u32, bytes: &[u8]) {
+    write_tag(buf, field, LENGTH_DELIMITED);
+    write_varint(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+fn write_packed(buf: &mut Vec<u8>, field: u32, values: &[u32]) {
+    let mut packed = Vec::with_capacity(values.len() * 2);
+    for &v in values {
+        write_varint(&mut packed, v as u64);
+    }
+    write_bytes(buf, field, &packed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unzigzag(n: u32) -> i32 {
+        ((n >> 1) as i32) ^ -((n & 1) as i32)
+    }
+
+    // Décode les commandes en anneaux (coordonnées absolues, sans point de fermeture)
+    fn decode(commands: &[u32]) -> Vec<Ring> {
+        let (mut rings, mut ring) = (Vec::new(), Vec::new());
+        let (mut cx, mut cy) = (0, 0);
+        let mut i = 0;
+        while i < commands.len() {
+            let (id, count) = (commands[i] & 0x7, commands[i] >> 3);
+            i += 1;
+            match id {
+                MOVE_TO | LINE_TO => {
+                    for _ in 0..count {
+                        cx += unzigzag(commands[i]);
+                        cy += unzigzag(commands[i + 1]);
+                        ring.push((cx, cy));
+                        i += 2;
+                    }
+                }
+                CLOSE_PATH => rings.push(std::mem::take(&mut ring)),
+                other => panic!("commande inconnue {other}"),
+            }
+        }
+        rings
+    }
+
+    #[test]
+    fn zigzag_encoding() {
+        assert_eq!([0, -1, 1, -2, 2].map(zigzag32), [0, 1, 2, 3, 4]);
+        assert_eq!(zigzag(-1), 1);
+        assert!([0, -1, 7, -4096, 4096].iter().all(|&n| unzigzag(zigzag32(n)) == n));
+    }
+
+    #[test]
+    fn square_with_hole() {
+        // Extérieur anti-horaire à l'écran et trou horaire : les deux sont inversés
+        let polygon = vec![
+            vec![(0, 0), (0, 10), (10, 10), (10, 0), (0, 0)],
+            vec![(2, 2), (8, 2), (8, 8), (2, 8), (2, 2)],
+        ];
+        let commands = encode_geometry(&[polygon]);
+        assert_eq!(commands[0], command(MOVE_TO, 1));
+        assert_eq!(commands[3], command(LINE_TO, 3));
+        assert_eq!(commands.iter().filter(|&&c| c == command(CLOSE_PATH, 1)).count(), 2);
+
+        let rings = decode(&commands);
+        assert_eq!(rings.len(), 2);
+        assert_eq!(rings[0], vec![(10, 0), (10, 10), (0, 10), (0, 0)]);
+        assert_eq!(rings[1], vec![(2, 8), (8, 8), (8, 2), (2, 2)]);
+        // Extérieur horaire (aire positive à l'écran), trou anti-horaire
+        assert_eq!(signed_area(&rings[0]), 200);
+        assert_eq!(signed_area(&rings[1]), -72);
+    }
+
+    #[test]
+    fn well_oriented_rings_are_kept() {
+        let exterior = vec![(100, 100), (200, 100), (200, 200), (100, 200)];
+        let rings = decode(&encode_geometry(&[vec![exterior.clone()]]));
+        assert_eq!(rings, vec![exterior]);
+    }
+
+    #[test]
+    fn degenerate_rings() {
+        let square = vec![(0, 0), (4, 0), (4, 4), (0, 4)];
+        let flat = vec![(0, 0), (5, 0), (9, 0), (0, 0)];
+        // Trou plat ignoré ; extérieur plat : polygone entier ignoré
+        let rings = decode(&encode_geometry(&[vec![square.clone(), flat.clone()], vec![flat.clone(), square.clone()]]));
+        assert_eq!(rings, vec![square]);
+        assert!(encode_geometry(&[vec![vec![(1, 1), (1, 1), (2, 2)]]]).is_empty());
+    }
+
+    #[test]
+    fn cursor_carries_across_polygons() {
+        let a = vec![(0, 0), (4, 0), (4, 4), (0, 4)];
+        let b = vec![(100, 100), (104, 100), (104, 104), (100, 104)];
+        let commands = encode_geometry(&[vec![a.clone()], vec![b.clone()]]);
+        // Second MoveTo relatif au dernier point du premier anneau (0, 4)
+        let second_move = commands.iter().rposition(|&c| c == command(MOVE_TO, 1)).unwrap();
+        assert_eq!((unzigzag(commands[second_move + 1]), unzigzag(commands[second_move + 2])), (100, 96));
+        assert_eq!(decode(&commands), vec![a, b]);
+    }
+}
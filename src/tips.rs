@@ -0,0 +1,310 @@
+// Comportement de pourboire : histogramme des pourcentages, taux par axe,
+// part des trajets par carte sans pourboire et détection des montants
+// proposés par les terminaux (15 / 20 / 25 / 30 %).
+//
+// Seuls les pourboires payés par carte sont enregistrés par les taximètres ;
+// les pourboires en espèces apparaissent à 0. L'histogramme et le résumé se
+// limitent donc aux trajets par carte, la ventilation couvre tous les trajets.
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use diesel::sql_types::{Array, BigInt, Float8, Integer, Nullable};
+
+use crate::cube::{self, Bind, Join, SqlBuilder};
+use crate::db::DbPool;
+use crate::filters;
+use crate::handlers::AppError;
+use crate::models::{
+    CubeDimension, TipBreakdown, TipBreakdownParams, TipDimension, TipDistribution, TipDistributionParams,
+    TipPresetShare, TipSummary, TripFilters,
+};
+
+/// Pourcentages proposés par les terminaux de paiement.
+pub const TIP_PRESETS: [i32; 4] = [15, 20, 25, 30];
+/// Écart toléré autour d'un pourcentage proposé, en points.
+pub const PRESET_TOLERANCE_PERCENT: f64 = 0.5;
+const DEFAULT_BUCKET_PERCENT: f64 = 2.0;
+const DEFAULT_MAX_PERCENT: f64 = 50.0;
+/// Nombre maximal de classes de l'histogramme.
+pub const MAX_BUCKETS: f64 = 500.0;
+
+// Pourcentage de pourboire sur le montant avant pourboire hors péages
+const TIP_PERCENT_SQL: &str =
+    "100.0 * f.tip_amount / NULLIF(f.total_amount - f.tip_amount - COALESCE(f.tolls_amount, 0), 0)";
+// Trajets par carte (`payment_type_id` TLC 1) dont le pourcentage est calculable
+const CARD_SQL: &str = "pt.payment_type_id = 1 AND f.total_amount - f.tip_amount - COALESCE(f.tolls_amount, 0) > 0";
+
+// Tranches de distance (miles), triées par borne basse
+const DISTANCE_BAND_SQL: &str = "CASE \
+        WHEN f.trip_distance < 1 THEN '0-1 mi' \
+        WHEN f.trip_distance < 2 THEN '1-2 mi' \
+        WHEN f.trip_distance < 5 THEN '2-5 mi' \
+        WHEN f.trip_distance < 10 THEN '5-10 mi' \
+        WHEN f.trip_distance < 20 THEN '10-20 mi' \
+        ELSE '20+ mi' \
+    END";
+
+/// Taux de pourboire ventilé selon `dimension`.
+pub fn tip_breakdown(
+    conn: &mut PgConnection,
+    filters: &TripFilters,
+    dimension: TipDimension,
+) -> QueryResult<Vec<TipBreakdown>> {
+    let cube_dimension = match dimension {
+        TipDimension::PaymentType => Some(CubeDimension::PaymentType),
+        TipDimension::PickupBorough => Some(CubeDimension::PickupBorough),
+        TipDimension::DropoffBorough => Some(CubeDimension::DropoffBorough),
+        TipDimension::Vendor => Some(CubeDimension::Vendor),
+        TipDimension::Hour | TipDimension::DistanceBand => None,
+    };
+    let (key, join, order) = match (dimension, cube_dimension) {
+        (_, Some(d)) => {
+            let (sql, _, join) = cube::dimension_sql(d);
+            (format!("COALESCE(({sql})::text, 'Inconnu')"), join, "trip_count DESC, key")
+        }
+        (TipDimension::Hour, None) => (cube::PICKUP_HOUR_KEY.to_string(), None, "key"),
+        (_, None) => (DISTANCE_BAND_SQL.to_string(), None, "MIN(f.trip_distance)"),
+    };
+    let mut joins = vec![Join::PaymentType];
+    joins.extend(join);
+
+    let mut builder = SqlBuilder::default();
+    let from_where = filters::from_where(filters, &mut builder, &joins);
+    builder.sql = format!(
+        "SELECT \
+            {key} AS key, \
+            COUNT(f.trip_id)::bigint AS trip_count, \
+            COALESCE(AVG((f.tip_amount > 0)::int), 0.0)::float8 AS tipped_share, \
+            COALESCE(AVG(f.tip_amount), 0.0)::float8 AS avg_tip, \
+            COALESCE(SUM(f.tip_amount) / NULLIF(SUM(f.fare_amount), 0), 0.0)::float8 AS tip_rate, \
+            AVG({TIP_PERCENT_SQL})::float8 AS avg_tip_percent \
+        {from_where} \
+        GROUP BY 1 \
+        ORDER BY {order}"
+    );
+    builder.into_query().load::<TipBreakdown>(conn)
+}
+
+#[derive(QueryableByName)]
+struct TipPercentStats {
+    #[diesel(sql_type = BigInt)]
+    trip_count: i64,
+    #[diesel(sql_type = BigInt)]
+    zero_tip_count: i64,
+    #[diesel(sql_type = Nullable<Float8>)]
+    mean_tip_percent: Option<f64>,
+    #[diesel(sql_type = Nullable<Float8>)]
+    median_tip_percent: Option<f64>,
+}
+
+#[derive(QueryableByName)]
+struct TipBucket {
+    #[diesel(sql_type = Integer)]
+    bucket: i32,
+    #[diesel(sql_type = BigInt)]
+    trip_count: i64,
+}
+
+/// Histogramme des pourcentages de pourboire des trajets par carte ; la
+/// dernière classe regroupe les pourcentages au-delà de `max_percent`.
+pub fn tip_distribution(
+    conn: &mut PgConnection,
+    filters: &TripFilters,
+    bucket_percent: f64,
+    max_percent: f64,
+) -> QueryResult<TipDistribution> {
+    let mut builder = SqlBuilder::default();
+    let from_where = filters::from_where(filters, &mut builder, &[Join::PaymentType]);
+    builder.sql = format!(
+        "SELECT \
+            COUNT(*)::bigint AS trip_count, \
+            COUNT(*) FILTER (WHERE f.tip_amount = 0)::bigint AS zero_tip_count, \
+            AVG({TIP_PERCENT_SQL})::float8 AS mean_tip_percent, \
+            percentile_cont(0.5) WITHIN GROUP (ORDER BY {TIP_PERCENT_SQL})::float8 AS median_tip_percent \
+        {from_where} AND {CARD_SQL}"
+    );
+    let stats = builder.into_query().get_result::<TipPercentStats>(conn)?;
+
+    let buckets = (max_percent / bucket_percent).ceil() as usize + 1;
+    let mut builder = SqlBuilder::default();
+    let from_where = filters::from_where(filters, &mut builder, &[Join::PaymentType]);
+    let width = builder.bind(Bind::Text(bucket_percent.to_string()));
+    builder.sql = format!(
+        "SELECT \
+            LEAST(FLOOR(GREATEST({TIP_PERCENT_SQL}, 0) / {width}::float8), {last})::int AS bucket, \
+            COUNT(*)::bigint AS trip_count \
+        {from_where} AND {CARD_SQL} \
+        GROUP BY 1",
+        last = buckets - 1
+    );
+    let mut histogram = vec![0; buckets];
+    for bucket in builder.into_query().load::<TipBucket>(conn)? {
+        histogram[bucket.bucket as usize] = bucket.trip_count;
+    }
+
+    Ok(TipDistribution {
+        bucket_percent,
+        max_percent,
+        trip_count: stats.trip_count,
+        zero_tip_count: stats.zero_tip_count,
+        mean_tip_percent: stats.mean_tip_percent,
+        median_tip_percent: stats.median_tip_percent,
+        histogram,
+    })
+}
+
+#[derive(QueryableByName)]
+struct TipSummaryRow {
+    #[diesel(sql_type = BigInt)]
+    trip_count: i64,
+    #[diesel(sql_type = BigInt)]
+    card_trips: i64,
+    #[diesel(sql_type = BigInt)]
+    zero_tip_card_trips: i64,
+    #[diesel(sql_type = BigInt)]
+    tipped_card_trips: i64,
+    #[diesel(sql_type = BigInt)]
+    round_dollar_trips: i64,
+    #[diesel(sql_type = Nullable<Float8>)]
+    avg_tip_percent_card: Option<f64>,
+    #[diesel(sql_type = Array<BigInt>)]
+    preset_trips: Vec<i64>,
+}
+
+// Part sûre (0 si le dénominateur est nul)
+fn share(count: i64, total: i64) -> f64 {
+    if total > 0 {
+        count as f64 / total as f64
+    } else {
+        0.0
+    }
+}
+
+/// Trajets par carte sans pourboire et pourboires correspondant aux
+/// pourcentages proposés ou à un montant rond en dollars.
+pub fn tip_summary(conn: &mut PgConnection, filters: &TripFilters) -> QueryResult<TipSummary> {
+    let tipped = format!("{CARD_SQL} AND f.tip_amount > 0");
+    let presets = TIP_PRESETS
+        .iter()
+        .map(|p| {
+            format!(
+                "COUNT(*) FILTER (WHERE {tipped} AND ABS({TIP_PERCENT_SQL} - {p}) <= {PRESET_TOLERANCE_PERCENT})"
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut builder = SqlBuilder::default();
+    let from_where = filters::from_where(filters, &mut builder, &[Join::PaymentType]);
+    builder.sql = format!(
+        "SELECT \
+            COUNT(f.trip_id)::bigint AS trip_count, \
+            COUNT(*) FILTER (WHERE {CARD_SQL})::bigint AS card_trips, \
+            COUNT(*) FILTER (WHERE {CARD_SQL} AND f.tip_amount = 0)::bigint AS zero_tip_card_trips, \
+            COUNT(*) FILTER (WHERE {tipped})::bigint AS tipped_card_trips, \
+            COUNT(*) FILTER (WHERE {tipped} AND f.tip_amount = ROUND(f.tip_amount::numeric)::float8)::bigint AS round_dollar_trips, \
+            (AVG({TIP_PERCENT_SQL}) FILTER (WHERE {CARD_SQL}))::float8 AS avg_tip_percent_card, \
+            ARRAY[{presets}]::bigint[] AS preset_trips \
+        {from_where}"
+    );
+    let row = builder.into_query().get_result::<TipSummaryRow>(conn)?;
+
+    Ok(TipSummary {
+        trip_count: row.trip_count,
+        card_trips: row.card_trips,
+        zero_tip_card_trips: row.zero_tip_card_trips,
+        zero_tip_card_share: share(row.zero_tip_card_trips, row.card_trips),
+        avg_tip_percent_card: row.avg_tip_percent_card,
+        preset_tolerance_percent: PRESET_TOLERANCE_PERCENT,
+        presets: TIP_PRESETS
+            .iter()
+            .zip(row.preset_trips)
+            .map(|(&percent, trip_count)| TipPresetShare {
+                percent,
+                trip_count,
+                share: share(trip_count, row.tipped_card_trips),
+            })
+            .collect(),
+        round_dollar_share: share(row.round_dollar_trips, row.tipped_card_trips),
+    })
+}
+
+// Handler pour la ventilation du taux de pourboire
+#[utoipa::path(
+    get,
+    path = "/api/v1/tips/breakdown",
+    tag = "analytics",
+    params(TripFilters, TipBreakdownParams),
+    responses(
+        (status = 200, description = "Taux de pourboire par type de paiement, heure, borough, tranche de distance ou fournisseur", body = Vec<TipBreakdown>),
+        (status = 400, description = "Paramètres invalides", body = String),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
+pub async fn get_tip_breakdown(
+    State(pool): State<DbPool>,
+    Query(filters): Query<TripFilters>,
+    Query(params): Query<TipBreakdownParams>,
+) -> Result<Json<Vec<TipBreakdown>>, AppError> {
+    filters::validate(&filters)?;
+    let dimension = params.by.unwrap_or_default();
+    let mut conn = pool.get().map_err(|e| AppError(anyhow::Error::new(e)))?;
+    let results = tokio::task::spawn_blocking(move || tip_breakdown(&mut conn, &filters, dimension)).await??;
+    Ok(Json(results))
+}
+
+// Handler pour l'histogramme des pourcentages de pourboire
+#[utoipa::path(
+    get,
+    path = "/api/v1/tips/distribution",
+    tag = "analytics",
+    params(TripFilters, TipDistributionParams),
+    responses(
+        (status = 200, description = "Histogramme des pourcentages de pourboire (trajets par carte)", body = TipDistribution),
+        (status = 400, description = "Paramètres invalides", body = String),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
+pub async fn get_tip_distribution(
+    State(pool): State<DbPool>,
+    Query(filters): Query<TripFilters>,
+    Query(params): Query<TipDistributionParams>,
+) -> Result<Json<TipDistribution>, AppError> {
+    filters::validate(&filters)?;
+    let bucket_percent = params.bucket_percent.unwrap_or(DEFAULT_BUCKET_PERCENT);
+    let max_percent = params.max_percent.unwrap_or(DEFAULT_MAX_PERCENT);
+    if !(bucket_percent > 0.0 && max_percent > 0.0 && max_percent / bucket_percent <= MAX_BUCKETS) {
+        return Err(AppError::bad_request(format!(
+            "bucket_percent et max_percent positifs requis, au plus {MAX_BUCKETS} classes"
+        )));
+    }
+    let mut conn = pool.get().map_err(|e| AppError(anyhow::Error::new(e)))?;
+    let distribution =
+        tokio::task::spawn_blocking(move || tip_distribution(&mut conn, &filters, bucket_percent, max_percent))
+            .await??;
+    Ok(Json(distribution))
+}
+
+// Handler pour le résumé : pourboires nuls et montants proposés
+#[utoipa::path(
+    get,
+    path = "/api/v1/tips/summary",
+    tag = "analytics",
+    params(TripFilters),
+    responses(
+        (status = 200, description = "Part des trajets par carte sans pourboire et des pourboires aux pourcentages proposés", body = TipSummary),
+        (status = 400, description = "Paramètres invalides", body = String),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
+pub async fn get_tip_summary(
+    State(pool): State<DbPool>,
+    Query(filters): Query<TripFilters>,
+) -> Result<Json<TipSummary>, AppError> {
+    filters::validate(&filters)?;
+    let mut conn = pool.get().map_err(|e| AppError(anyhow::Error::new(e)))?;
+    let summary = tokio::task::spawn_blocking(move || tip_summary(&mut conn, &filters)).await??;
+    Ok(Json(summary))
+}
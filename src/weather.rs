@@ -0,0 +1,500 @@
+// Météo : import des observations NOAA dans `dim_weather` (voir
+// `src/bin/import_weather.rs`) et ventilation des trajets selon la météo de
+// leur heure de prise en charge.
+//
+// Deux formats CSV sont reconnus à leur en-tête :
+// - GHCN-Daily (Climate Data Online, « Daily Summaries ») : colonnes `DATE`,
+//   `PRCP`, `SNOW`, `TAVG` / `TMAX` / `TMIN`, `AWND`, `WT01` (brouillard) ;
+//   chaque jour couvre ses 24 heures ;
+// - Local Climatological Data (LCD) : colonnes `DATE` (horodatage),
+//   `HourlyDryBulbTemperature`, `HourlyPrecipitation`,
+//   `HourlyPresentWeatherType`, `HourlyVisibility`, `HourlyWindSpeed` ;
+//   les horodatages, en heure normale locale (LST, UTC-5 toute l'année), sont
+//   ramenés à l'heure légale de New York, celle des trajets, puis les relevés
+//   d'une même heure sont regroupés.
+// Les valeurs sont converties en °C, mm et m/s.
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::Context;
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Timelike, Weekday};
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use diesel::sql_types::{Array, Date, Float8, Int4, Nullable, Text};
+
+use crate::cube::{Bind, SqlBuilder};
+use crate::db::DbPool;
+use crate::filters;
+use crate::handlers::AppError;
+use crate::models::{TripFilters, WeatherBreakdown, WeatherBreakdownParams, WeatherDimension};
+
+/// Dossier lu par l'importeur quand aucun fichier n'est donné.
+pub const DEFAULT_DIR: &str = "data/weather";
+/// Ordre d'affichage des conditions.
+pub const CONDITIONS: [&str; 6] = ["dry", "rain", "heavy_rain", "snow", "fog", "unknown"];
+const DEFAULT_BAND_CELSIUS: f64 = 5.0;
+// Seuils de pluie (mm) : forte / mesurable, par jour et par heure
+const DAILY_RAIN_MM: (f64, f64) = (10.0, 1.0);
+const HOURLY_RAIN_MM: (f64, f64) = (4.0, 0.0);
+// Visibilité (miles) en dessous de laquelle une heure compte comme brouillard
+const FOG_VISIBILITY_MILES: f64 = 0.625;
+
+/// Unités des colonnes GHCN-Daily (les fichiers LCD sont toujours en unités US).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Units {
+    /// Pouces, °F, mph (valeur par défaut de Climate Data Online)
+    Standard,
+    /// mm, °C, m/s
+    Metric,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeatherObservation {
+    pub date: NaiveDate,
+    pub hour: i32,
+    pub station: String,
+    pub temperature_c: Option<f64>,
+    pub precipitation_mm: Option<f64>,
+    pub snowfall_mm: Option<f64>,
+    pub wind_speed_ms: Option<f64>,
+    pub condition: &'static str,
+    pub source: &'static str,
+}
+
+// Valeur numérique NOAA : « T » (traces) vaut 0, suffixes de qualité ignorés
+fn value(raw: Option<&str>) -> Option<f64> {
+    let raw = raw?.trim().trim_end_matches(['s', 'V', '*']);
+    if raw == "T" {
+        return Some(0.0);
+    }
+    raw.parse().ok()
+}
+
+fn fahrenheit_to_celsius(f: f64) -> f64 {
+    (f - 32.0) * 5.0 / 9.0
+}
+
+fn condition(
+    precipitation_mm: Option<f64>,
+    snow: bool,
+    fog: bool,
+    (heavy_mm, rain_mm): (f64, f64),
+    observed: bool,
+) -> &'static str {
+    match precipitation_mm {
+        _ if snow => "snow",
+        Some(p) if p >= heavy_mm => "heavy_rain",
+        Some(p) if p > 0.0 && p >= rain_mm => "rain",
+        _ if fog => "fog",
+        _ if observed || precipitation_mm.is_some() => "dry",
+        _ => "unknown",
+    }
+}
+
+// Colonnes d'un en-tête CSV, par nom
+struct Columns(Vec<String>);
+
+impl Columns {
+    fn get<'r>(&self, record: &'r csv::StringRecord, name: &str) -> Option<&'r str> {
+        let index = self.0.iter().position(|c| c == name)?;
+        record.get(index).filter(|v| !v.trim().is_empty())
+    }
+}
+
+fn parse_daily(
+    columns: &Columns,
+    records: impl Iterator<Item = csv::StringRecord>,
+    units: Units,
+) -> anyhow::Result<Vec<WeatherObservation>> {
+    let (length, temperature, speed): (f64, fn(f64) -> f64, f64) = match units {
+        Units::Standard => (25.4, fahrenheit_to_celsius, 0.44704),
+        Units::Metric => (1.0, |c| c, 1.0),
+    };
+    let mut observations = Vec::new();
+    for record in records {
+        let date = columns.get(&record, "DATE").context("colonne DATE vide")?;
+        let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").with_context(|| format!("date invalide : {date}"))?;
+        let average = value(columns.get(&record, "TAVG")).or_else(|| {
+            Some((value(columns.get(&record, "TMAX"))? + value(columns.get(&record, "TMIN"))?) / 2.0)
+        });
+        let precipitation_mm = value(columns.get(&record, "PRCP")).map(|p| p * length);
+        let snowfall_mm = value(columns.get(&record, "SNOW")).map(|s| s * length);
+        let fog = ["WT01", "WT02"].iter().any(|c| columns.get(&record, c) == Some("1"));
+        let observation = WeatherObservation {
+            date,
+            hour: 0,
+            station: columns.get(&record, "STATION").unwrap_or_default().to_string(),
+            temperature_c: average.map(temperature),
+            precipitation_mm,
+            snowfall_mm,
+            wind_speed_ms: value(columns.get(&record, "AWND")).map(|w| w * speed),
+            condition: condition(precipitation_mm, snowfall_mm.is_some_and(|s| s > 0.0), fog, DAILY_RAIN_MM, false),
+            source: "daily",
+        };
+        observations.extend((0..24).map(|hour| WeatherObservation { hour, ..observation.clone() }));
+    }
+    Ok(observations)
+}
+
+// Heure normale locale (LST) -> heure légale de New York. L'heure d'été
+// commence à 2 h LST et finit à 2 h heure d'été, soit 1 h LST : deuxième
+// dimanche de mars et premier de novembre depuis 2007, premier dimanche
+// d'avril et dernier d'octobre auparavant.
+fn local_standard_to_new_york(lst: NaiveDateTime) -> NaiveDateTime {
+    let year = lst.year();
+    let sunday = |month, n| NaiveDate::from_weekday_of_month_opt(year, month, Weekday::Sun, n);
+    let (start, end) = if year >= 2007 {
+        (sunday(3, 2), sunday(11, 1))
+    } else {
+        (sunday(4, 1), sunday(10, 5).or_else(|| sunday(10, 4)))
+    };
+    let daylight = match (start.and_then(|d| d.and_hms_opt(2, 0, 0)), end.and_then(|d| d.and_hms_opt(1, 0, 0))) {
+        (Some(start), Some(end)) => (start..end).contains(&lst),
+        _ => false,
+    };
+    if daylight {
+        lst + Duration::hours(1)
+    } else {
+        lst
+    }
+}
+
+// Relevés d'une heure, avant agrégation
+#[derive(Default)]
+struct HourlyReadings {
+    station: String,
+    temperatures_f: Vec<f64>,
+    precipitation_in: Option<f64>,
+    winds_mph: Vec<f64>,
+    weather_types: String,
+    visibility_miles: Option<f64>,
+}
+
+fn parse_hourly(columns: &Columns, records: impl Iterator<Item = csv::StringRecord>) -> anyhow::Result<Vec<WeatherObservation>> {
+    let mut hours: BTreeMap<(NaiveDate, u32), HourlyReadings> = BTreeMap::new();
+    for record in records {
+        let timestamp = columns.get(&record, "DATE").context("colonne DATE vide")?;
+        let timestamp = NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%dT%H:%M:%S")
+            .map(local_standard_to_new_york)
+            .with_context(|| format!("horodatage invalide : {timestamp}"))?;
+        let temperature = value(columns.get(&record, "HourlyDryBulbTemperature"));
+        let precipitation = value(columns.get(&record, "HourlyPrecipitation"));
+        let wind = value(columns.get(&record, "HourlyWindSpeed"));
+        let weather_type = columns.get(&record, "HourlyPresentWeatherType");
+        let visibility = value(columns.get(&record, "HourlyVisibility"));
+        // Résumés journaliers ou mensuels (SOD, SOM) : aucune valeur horaire
+        if temperature.is_none() && precipitation.is_none() && weather_type.is_none() {
+            continue;
+        }
+        let readings = hours.entry((timestamp.date(), timestamp.hour())).or_default();
+        readings.station = columns.get(&record, "STATION").unwrap_or_default().to_string();
+        readings.temperatures_f.extend(temperature);
+        readings.winds_mph.extend(wind);
+        // Cumul depuis le début de l'heure : le plus grand relevé
+        if let Some(p) = precipitation {
+            readings.precipitation_in = Some(readings.precipitation_in.map_or(p, |max| max.max(p)));
+        }
+        if let Some(weather_type) = weather_type {
+            readings.weather_types.push_str(weather_type);
+            readings.weather_types.push(' ');
+        }
+        if let Some(v) = visibility {
+            readings.visibility_miles = Some(readings.visibility_miles.map_or(v, |m| m.min(v)));
+        }
+    }
+
+    let mean = |values: &[f64]| (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64);
+    Ok(hours
+        .into_iter()
+        .map(|((date, hour), r)| {
+            let precipitation_mm = r.precipitation_in.map(|p| p * 25.4);
+            let snow = r.weather_types.contains("SN");
+            let fog = r.weather_types.contains("FG") || r.visibility_miles.is_some_and(|v| v < FOG_VISIBILITY_MILES);
+            let rain = r.weather_types.contains("RA") || r.weather_types.contains("DZ");
+            let condition = match condition(precipitation_mm, snow, fog, HOURLY_RAIN_MM, true) {
+                // Pluie signalée sans cumul mesurable
+                "dry" | "fog" if rain => "rain",
+                other => other,
+            };
+            WeatherObservation {
+                date,
+                hour: hour as i32,
+                station: r.station,
+                temperature_c: mean(&r.temperatures_f).map(fahrenheit_to_celsius),
+                precipitation_mm,
+                snowfall_mm: None,
+                wind_speed_ms: mean(&r.winds_mph).map(|w| w * 0.44704),
+                condition,
+                source: "hourly",
+            }
+        })
+        .collect())
+}
+
+/// Observations d'un fichier CSV NOAA (format détecté à l'en-tête), pour
+/// `station` ou, à défaut, la première station du fichier.
+pub fn load(path: &Path, station: Option<&str>, units: Units) -> anyhow::Result<Vec<WeatherObservation>> {
+    let mut reader = csv::Reader::from_path(path).with_context(|| format!("lecture de {}", path.display()))?;
+    let columns = Columns(reader.headers()?.iter().map(str::to_string).collect());
+    let records: Vec<csv::StringRecord> = reader.records().collect::<Result<_, _>>()?;
+    let station = station
+        .map(str::to_string)
+        .or_else(|| records.first().and_then(|r| columns.get(r, "STATION")).map(str::to_string));
+    let records = records
+        .into_iter()
+        .filter(|r| station.is_none() || columns.get(r, "STATION") == station.as_deref());
+
+    if columns.0.iter().any(|c| c == "HourlyDryBulbTemperature") {
+        parse_hourly(&columns, records)
+    } else if columns.0.iter().any(|c| c == "PRCP") {
+        parse_daily(&columns, records, units)
+    } else {
+        anyhow::bail!("{} : ni colonnes GHCN-Daily (PRCP) ni LCD (HourlyDryBulbTemperature)", path.display())
+    }
+}
+
+/// Enregistre les observations ; une observation journalière ne remplace
+/// jamais une observation horaire déjà présente. Renvoie le nombre de lignes
+/// insérées ou mises à jour.
+pub fn store(conn: &mut PgConnection, rows: &[WeatherObservation]) -> QueryResult<usize> {
+    diesel::sql_query(
+        "INSERT INTO dim_weather AS w \
+            (weather_date, hour, station, temperature_c, precipitation_mm, snowfall_mm, wind_speed_ms, condition, source) \
+        SELECT * FROM UNNEST($1, $2, $3, $4, $5, $6, $7, $8, $9) \
+        ON CONFLICT (weather_date, hour) DO UPDATE SET \
+            station = EXCLUDED.station, \
+            temperature_c = EXCLUDED.temperature_c, \
+            precipitation_mm = EXCLUDED.precipitation_mm, \
+            snowfall_mm = EXCLUDED.snowfall_mm, \
+            wind_speed_ms = EXCLUDED.wind_speed_ms, \
+            condition = EXCLUDED.condition, \
+            source = EXCLUDED.source \
+        WHERE w.source = 'daily' OR EXCLUDED.source = 'hourly'",
+    )
+    .bind::<Array<Date>, _>(rows.iter().map(|r| r.date).collect::<Vec<_>>())
+    .bind::<Array<Int4>, _>(rows.iter().map(|r| r.hour).collect::<Vec<_>>())
+    .bind::<Array<Text>, _>(rows.iter().map(|r| r.station.clone()).collect::<Vec<_>>())
+    .bind::<Array<Nullable<Float8>>, _>(rows.iter().map(|r| r.temperature_c).collect::<Vec<_>>())
+    .bind::<Array<Nullable<Float8>>, _>(rows.iter().map(|r| r.precipitation_mm).collect::<Vec<_>>())
+    .bind::<Array<Nullable<Float8>>, _>(rows.iter().map(|r| r.snowfall_mm).collect::<Vec<_>>())
+    .bind::<Array<Nullable<Float8>>, _>(rows.iter().map(|r| r.wind_speed_ms).collect::<Vec<_>>())
+    .bind::<Array<Text>, _>(rows.iter().map(|r| r.condition).collect::<Vec<_>>())
+    .bind::<Array<Text>, _>(rows.iter().map(|r| r.source).collect::<Vec<_>>())
+    .execute(conn)
+}
+
+/// Volume, volume horaire, durée moyenne et taux de pourboire par condition
+/// météo ou tranche de température (`band_celsius` degrés) de l'heure de
+/// prise en charge ; `unknown` regroupe les heures sans observation.
+pub fn weather_breakdown(
+    conn: &mut PgConnection,
+    filters: &TripFilters,
+    dimension: WeatherDimension,
+    band_celsius: f64,
+) -> QueryResult<Vec<WeatherBreakdown>> {
+    let mut builder = SqlBuilder::default();
+    let from_where = filters::from_where(filters, &mut builder, &[]);
+    let (key, from, order) = match dimension {
+        WeatherDimension::Condition => {
+            let conditions = builder.bind(Bind::TextArray(CONDITIONS.iter().map(|c| c.to_string()).collect()));
+            let key = "COALESCE(w.condition, 'unknown')";
+            (key.to_string(), "NULL::float8".to_string(), format!("array_position({conditions}::text[], {key}), 1"))
+        }
+        WeatherDimension::TemperatureBand => {
+            let width = builder.bind(Bind::Text(band_celsius.to_string()));
+            let from = format!("FLOOR(w.temperature_c / {width}::float8) * {width}::float8");
+            (
+                format!("COALESCE(({from})::text || '..' || ({from} + {width}::float8)::text, 'unknown')"),
+                from,
+                "temperature_from NULLS LAST".to_string(),
+            )
+        }
+    };
+    builder.sql = format!(
+        "WITH trips AS ( \
+            SELECT \
+                f.tpep_pickup_datetime AS pickup, \
+                EXTRACT(EPOCH FROM f.trip_duration) AS duration, \
+                f.tip_amount AS tip, \
+                f.fare_amount AS fare \
+            {from_where} AND f.tpep_pickup_datetime IS NOT NULL \
+        ) \
+        SELECT \
+            {key} AS key, \
+            ({from})::float8 AS temperature_from, \
+            COUNT(*)::bigint AS trip_count, \
+            COUNT(DISTINCT date_trunc('hour', t.pickup))::bigint AS hours, \
+            (COUNT(*)::float8 / COUNT(DISTINCT date_trunc('hour', t.pickup)))::float8 AS trips_per_hour, \
+            (AVG(t.duration) FILTER (WHERE t.duration > 0))::float8 AS avg_duration_seconds, \
+            (SUM(t.tip) / NULLIF(SUM(t.fare), 0))::float8 AS tip_rate \
+        FROM trips t \
+        LEFT JOIN dim_weather w \
+            ON w.weather_date = t.pickup::date AND w.hour = EXTRACT(HOUR FROM t.pickup)::int \
+        GROUP BY 1, 2 \
+        ORDER BY {order}"
+    );
+    builder.into_query().load::<WeatherBreakdown>(conn)
+}
+
+// Handler pour la ventilation des trajets selon la météo
+#[utoipa::path(
+    get,
+    path = "/api/v1/weather/breakdown",
+    tag = "analytics",
+    params(TripFilters, WeatherBreakdownParams),
+    responses(
+        (status = 200, description = "Volume, durée moyenne et taux de pourboire par condition météo ou tranche de température", body = Vec<WeatherBreakdown>),
+        (status = 400, description = "Paramètres invalides", body = String),
+        (status = 500, description = "Erreur base de données", body = String)
+    )
+)]
+pub async fn get_weather_breakdown(
+    State(pool): State<DbPool>,
+    Query(filters): Query<TripFilters>,
+    Query(params): Query<WeatherBreakdownParams>,
+) -> Result<Json<Vec<WeatherBreakdown>>, AppError> {
+    filters::validate(&filters)?;
+    let band_celsius = params.band_celsius.unwrap_or(DEFAULT_BAND_CELSIUS);
+    if !(0.5..=20.0).contains(&band_celsius) {
+        return Err(AppError::bad_request("band_celsius entre 0.5 et 20 requis"));
+    }
+    let dimension = params.by.unwrap_or_default();
+    let mut conn = pool.get().map_err(|e| AppError(anyhow::Error::new(e)))?;
+    let results =
+        tokio::task::spawn_blocking(move || weather_breakdown(&mut conn, &filters, dimension, band_celsius)).await??;
+    Ok(Json(results))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(csv: &str) -> (Columns, Vec<csv::StringRecord>) {
+        let mut reader = csv::Reader::from_reader(csv.as_bytes());
+        let columns = Columns(reader.headers().unwrap().iter().map(str::to_string).collect());
+        (columns, reader.records().map(Result::unwrap).collect())
+    }
+
+    fn datetime(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn noaa_values() {
+        assert_eq!(value(Some("T")), Some(0.0));
+        assert_eq!(value(Some(" 0.25 ")), Some(0.25));
+        assert_eq!(value(Some("0.12s")), Some(0.12));
+        assert_eq!(value(Some("10V")), Some(10.0));
+        assert_eq!(value(Some("45*")), Some(45.0));
+        assert_eq!(value(Some("M")), None);
+        assert_eq!(value(None), None);
+    }
+
+    #[test]
+    fn conditions() {
+        assert_eq!(condition(Some(12.0), false, false, DAILY_RAIN_MM, false), "heavy_rain");
+        assert_eq!(condition(Some(2.0), false, false, DAILY_RAIN_MM, false), "rain");
+        // Sous le seuil journalier de pluie mesurable
+        assert_eq!(condition(Some(0.5), false, false, DAILY_RAIN_MM, false), "dry");
+        assert_eq!(condition(Some(0.5), false, false, HOURLY_RAIN_MM, true), "rain");
+        assert_eq!(condition(Some(20.0), true, false, DAILY_RAIN_MM, false), "snow");
+        assert_eq!(condition(Some(0.0), false, true, DAILY_RAIN_MM, false), "fog");
+        assert_eq!(condition(None, false, false, HOURLY_RAIN_MM, true), "dry");
+        assert_eq!(condition(None, false, false, DAILY_RAIN_MM, false), "unknown");
+    }
+
+    #[test]
+    fn daily_summaries() {
+        let (columns, records) = parse(
+            "STATION,DATE,AWND,PRCP,SNOW,TAVG,TMAX,TMIN,WT01\n\
+             USW00094728,2024-01-09,10.0,1.2,0.0,,50,40,\n\
+             USW00094728,2024-01-16,5.0,T,1.5,32,,,\n\
+             USW00094728,2024-01-20,2.0,0.00,0.0,41,50,30,1\n",
+        );
+        let rows = parse_daily(&columns, records.into_iter(), Units::Standard).unwrap();
+        assert_eq!(rows.len(), 3 * 24);
+        assert!(rows[..24].iter().enumerate().all(|(h, r)| r.hour == h as i32 && r.date == rows[0].date));
+
+        let rainy = &rows[0];
+        // TAVG absent : moyenne de TMAX et TMIN (45 °F)
+        assert!((rainy.temperature_c.unwrap() - 7.222).abs() < 1e-3);
+        assert!((rainy.precipitation_mm.unwrap() - 30.48).abs() < 1e-9);
+        assert!((rainy.wind_speed_ms.unwrap() - 4.4704).abs() < 1e-9);
+        assert_eq!((rainy.condition, rainy.source, rainy.station.as_str()), ("heavy_rain", "daily", "USW00094728"));
+
+        let snowy = &rows[24];
+        assert_eq!(snowy.precipitation_mm, Some(0.0));
+        assert_eq!(snowy.temperature_c, Some(0.0));
+        assert_eq!(snowy.condition, "snow");
+        assert_eq!(rows[48].condition, "fog");
+    }
+
+    #[test]
+    fn daily_metric_units() {
+        let (columns, records) = parse("DATE,PRCP,TAVG\n2024-07-01,3.0,25.0\n");
+        let rows = parse_daily(&columns, records.into_iter(), Units::Metric).unwrap();
+        assert_eq!((rows[0].precipitation_mm, rows[0].temperature_c), (Some(3.0), Some(25.0)));
+        assert_eq!(rows[0].condition, "rain");
+    }
+
+    #[test]
+    fn hourly_readings() {
+        let (columns, records) = parse(
+            "STATION,DATE,REPORT_TYPE,HourlyDryBulbTemperature,HourlyPrecipitation,HourlyPresentWeatherType,HourlyVisibility,HourlyWindSpeed\n\
+             72505394728,2024-01-10T05:51:00,FM-15,40,0.02,-RA:02 BR:1 |RA |,5.00,10\n\
+             72505394728,2024-01-10T05:20:00,FM-16,42,0.01,,,6\n\
+             72505394728,2024-01-10T06:51:00,FM-15,38s,T,DZ:01 |DZ |,10.00,\n\
+             72505394728,2024-01-10T07:51:00,FM-15,36,,BR:1 FG:2 |FG |,0.25,3\n\
+             72505394728,2024-01-10T23:59:00,SOD,,,,,\n",
+        );
+        let rows = parse_hourly(&columns, records.into_iter()).unwrap();
+        // Ligne SOD ignorée
+        assert_eq!(rows.len(), 3);
+
+        let first = &rows[0];
+        assert_eq!((first.date, first.hour), (NaiveDate::from_ymd_opt(2024, 1, 10).unwrap(), 5));
+        assert!((first.temperature_c.unwrap() - 5.0).abs() < 1e-9);
+        assert!((first.precipitation_mm.unwrap() - 0.508).abs() < 1e-9);
+        assert!((first.wind_speed_ms.unwrap() - 8.0 * 0.44704).abs() < 1e-9);
+        assert_eq!((first.condition, first.source), ("rain", "hourly"));
+
+        // Bruine sans cumul mesurable : pluie
+        assert_eq!(rows[1].precipitation_mm, Some(0.0));
+        assert_eq!(rows[1].condition, "rain");
+        assert_eq!(rows[1].wind_speed_ms, None);
+        assert_eq!(rows[2].condition, "fog");
+    }
+
+    #[test]
+    fn hourly_timestamps_follow_daylight_saving() {
+        let (columns, records) = parse(
+            "DATE,HourlyDryBulbTemperature\n\
+             2024-07-01T14:51:00,80\n\
+             2024-03-10T01:51:00,35\n\
+             2024-03-10T02:51:00,36\n",
+        );
+        let rows = parse_hourly(&columns, records.into_iter()).unwrap();
+        let hours: Vec<(u32, i32)> = rows.iter().map(|r| (r.date.day(), r.hour)).collect();
+        assert_eq!(hours, vec![(10, 1), (10, 3), (1, 15)]);
+    }
+
+    #[test]
+    fn local_standard_time() {
+        let convert = |s| local_standard_to_new_york(datetime(s));
+        assert_eq!(convert("2024-01-15T12:00:00"), datetime("2024-01-15T12:00:00"));
+        assert_eq!(convert("2024-03-10T01:59:00"), datetime("2024-03-10T01:59:00"));
+        assert_eq!(convert("2024-03-10T02:00:00"), datetime("2024-03-10T03:00:00"));
+        assert_eq!(convert("2024-07-04T23:30:00"), datetime("2024-07-05T00:30:00"));
+        // Retour à l'heure d'hiver : 1 h heure d'été = 0 h LST
+        assert_eq!(convert("2024-11-03T00:30:00"), datetime("2024-11-03T01:30:00"));
+        assert_eq!(convert("2024-11-03T01:30:00"), datetime("2024-11-03T01:30:00"));
+        // Règle antérieure à 2007 : premier dimanche d'avril, dernier d'octobre
+        assert_eq!(convert("2006-04-02T02:00:00"), datetime("2006-04-02T03:00:00"));
+        assert_eq!(convert("2006-10-29T00:30:00"), datetime("2006-10-29T01:30:00"));
+        assert_eq!(convert("2006-10-29T01:00:00"), datetime("2006-10-29T01:00:00"));
+    }
+}
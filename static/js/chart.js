@@ -2,6 +2,7 @@
 const appState = {
     rawData: [],
     filteredData: [],
+    anomalies: [], // Jours anormaux (volume de trajets), marqués sur le graphique de tendance
     charts: {},
     dataTable: null,
     selectedYears: ['2024'], // Tableau pour stocker les années sélectionnées
@@ -15,6 +16,7 @@ async function initDashboard() {
     
     // Charger les données
     await fetchData();
+    await fetchAnomalies();
     
     // Mettre à jour les visualisations
     updateDashboard();
@@ -130,7 +132,7 @@ function activateSection(sectionId) {
 // Récupération des données depuis l'API
 async function fetchData() {
     try {
-        const response = await fetch('/api/trip_volume');
+        const response = await fetch('/api/v1/trip_volume');
         if (!response.ok) {
             throw new Error(`HTTP error! status: ${response.status}`);
         }
@@ -146,6 +148,21 @@ async function fetchData() {
     }
 }
 
+// Récupération des jours anormaux (non bloquante : le graphique s'affiche sans marqueurs)
+async function fetchAnomalies() {
+    try {
+        const response = await fetch('/api/v1/anomalies');
+        if (!response.ok) {
+            throw new Error(`HTTP error! status: ${response.status}`);
+        }
+        const report = await response.json();
+        appState.anomalies = report.anomalies;
+    } catch (error) {
+        console.error("Failed to fetch anomalies:", error);
+        appState.anomalies = [];
+    }
+}
+
 // Met à jour l'ensemble du dashboard
 function updateDashboard() {
     // Filtrer les données selon l'année sélectionnée
@@ -195,7 +212,7 @@ async function updateStatCards() {
     // D'abord, récupérer les données de tendance
     let trendData = null;
     try {
-        const response = await fetch('/api/kpi_trends');
+        const response = await fetch('/api/v1/kpi_trends');
         if (response.ok) {
             trendData = await response.json();
         } else {
@@ -385,6 +402,26 @@ function updateTrendChartVisibility() {
     chart.update();
 }
 
+// Marqueurs des jours anormaux, rattachés à la période affichée (jour, semaine,
+// mois...) qui les contient. Un point par période, aligné sur `data` (y null
+// hors anomalie) pour que l'infobulle en mode 'index' reste cohérente.
+function anomalyMarkers(data) {
+    const years = appState.selectedYears.map(year => parseInt(year));
+    const markers = new Map();
+    appState.anomalies.forEach(anomaly => {
+        const day = new Date(anomaly.date);
+        if (!years.includes(day.getFullYear())) return;
+        // Dernière période commençant au plus tard le jour de l'anomalie
+        const period = data.filter(d => new Date(d.date) <= day).pop();
+        if (!period) return;
+        if (!markers.has(period.date)) {
+            markers.set(period.date, { x: period.date, y: period.trip_count, anomalies: [] });
+        }
+        markers.get(period.date).anomalies.push(anomaly);
+    });
+    return data.map(d => markers.get(d.date) || { x: d.date, y: null });
+}
+
 // Graphique de tendance temporelle (Simplifié et Stylisé)
 function createOrUpdateTrendChart(data) {
     const canvas = document.getElementById('tripVolumeChart');
@@ -415,6 +452,14 @@ function createOrUpdateTrendChart(data) {
             backgroundColor: 'rgba(255, 152, 0, 0.1)',
             yAxisID: 'yAmount',
             fill: true, tension: 0.4, borderWidth: 2.5, pointRadius: 1, pointHoverRadius: 4
+        },
+        {
+            label: 'Anomalies',
+            data: anomalyMarkers(data),
+            borderColor: '#f44336',
+            backgroundColor: '#f44336',
+            yAxisID: 'yCount',
+            showLine: false, pointStyle: 'triangle', pointRadius: 7, pointHoverRadius: 9
         }
         // Les autres métriques sont désactivées pour l'instant
     ];
@@ -494,6 +539,12 @@ function createOrUpdateTrendChart(data) {
                         const datasetLabel = context.dataset.label || '';
                         const value = context.parsed.y;
                         if (value === null) return null;
+                        if (context.raw.anomalies) {
+                            return context.raw.anomalies.map(a => {
+                                const change = a.relative_deviation === null ? '' : ` (${a.deviation > 0 ? '+' : ''}${Math.round(a.relative_deviation * 100)} %)`;
+                                return `Anomalie ${a.date}: ${formatNumber(a.value)} trajets, attendu ${formatNumber(Math.round(a.expected))}${change}`;
+                            });
+                        }
                         
                         let formattedValue = '';
                         switch(datasetLabel) {
@@ -627,7 +678,7 @@ async function createOrUpdatePaymentChart() {
 
     try {
         // Fetch data for payment analysis
-        const response = await fetch('/api/payment_analysis');
+        const response = await fetch('/api/v1/payment_analysis');
         if (!response.ok) {
             throw new Error(`HTTP error! status: ${response.status}`);
         }
@@ -782,7 +833,7 @@ async function createOrUpdateHourlyHeatmap() {
     const ctx = canvas.getContext('2d');
 
     try {
-        const response = await fetch('/api/hourly_activity');
+        const response = await fetch('/api/v1/hourly_activity');
         if (!response.ok) {
             throw new Error(`HTTP error! status: ${response.status}`);
         }
@@ -1071,7 +1122,7 @@ async function createOrUpdatePassengerChart() {
     const ctx = container.getContext('2d');
 
     try {
-        const response = await fetch('/api/passenger_analysis');
+        const response = await fetch('/api/v1/passenger_analysis');
         if (!response.ok) throw new Error(`HTTP ${response.status}`);
         let data = await response.json();
 
@@ -1151,7 +1202,7 @@ async function createOrUpdateFinancialBreakdownChart() {
     const ctx = container.getContext('2d');
 
     try {
-        const response = await fetch('/api/financial_breakdown');
+        const response = await fetch('/api/v1/financial_breakdown');
         if (!response.ok) throw new Error(`HTTP ${response.status}`);
         let data = await response.json();
 
@@ -1181,7 +1232,9 @@ async function createOrUpdateFinancialBreakdownChart() {
             'rgba(255, 159, 64, 0.7)', // Tolls
             'rgba(255, 99, 132, 0.7)', // Tax
             'rgba(153, 102, 255, 0.7)', // Surcharge
-            'rgba(201, 203, 207, 0.7)' // Extra
+            'rgba(201, 203, 207, 0.7)', // Extra
+            'rgba(255, 205, 86, 0.7)', // Congestion
+            'rgba(100, 120, 140, 0.7)' // Airport fee
         ];
         
         const datasets = [
@@ -1190,7 +1243,9 @@ async function createOrUpdateFinancialBreakdownChart() {
             { label: 'Péages', data: data.map(d => d.avg_tolls_amount), backgroundColor: financialColors[2] },
             { label: 'Taxe MTA', data: data.map(d => d.avg_mta_tax), backgroundColor: financialColors[3] },
             { label: 'Surcharge Amél.', data: data.map(d => d.avg_improvement_surcharge), backgroundColor: financialColors[4] },
-            { label: 'Extras', data: data.map(d => d.avg_extra), backgroundColor: financialColors[5] }
+            { label: 'Extras', data: data.map(d => d.avg_extra), backgroundColor: financialColors[5] },
+            { label: 'Surcharge Congestion', data: data.map(d => d.avg_congestion_surcharge), backgroundColor: financialColors[6] },
+            { label: 'Taxe Aéroport', data: data.map(d => d.avg_airport_fee), backgroundColor: financialColors[7] }
         ];
 
         const chartData = { labels: labels, datasets: datasets };
@@ -1231,7 +1286,7 @@ async function createOrUpdateVendorChart() {
     const ctx = container.getContext('2d');
 
     try {
-        const response = await fetch('/api/vendor_analysis');
+        const response = await fetch('/api/v1/vendor_analysis');
         if (!response.ok) throw new Error(`HTTP ${response.status}`);
         let data = await response.json();
 
@@ -1389,7 +1444,7 @@ async function createOrUpdateBoroughFlowTable() {
 
 async function fetchBoroughFlowData() {
     try {
-        const response = await fetch('/api/borough_flows');
+        const response = await fetch('/api/v1/borough_flows');
         if (!response.ok) {
             throw new Error(`HTTP error! status: ${response.status}`);
         }